@@ -1,9 +1,12 @@
 use anyhow::{Context, Result, bail, ensure};
 use clap::{Args, ValueHint};
 use polars::prelude::*;
+use polars::sql::sql_expr;
 
-use crate::args::OutputFormat;
-use crate::io::{read_data, write_data};
+use crate::args::GlobalArgs;
+use crate::crosstab::{label_mask, labels};
+use crate::derive::derived_column;
+use crate::io::{print_plan, resolve_columns, scan_data, write_data};
 
 #[derive(Args, Debug)]
 pub struct AggregateArgs {
@@ -19,19 +22,60 @@ pub struct AggregateArgs {
 
     /// Aggregation operations as column=operation pairs (comma separated)
     ///
-    /// Operations: sum, mean, median, min, max, range, count, variance, stddev, first, last, describe
+    /// Operations: sum, product, mean, median, min, max, range, count, nunique, `approx_nunique`,
+    /// mode, variance, stddev, sem, skew, kurtosis, first, last, describe, and wmean:<weight>
+    /// and wsum:<weight> weighted by a column
     ///
     /// Examples:
     /// - Single aggregation: --with "amount=sum"
     /// - Multiple aggregations: --with "amount=sum,price=mean,quantity=max"
     /// - Row-based operations: --with "_=count" (counts rows)
     /// - Multiple ops on same column: --with "price=min,price=max,price=mean"
+    /// - Every (numeric) column but the groups: --with "*=count" or --with "numeric()=mean"
+    /// - Weighted by another column: --with "price=wmean:quantity"
     #[arg(long, value_delimiter = ',')]
     pub with: Vec<String>,
 
+    /// New columns computed from SQL expressions before grouping, as name=expression
+    ///
+    /// Derived columns can be grouped by and aggregated like any other column, and later
+    /// ones can use earlier ones. Repeat the option for several columns.
+    /// Examples: --derive "revenue=price * quantity" --with revenue=sum
+    #[arg(long)]
+    pub derive: Vec<String>,
+
+    /// Keep only the groups matching this SQL condition on the aggregated columns
+    ///
+    /// Examples: --having "count > 10" or --having "`price_sum >= 100 AND count > 1`"
+    #[arg(long)]
+    pub having: Option<String>,
+
+    /// Column whose values spread each aggregation across columns, one per value
+    ///
+    /// Each group of --by becomes one row, with a `<value>_<aggregation>` column for each
+    /// value of the column, e.g. `--by region --spread month --with amount=sum` makes
+    /// `2024-01_amount_sum`, `2024-02_amount_sum` and so on. Groups without rows for a value
+    /// are null.
+    #[arg(long)]
+    pub spread: Option<String>,
+
+    /// Output columns to sort the groups by, with later columns breaking ties (comma separated)
+    ///
+    /// Examples: --sort-by `price_sum` or --sort-by "`count,customer_id`"
+    #[arg(long, value_delimiter = ',')]
+    pub sort_by: Vec<String>,
+
+    /// Sort the groups in descending order (largest first) with --sort-by
+    #[arg(long, requires = "sort_by")]
+    pub descending: bool,
+
     /// Delimiter for input files
     #[arg(long, default_value = ",")]
     pub delimiter: char,
+
+    /// Print the optimized aggregation plan instead of aggregating the table
+    #[arg(long)]
+    pub dry_run: bool,
 }
 
 impl AggregateArgs {
@@ -43,8 +87,29 @@ impl AggregateArgs {
 
         // Validate aggregation operations
         let valid_ops = [
-            "sum", "mean", "median", "min", "max", "range", "count", "len", "nrow",
-            "variance", "stddev", "first", "last", "describe",
+            "sum",
+            "mean",
+            "median",
+            "min",
+            "max",
+            "range",
+            "count",
+            "len",
+            "nrow",
+            "nunique",
+            "mode",
+            "variance",
+            "stddev",
+            "first",
+            "last",
+            "describe",
+            "wmean",
+            "wsum",
+            "product",
+            "sem",
+            "skew",
+            "kurtosis",
+            "approx_nunique",
         ];
 
         for spec in &self.with {
@@ -56,13 +121,21 @@ impl AggregateArgs {
             );
 
             let column = parts[0];
-            let operation = parts[1];
+            let (operation, weight) = match parts[1].split_once(':') {
+                Some((operation, weight)) => (operation, Some(weight)),
+                None => (parts[1], None),
+            };
             ensure!(
                 valid_ops.contains(&operation),
                 "Invalid operation '{}'. Valid operations: {}",
                 operation,
                 valid_ops.join(", ")
             );
+            let weighted = matches!(operation, "wmean" | "wsum");
+            ensure!(
+                weighted == weight.is_some_and(|w| !w.is_empty()),
+                "Invalid aggregation specification '{spec}'. Only wmean and wsum take a weight column, as column=wmean:weight"
+            );
 
             if column == "_" {
                 ensure!(
@@ -73,36 +146,197 @@ impl AggregateArgs {
             }
         }
 
+        if let Some(spread) = &self.spread {
+            ensure!(
+                !self.by.contains(spread),
+                "--spread '{spread}' can't also be one of --by"
+            );
+        }
+        self.derived()?;
+        self.having()?;
+
         Ok(())
     }
 
-    pub fn execute(&self, format: &OutputFormat) -> Result<()> {
-        // Read input data
-        let df = read_data(&self.table, Some(self.delimiter))
+    pub fn execute(&self, global: &GlobalArgs) -> Result<()> {
+        // Scan input data, so the streaming engine can aggregate files larger than memory
+        let mut lf = scan_data(&self.table, Some(self.delimiter), global)
             .with_context(|| format!("Failed to read data from {}", self.table))?;
+        for derived in self.derived()? {
+            lf = lf.with_column(derived);
+        }
+        let schema = lf
+            .collect_schema()
+            .with_context(|| format!("Failed to read data from {}", self.table))?;
+        let df = DataFrame::empty_with_schema(&schema);
 
-        // Parse aggregation specifications
-        let aggs = parse_aggs(&self.with)?;
+        // Resolve column names and parse aggregation specifications
+        let by = resolve_columns(&df, &self.by, global.ignore_column_case)?;
+        let spread =
+            resolve_columns(&df, self.spread.as_slice(), global.ignore_column_case)?.pop();
+        let mut groups = by.clone();
+        groups.extend(spread.clone());
+        let with = resolve_specs(&df, &self.with, &groups, global.ignore_column_case)?;
+        let aggs = parse_aggs(&with)?;
+        let labels = match &spread {
+            Some(spread) => labels(lf.clone(), spread).with_context(|| {
+                format!("Failed to read the values of {spread} in {}", self.table)
+            })?,
+            None => vec![],
+        };
 
         // Perform aggregation
-        let result: LazyFrame = if self.by.is_empty() {
-            df.lazy().select(aggs)
+        let result: LazyFrame = if groups.is_empty() {
+            // Common subexpression elimination would compute sums used twice (e.g. by wmean)
+            // as a column beside the scan, repeating every aggregate on each row
+            let optimizations =
+                lf.get_current_optimizations() - OptFlags::COMM_SUBEXPR_ELIM;
+            lf.with_optimizations(optimizations).select(aggs)
         } else {
-            let cols: Vec<_> = self.by.iter().map(std::string::String::as_str).collect();
-            df.lazy().group_by_stable(cols).agg(aggs)
+            let cols: Vec<_> = groups.iter().map(std::string::String::as_str).collect();
+            lf.group_by_stable(cols).agg(aggs)
         };
+        let mut result = match self.having()? {
+            Some(having) => result.filter(having),
+            None => result,
+        };
+        if let Some(spread) = &spread {
+            result = spread_groups(result, &by, spread, &labels)?;
+        }
+        if !self.sort_by.is_empty() {
+            let schema = result.collect_schema().with_context(|| {
+                format!("Failed to perform aggregation on {}", self.table)
+            })?;
+            let sort_by = resolve_columns(
+                &DataFrame::empty_with_schema(&schema),
+                &self.sort_by,
+                global.ignore_column_case,
+            )?;
+            result = result.sort(
+                sort_by,
+                SortMultipleOptions::default()
+                    .with_order_descending(self.descending)
+                    .with_nulls_last(true)
+                    .with_maintain_order(true),
+            );
+        }
+
+        if self.dry_run {
+            return print_plan(&result);
+        }
 
         // Write output
         write_data(
-            result.collect().with_context(|| {
-                format!("Failed to perform aggregation on {}", self.table)
-            })?,
-            format,
+            result
+                .collect_with_engine(Engine::Streaming)
+                .with_context(|| {
+                    format!("Failed to perform aggregation on {}", self.table)
+                })?,
+            global,
         )
         .with_context(|| "Failed to write aggregated data to stdout")?;
 
         Ok(())
     }
+
+    /// Filter for the `--having` condition
+    fn having(&self) -> Result<Option<Expr>> {
+        self.having
+            .as_ref()
+            .map(|having| {
+                sql_expr(having).with_context(|| format!("Invalid --having '{having}'"))
+            })
+            .transpose()
+    }
+
+    /// Expressions for the `--derive` columns, in order
+    fn derived(&self) -> Result<Vec<Expr>> {
+        self.derive
+            .iter()
+            .map(|spec| derived_column(spec, "--derive"))
+            .collect()
+    }
+}
+
+/// Resolves the column in each `column=operation` spec against the columns of `df`
+///
+/// The `*` and `numeric()` wildcards expand to one spec for each column (or each numeric
+/// column) of `df` that isn't grouped `by`, in table order.
+fn resolve_specs(
+    df: &DataFrame,
+    with_strs: &[String],
+    by: &[String],
+    ignore_case: bool,
+) -> Result<Vec<String>> {
+    let mut specs = Vec::with_capacity(with_strs.len());
+    for spec in with_strs {
+        match spec.split_once('=') {
+            Some((wildcard @ ("*" | "numeric()"), operation)) => {
+                let columns: Vec<_> = df
+                    .get_columns()
+                    .iter()
+                    .filter(|c| !by.iter().any(|b| b == c.name().as_str()))
+                    .filter(|c| wildcard == "*" || c.dtype().is_primitive_numeric())
+                    .map(|c| format!("{}={operation}", c.name()))
+                    .collect();
+                ensure!(
+                    !columns.is_empty(),
+                    "'{spec}' doesn't match any columns outside of --by"
+                );
+                specs.extend(columns);
+            }
+            Some((column, operation)) if column != "_" => {
+                let resolved = resolve_columns(df, &[column.to_string()], ignore_case)?;
+                let operation = match operation.split_once(':') {
+                    Some((operation, weight)) => {
+                        let weight =
+                            resolve_columns(df, &[weight.to_string()], ignore_case)?;
+                        format!("{operation}:{}", weight[0])
+                    }
+                    None => operation.to_string(),
+                };
+                specs.push(format!("{}={operation}", resolved[0]));
+            }
+            _ => specs.push(spec.clone()),
+        }
+    }
+
+    Ok(specs)
+}
+
+/// Spreads the aggregated `cells` of each `by` group and `spread` value into one row per
+/// group, with a `<value>_<aggregation>` column for each of the `spread` values `labels`
+fn spread_groups(
+    mut cells: LazyFrame,
+    by: &[String],
+    spread: &str,
+    labels: &[String],
+) -> Result<LazyFrame> {
+    let schema = cells.collect_schema()?;
+    let values: Vec<&str> = schema
+        .iter_names()
+        .map(PlSmallStr::as_str)
+        .filter(|name| *name != spread && !by.iter().any(|b| b == name))
+        .collect();
+    let columns: Vec<Expr> = labels
+        .iter()
+        .flat_map(|label| {
+            values.iter().map(move |value| {
+                col(*value)
+                    .filter(label_mask(spread, label))
+                    .first()
+                    .alias(format!("{label}_{value}"))
+            })
+        })
+        .collect();
+
+    Ok(if by.is_empty() {
+        cells.select(columns)
+    } else {
+        cells
+            .group_by_stable(by.iter().map(|c| col(c.as_str())).collect::<Vec<_>>())
+            .agg(columns)
+    })
 }
 
 fn parse_aggs(with_strs: &[String]) -> Result<Vec<Expr>> {
@@ -111,7 +345,7 @@ fn parse_aggs(with_strs: &[String]) -> Result<Vec<Expr>> {
     for spec in with_strs {
         let parts: Vec<&str> = spec.split('=').collect();
         let column = parts[0];
-        let operation = parts[1];
+        let (operation, weight) = parts[1].split_once(':').unwrap_or((parts[1], ""));
         let alias = format!("{column}_{operation}");
         let expr = match (column, operation) {
             ("_", "count") => len().alias("count"),
@@ -126,8 +360,41 @@ fn parse_aggs(with_strs: &[String]) -> Result<Vec<Expr>> {
             (_, "last") => col(column).last().alias(&alias),
             (_, "range") => (col(column).max() - col(column).min()).alias(&alias),
             (_, "count") | ("len" | "nrow", _) => col(column).count().alias(&alias),
+            (_, "nunique") => col(column).drop_nulls().n_unique().alias(&alias),
+            // HyperLogLog estimate, much cheaper than nunique on very large tables
+            (_, "approx_nunique") => {
+                col(column).drop_nulls().approx_n_unique().alias(&alias)
+            }
+            // Ties go to the smallest value, so the result doesn't depend on row order
+            (_, "mode") => col(column)
+                .drop_nulls()
+                .mode()
+                .sort(SortOptions::default())
+                .first()
+                .alias(&alias),
             (_, "variance") => col(column).var(1).alias(&alias), // Use sample variance (ddof=1)
             (_, "stddev") => col(column).std(1).alias(&alias), // Use sample std dev (ddof=1)
+            (_, "product") => col(column).product().alias(&alias),
+            // Standard error of the mean, from the sample std dev like stddev
+            (_, "sem") => (col(column).std(1)
+                / col(column).count().cast(DataType::Float64).sqrt())
+            .alias(&alias),
+            // Bias corrected, like the sample variance, with kurtosis as excess kurtosis
+            (_, "skew") => col(column).skew(false).alias(&alias),
+            (_, "kurtosis") => col(column).kurtosis(true, false).alias(&alias),
+            // Rows missing the value or the weight are left out of both sums
+            (_, "wsum") => (col(column) * col(weight)).sum().alias(&alias),
+            (_, "wmean") => {
+                let total = (col(column) * col(weight)).sum().cast(DataType::Float64);
+                let weights = col(weight)
+                    .filter(col(column).is_not_null())
+                    .sum()
+                    .cast(DataType::Float64);
+                when(weights.clone().neq(lit(0.0)))
+                    .then(total / weights)
+                    .otherwise(lit(NULL).cast(DataType::Float64))
+                    .alias(&alias)
+            }
             (_, "describe") => {
                 // For describe, we'll create a concatenated string of statistics
                 // This is a simplified version - in a real implementation you might want
@@ -162,13 +429,100 @@ fn parse_aggs(with_strs: &[String]) -> Result<Vec<Expr>> {
 mod tests {
     use super::*;
 
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn test_resolve_specs_wildcards() {
+        let df = df!(
+            "region" => ["north"],
+            "Amount" => [1.0],
+            "status" => ["ok"],
+            "quantity" => [2],
+        )
+        .unwrap();
+        let specs = |with: &[&str]| {
+            let with: Vec<String> = with.iter().map(ToString::to_string).collect();
+            resolve_specs(&df, &with, &["region".to_string()], true)
+        };
+
+        assert_eq!(
+            specs(&["numeric()=mean", "_=count"]).unwrap(),
+            ["Amount=mean", "quantity=mean", "_=count"]
+        );
+        assert_eq!(
+            specs(&["*=first", "amount=max"]).unwrap(),
+            [
+                "Amount=first",
+                "status=first",
+                "quantity=first",
+                "Amount=max"
+            ]
+        );
+        assert!(
+            resolve_specs(
+                &df.select(["region", "status"]).unwrap(),
+                &["numeric()=sum".to_string()],
+                &[],
+                false
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn test_spread_groups() {
+        let cells = df!(
+            "region" => ["north", "north", "south"],
+            "month" => [Some("jan"), None, Some("feb")],
+            "amount_sum" => [1.0, 2.0, 4.0],
+        )
+        .unwrap();
+        let by = ["region".to_string()];
+        let labels = labels(cells.clone().lazy(), "month").unwrap();
+
+        let wide = spread_groups(cells.clone().lazy(), &by, "month", &labels)
+            .unwrap()
+            .collect()
+            .unwrap();
+        assert_eq!(
+            wide.get_column_names_str(),
+            [
+                "region",
+                "feb_amount_sum",
+                "jan_amount_sum",
+                "null_amount_sum"
+            ]
+        );
+        let jan: Vec<Option<f64>> = wide
+            .column("jan_amount_sum")
+            .unwrap()
+            .f64()
+            .unwrap()
+            .into_iter()
+            .collect();
+        assert_eq!(jan, [Some(1.0), None]);
+
+        let cells = cells.drop("region").unwrap();
+        let total = spread_groups(cells.lazy(), &[], "month", &labels)
+            .unwrap()
+            .collect()
+            .unwrap();
+        assert_eq!(total.shape(), (1, 3));
+    }
+
     #[test]
     fn test_validate_no_operations() {
         let args = AggregateArgs {
             table: "test.csv".to_string(),
             by: vec![],
             with: vec![],
+            derive: vec![],
+            having: None,
+            spread: None,
+            sort_by: vec![],
+            descending: false,
             delimiter: ',',
+            dry_run: false,
         };
         assert!(args.validate().is_err());
     }
@@ -179,9 +533,21 @@ mod tests {
             table: "test.csv".to_string(),
             by: vec![],
             with: vec!["col=invalid".to_string()],
+            derive: vec![],
+            having: None,
+            spread: None,
+            sort_by: vec![],
+            descending: false,
             delimiter: ',',
+            dry_run: false,
         };
         assert!(args.validate().is_err());
+
+        let mut args = args;
+        for with in ["price=wmean", "price=mean:quantity", "price=wsum:"] {
+            args.with = vec![with.to_string()];
+            assert!(args.validate().is_err(), "{with}");
+        }
     }
 
     #[test]
@@ -190,7 +556,13 @@ mod tests {
             table: "test.csv".to_string(),
             by: vec!["group".to_string()],
             with: vec!["value=sum".to_string(), "count=count".to_string()],
+            derive: vec![],
+            having: None,
+            spread: None,
+            sort_by: vec![],
+            descending: false,
             delimiter: ',',
+            dry_run: false,
         };
         assert!(args.validate().is_ok());
     }
@@ -201,7 +573,13 @@ mod tests {
             table: "test.csv".to_string(),
             by: vec!["group".to_string()],
             with: vec!["_=count".to_string()],
+            derive: vec![],
+            having: None,
+            spread: None,
+            sort_by: vec![],
+            descending: false,
             delimiter: ',',
+            dry_run: false,
         };
         assert!(args.validate().is_ok());
     }
@@ -212,7 +590,13 @@ mod tests {
             table: "test.csv".to_string(),
             by: vec![],
             with: vec!["_=mean".to_string()],
+            derive: vec![],
+            having: None,
+            spread: None,
+            sort_by: vec![],
+            descending: false,
             delimiter: ',',
+            dry_run: false,
         };
         assert!(args.validate().is_err());
     }
@@ -223,8 +607,197 @@ mod tests {
             table: "test.csv".to_string(),
             by: vec!["group".to_string()],
             with: vec!["value=first".to_string(), "other=last".to_string()],
+            derive: vec![],
+            having: None,
+            spread: None,
+            sort_by: vec![],
+            descending: false,
+            delimiter: ',',
+            dry_run: false,
+        };
+        assert!(args.validate().is_ok());
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn test_nunique_and_mode() {
+        let df = df!(
+            "region" => ["north", "north", "north", "south", "south"],
+            "customer" => [Some("a"), Some("b"), Some("a"), Some("c"), None],
+            "size" => [2, 1, 1, 3, 2],
+        )
+        .unwrap();
+        let aggs = parse_aggs(&[
+            "customer=nunique".to_string(),
+            "size=mode".to_string(),
+            "customer=approx_nunique".to_string(),
+        ])
+        .unwrap();
+
+        let result = df
+            .lazy()
+            .group_by_stable([col("region")])
+            .agg(aggs)
+            .collect()
+            .unwrap();
+        let distinct: Vec<_> = result
+            .column("customer_nunique")
+            .unwrap()
+            .cast(&DataType::Int64)
+            .unwrap()
+            .i64()
+            .unwrap()
+            .to_vec();
+        assert_eq!(distinct, [Some(2), Some(1)]);
+        let approx: Vec<_> = result
+            .column("customer_approx_nunique")
+            .unwrap()
+            .cast(&DataType::Int64)
+            .unwrap()
+            .i64()
+            .unwrap()
+            .to_vec();
+        assert_eq!(approx, distinct);
+        assert_eq!(
+            result.column("size_mode").unwrap().i32().unwrap().to_vec(),
+            [Some(1), Some(2)]
+        );
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn test_weighted() {
+        let df = df!(
+            "region" => ["north", "north", "north", "south"],
+            "price" => [Some(10.0), Some(20.0), None, Some(5.0)],
+            "quantity" => [Some(1), Some(3), Some(4), None],
+        )
+        .unwrap();
+        let aggs = parse_aggs(&[
+            "price=wmean:quantity".to_string(),
+            "price=wsum:quantity".to_string(),
+        ])
+        .unwrap();
+
+        let result = df
+            .lazy()
+            .group_by_stable([col("region")])
+            .agg(aggs)
+            .collect()
+            .unwrap();
+        assert_eq!(
+            result
+                .column("price_wmean")
+                .unwrap()
+                .f64()
+                .unwrap()
+                .to_vec(),
+            [Some(17.5), None]
+        );
+        assert_eq!(
+            result.column("price_wsum").unwrap().f64().unwrap().to_vec(),
+            [Some(70.0), Some(0.0)]
+        );
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn test_moments() {
+        let df =
+            df!("size" => [Some(1), Some(2), None, Some(3), Some(4), Some(10)]).unwrap();
+        let aggs = parse_aggs(&[
+            "size=product".to_string(),
+            "size=sem".to_string(),
+            "size=skew".to_string(),
+            "size=kurtosis".to_string(),
+        ])
+        .unwrap();
+
+        let result = df.lazy().select(aggs).collect().unwrap();
+        let value = |name: &str| {
+            result
+                .column(name)
+                .unwrap()
+                .cast(&DataType::Float64)
+                .unwrap()
+                .f64()
+                .unwrap()
+                .get(0)
+                .unwrap()
+        };
+        assert!((value("size_product") - 240.0).abs() < 1e-9);
+        assert!((value("size_sem") - 1.581_138_830_084_19).abs() < 1e-9);
+        assert!((value("size_skew") - 1.697_056_274_847_714).abs() < 1e-9);
+        assert!((value("size_kurtosis") - 3.152).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_validate_derive() {
+        let mut args = AggregateArgs {
+            table: "data/orders/orders.csv".to_string(),
+            by: vec!["customer_id".to_string()],
+            with: vec!["revenue=sum".to_string()],
+            derive: vec!["revenue=price * quantity".to_string()],
+            having: None,
+            spread: None,
+            sort_by: vec![],
+            descending: false,
+            delimiter: ',',
+            dry_run: false,
+        };
+        assert!(args.validate().is_ok());
+        assert!(args.execute(&GlobalArgs::default()).is_ok());
+
+        args.derive = vec!["price * quantity".to_string()];
+        assert!(args.validate().is_err());
+
+        args.derive = vec!["revenue=price *".to_string()];
+        assert!(args.validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_having() {
+        let mut args = AggregateArgs {
+            table: "data/orders/orders.csv".to_string(),
+            by: vec!["customer_id".to_string()],
+            with: vec!["_=count".to_string()],
+            derive: vec![],
+            having: Some("count > 1".to_string()),
+            spread: None,
+            sort_by: vec![],
+            descending: false,
+            delimiter: ',',
+            dry_run: false,
+        };
+        assert!(args.validate().is_ok());
+        assert!(args.execute(&GlobalArgs::default()).is_ok());
+
+        args.having = Some("count >".to_string());
+        assert!(args.validate().is_err());
+    }
+
+    #[test]
+    fn test_sort_by() {
+        let mut args = AggregateArgs {
+            table: "data/orders/orders.csv".to_string(),
+            by: vec!["customer_id".to_string()],
+            with: vec!["price=sum".to_string()],
+            derive: vec![],
+            having: None,
+            spread: None,
+            sort_by: vec!["PRICE_SUM".to_string()],
+            descending: true,
             delimiter: ',',
+            dry_run: false,
+        };
+        let global = GlobalArgs {
+            ignore_column_case: true,
+            ..GlobalArgs::default()
         };
         assert!(args.validate().is_ok());
+        assert!(args.execute(&global).is_ok());
+
+        args.sort_by = vec!["total".to_string()];
+        assert!(args.execute(&global).is_err());
     }
 }
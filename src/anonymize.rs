@@ -0,0 +1,253 @@
+// Pseudonymize personal data columns by hashing, masking or faking their values.
+use anyhow::{Context, Result, ensure};
+use clap::{Args, ValueEnum, ValueHint};
+use itertools::Itertools;
+use log::warn;
+use polars::prelude::*;
+use sha2::{Digest, Sha256};
+use std::fmt::Write;
+
+use crate::args::GlobalArgs;
+use crate::io::{read_data, resolve_columns, write_data};
+
+/// Number of trailing characters --method mask leaves visible
+const VISIBLE: usize = 4;
+
+#[derive(Debug, Clone, Copy, ValueEnum, PartialEq, Eq)]
+pub enum Method {
+    /// Hex SHA-256 digest of the salted value, so equal values still join
+    Sha256,
+    /// Replace all but the last 4 characters with `*`
+    Mask,
+    /// Replace each letter and digit with another, keeping the shape of the value
+    Fake,
+}
+
+#[derive(Args, Debug)]
+pub struct AnonymizeArgs {
+    /// Input table (file or '-' for stdin)
+    #[arg(required = true, value_hint = ValueHint::FilePath)]
+    pub table: String,
+
+    /// Columns to anonymize (comma separated)
+    ///
+    /// Examples: --columns email or --columns "email,ssn"
+    #[arg(long, required = true, value_delimiter = ',')]
+    pub columns: Vec<String>,
+
+    /// How to replace the values
+    ///
+    /// sha256 and fake give the same output for the same value and salt, so anonymized
+    /// tables still join on those columns. mask loses that.
+    #[arg(long, value_enum, default_value = "sha256")]
+    pub method: Method,
+
+    /// Secret mixed into sha256 and fake values, so they can't be reversed by hashing
+    /// guesses. Use the same salt for tables which need to join.
+    #[arg(long)]
+    pub salt: Option<String>,
+
+    /// Delimiter for input files
+    #[arg(long, default_value = ",")]
+    pub delimiter: char,
+}
+
+impl AnonymizeArgs {
+    pub fn validate(&self) -> Result<()> {
+        ensure!(
+            self.columns.iter().all_unique(),
+            "--columns can't repeat a column"
+        );
+        ensure!(
+            self.salt.is_none() || self.method != Method::Mask,
+            "--salt has no effect with --method mask"
+        );
+
+        Ok(())
+    }
+
+    pub fn execute(&self, global: &GlobalArgs) -> Result<()> {
+        let df =
+            read_data(&self.table, Some(self.delimiter), global).with_context(|| {
+                format!("anonymize - failed to read csv data from {}", self.table)
+            })?;
+
+        let columns = resolve_columns(&df, &self.columns, global.ignore_column_case)?;
+        if self.salt.is_none() && self.method != Method::Mask {
+            warn!(
+                "anonymizing without --salt, so common values can be recovered by hashing guesses"
+            );
+        }
+        let result = self
+            .anonymize(df, &columns)
+            .with_context(|| format!("anonymize - failed to anonymize {}", self.table))?;
+
+        write_data(result, global)
+            .with_context(|| "anonymize - failed to write data to stdout".to_string())?;
+
+        Ok(())
+    }
+
+    /// Replaces the values of `columns` as text, leaving nulls as they are
+    fn anonymize(&self, mut df: DataFrame, columns: &[String]) -> Result<DataFrame> {
+        let salt = self.salt.as_deref().unwrap_or_default().as_bytes();
+        for name in columns {
+            let text = df.column(name)?.cast(&DataType::String)?;
+            let values: StringChunked = text
+                .str()?
+                .into_iter()
+                .map(|value| {
+                    value.map(|value| match self.method {
+                        Method::Sha256 => hex(&digest(salt, value, 0)),
+                        Method::Mask => mask(value),
+                        Method::Fake => fake(salt, value),
+                    })
+                })
+                .collect();
+            df.with_column(values.with_name(name.as_str().into()).into_column())?;
+        }
+
+        Ok(df)
+    }
+}
+
+/// SHA-256 digest of `salt`, `value` and the `block` counter
+fn digest(salt: &[u8], value: &str, block: u32) -> Vec<u8> {
+    let mut hasher = Sha256::new();
+    hasher.update(salt);
+    hasher.update(value.as_bytes());
+    if block > 0 {
+        hasher.update(block.to_le_bytes());
+    }
+    hasher.finalize().to_vec()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().fold(String::new(), |mut hex, byte| {
+        let _ = write!(hex, "{byte:02x}");
+        hex
+    })
+}
+
+/// `value` with all but its last few characters replaced by `*`, or all of them if it's short
+fn mask(value: &str) -> String {
+    let len = value.chars().count();
+    let hidden = if len > VISIBLE { len - VISIBLE } else { len };
+    value
+        .chars()
+        .enumerate()
+        .map(|(idx, c)| if idx < hidden { '*' } else { c })
+        .collect()
+}
+
+/// `value` with each ASCII letter and digit swapped for one picked by the salted hash of the
+/// whole value, so `123-45-6789` becomes another `ddd-dd-dddd` and emails keep their `@`
+fn fake(salt: &[u8], value: &str) -> String {
+    let mut stream = (0..).flat_map(|block| digest(salt, value, block));
+    value
+        .chars()
+        .map(|c| {
+            let byte = if c.is_ascii_alphanumeric() {
+                stream.next().unwrap_or_default()
+            } else {
+                0
+            };
+            if c.is_ascii_digit() {
+                char::from(b'0' + byte % 10)
+            } else if c.is_ascii_lowercase() {
+                char::from(b'a' + byte % 26)
+            } else if c.is_ascii_uppercase() {
+                char::from(b'A' + byte % 26)
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn args(method: Method, salt: Option<&str>) -> AnonymizeArgs {
+        AnonymizeArgs {
+            table: "data/orders/customers.csv".to_string(),
+            columns: vec!["customer_email".to_string()],
+            method,
+            salt: salt.map(str::to_string),
+            delimiter: ',',
+        }
+    }
+
+    fn values(args: &AnonymizeArgs) -> Vec<Option<String>> {
+        let df = df!(
+            "email" => [Some("ann@example.com"), None, Some("ann@example.com")],
+        )
+        .unwrap();
+        args.anonymize(df, &["email".to_string()])
+            .unwrap()
+            .column("email")
+            .unwrap()
+            .str()
+            .unwrap()
+            .into_iter()
+            .map(|v| v.map(str::to_string))
+            .collect()
+    }
+
+    #[test]
+    fn test_sha256() {
+        let hashed = values(&args(Method::Sha256, None));
+        assert_eq!(
+            hashed[0].as_deref(),
+            Some("71d4f55f72fa128dfb468a1a3901507c804b74316488744d769d7f4b16696476")
+        );
+        assert_eq!(hashed[1], None);
+        assert_eq!(hashed[0], hashed[2]);
+
+        let salted = values(&args(Method::Sha256, Some("secret")));
+        assert_ne!(salted[0], hashed[0]);
+    }
+
+    #[test]
+    fn test_mask() {
+        assert_eq!(mask("123-45-6789"), "*******6789");
+        assert_eq!(mask("abc"), "***");
+        assert_eq!(
+            values(&args(Method::Mask, None))[0].as_deref(),
+            Some("***********.com")
+        );
+    }
+
+    #[test]
+    fn test_fake() {
+        let faked = fake(b"secret", "123-45-6789");
+        assert_eq!(faked.len(), 11);
+        assert_eq!(&faked[3..4], "-");
+        assert!(
+            faked
+                .chars()
+                .filter(|c| *c != '-')
+                .all(|c| c.is_ascii_digit())
+        );
+        assert_eq!(faked, fake(b"secret", "123-45-6789"));
+        assert_ne!(faked, fake(b"other", "123-45-6789"));
+
+        let long = "x".repeat(100);
+        assert_eq!(fake(b"", &long).len(), 100);
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(args(Method::Fake, Some("secret")).validate().is_ok());
+        assert!(args(Method::Mask, Some("secret")).validate().is_err());
+    }
+
+    #[test]
+    fn test_anonymize_customers_csv() {
+        let args = args(Method::Fake, Some("secret"));
+        assert!(args.validate().is_ok());
+        assert!(args.execute(&GlobalArgs::default()).is_ok());
+    }
+}
@@ -1,33 +1,437 @@
 use anyhow::Result;
-use clap::{CommandFactory, Parser, ValueEnum};
+use clap::{ArgAction, CommandFactory, Parser, ValueEnum, ValueHint};
 use clap_complete::{Shell, generate};
 
 use crate::aggregate::AggregateArgs;
+use crate::anonymize::AnonymizeArgs;
+use crate::bench::BenchArgs;
+use crate::bin::BinArgs;
 use crate::cat::CatArgs;
+use crate::clean::CleanArgs;
+use crate::complete::{CompleteArgs, dynamic_script};
+use crate::concat::ConcatArgs;
+use crate::corr::CorrArgs;
+use crate::crosstab::CrosstabArgs;
+use crate::dates::DatesArgs;
+use crate::ddl::DdlArgs;
+use crate::demo::DemoArgs;
+use crate::derive::DeriveArgs;
+use crate::diff::DiffArgs;
+use crate::exit::Failure;
+use crate::explode::ExplodeArgs;
+use crate::extract::ExtractArgs;
+use crate::freq::FreqArgs;
+use crate::grep::GrepArgs;
 use crate::head::HeadArgs;
+use crate::hist::HistArgs;
 use crate::join::JoinArgs;
+use crate::keys::KeysArgs;
+use crate::partition::PartitionArgs;
+use crate::profile::ProfileArgs;
 use crate::query::QueryArgs;
+use crate::rank::RankArgs;
+use crate::rolling::RollingArgs;
+use crate::shift::ShiftArgs;
+use crate::split::SplitArgs;
+use crate::sqlite::FromSqliteArgs;
+use crate::suggest_types::SuggestTypesArgs;
 use crate::tail::TailArgs;
+use crate::timings::parse_size;
+use crate::top::TopArgs;
+use crate::unpack::UnpackArgs;
+use crate::validate::ValidateArgs;
+use crate::window::WindowArgs;
 
-#[derive(ValueEnum, Debug, Clone)]
+#[derive(ValueEnum, Debug, Clone, Default)]
 pub enum OutputFormat {
     /// Automatically detect based on terminal (default)
+    #[default]
     Auto,
     /// Table format output
     Table,
     /// CSV format output
     Csv,
+    /// Table in a sqlite database (requires --output)
+    Sqlite,
+}
+
+/// Borders and separators drawn around table output
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TableStyle {
+    /// Box drawing borders with rounded corners (default)
+    Rounded,
+    /// Borders drawn with +, - and |
+    Ascii,
+    /// A Markdown table, for pasting into issues and docs
+    Markdown,
+    /// Columns separated by spaces, without borders
+    Minimal,
+    /// Columns separated by |, with a line under the header, like psql
+    Psql,
+}
+
+/// Bundled CSV conventions for matching another system's flavor of CSV
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dialect {
+    /// Comma separated, quoted where necessary, CRLF line endings
+    Excel,
+    /// Tab separated, quoted where necessary, CRLF line endings
+    ExcelTab,
+    /// Comma separated, every field quoted, LF line endings
+    Unix,
+    /// Tab separated, unquoted, `\N` for nulls, LF line endings (Postgres `COPY` text format)
+    PostgresCopy,
+}
+
+/// Character encoding of the input tables, which are transcoded to UTF-8 when read
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Encoding {
+    /// UTF-8 (default)
+    #[default]
+    #[value(name = "utf-8", alias = "utf8")]
+    Utf8,
+    /// ISO-8859-1, common in older European exports
+    #[value(alias = "iso-8859-1")]
+    Latin1,
+    /// Windows-1252, Latin-1 plus characters such as € and curly quotes (Excel and ERP exports)
+    #[value(name = "windows-1252", alias = "cp1252")]
+    Windows1252,
+    /// UTF-16, little endian unless the file starts with a big endian byte order mark
+    #[value(name = "utf-16", alias = "utf16")]
+    Utf16,
+}
+
+/// Format of the input tables
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputFormat {
+    /// Delimited text, including printed tables and fixed-width text
+    Csv,
+    /// Arrow IPC files (`.arrow`, `.feather` or `.ipc`), which keep the column types
+    #[value(alias = "arrow", alias = "feather")]
+    Ipc,
+    /// Parquet files (`.parquet` or `.pq`), or directories of them such as hive
+    /// partitioned datasets (`date=2024-01-01/part-0.parquet`)
+    Parquet,
+    /// A JSON array of objects (`.json`), one per row
+    Json,
+    /// Newline delimited JSON (`.ndjson` or `.jsonl`), one object per row
+    #[value(name = "ndjson", alias = "jsonl")]
+    NdJson,
+}
+
+/// What to do with input rows whose number of fields differs from the header's
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BadLines {
+    /// Fail, naming the first bad line
+    Error,
+    /// Drop bad lines, printing how many were dropped
+    Skip,
+    /// Drop bad lines, printing each one and how many were dropped
+    Warn,
+}
+
+/// When fields are quoted in CSV output
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Quoting {
+    /// Quote every field
+    Always,
+    /// Quote fields containing the delimiter, quote character or line breaks
+    Necessary,
+    /// Never quote fields, even if the output becomes ambiguous
+    Never,
 }
 
 #[derive(Parser, Debug)]
 #[command(name = "rabbet", about = "User-friendly CLI tool for joining tables")]
 pub struct Args {
+    #[command(flatten)]
+    pub global: GlobalArgs,
+
+    #[command(subcommand)]
+    command: Commands,
+}
+
+/// Options shared by every subcommand
+#[derive(clap::Args, Debug, Clone, Default)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct GlobalArgs {
     /// Output format
     #[arg(long, value_enum, default_value = "auto", global = true)]
     pub format: OutputFormat,
 
-    #[command(subcommand)]
-    command: Commands,
+    /// Write the output to this file instead of stdout (the database for --format sqlite)
+    #[arg(long, global = true, required_if_eq("format", "sqlite"), value_hint = ValueHint::FilePath)]
+    pub output: Option<String>,
+
+    /// Table to create when writing with --format sqlite (replaced if it exists)
+    #[arg(long, default_value = "result", global = true)]
+    pub output_table: String,
+
+    /// Add the rows to the existing --output instead of replacing it
+    ///
+    /// CSV files keep their header, directories get another Parquet file of the dataset and
+    /// sqlite tables get more rows.
+    #[arg(long, requires = "output", global = true)]
+    pub append: bool,
+
+    /// Copy the output to the clipboard as tab separated text, for pasting into a spreadsheet
+    #[arg(long, global = true, conflicts_with_all = ["output", "append"])]
+    pub to_clipboard: bool,
+
+    /// CSV dialect preset for reading and writing (overrides --delimiter)
+    #[arg(long, value_enum, global = true)]
+    pub dialect: Option<Dialect>,
+
+    /// Field delimiter for CSV output (overrides --dialect)
+    ///
+    /// Examples: --output-delimiter ';' or --output-delimiter '|'
+    #[arg(long, global = true)]
+    pub output_delimiter: Option<char>,
+
+    /// When to quote fields in CSV output (overrides --dialect)
+    #[arg(long, value_enum, global = true)]
+    pub quote_style: Option<Quoting>,
+
+    /// Character used to quote fields in CSV output [default: "]
+    #[arg(long, global = true)]
+    pub quote_char: Option<char>,
+
+    /// Leave the header row out of CSV output, e.g. to append to an existing file
+    #[arg(long, global = true)]
+    pub no_output_header: bool,
+
+    /// Number of decimal places to show for floats in table and CSV output
+    ///
+    /// Examples: --float-precision 2
+    #[arg(long, global = true)]
+    pub float_precision: Option<usize>,
+
+    /// Group the digits of numbers in table output with this separator
+    ///
+    /// Examples: --thousands-sep ',' or --thousands-sep '_'
+    #[arg(long, global = true)]
+    pub thousands_sep: Option<char>,
+
+    /// Abort if any of these columns contain nulls in the output (comma separated)
+    ///
+    /// Examples: --fail-on-nulls "id" or --fail-on-nulls "id,amount"
+    #[arg(long, value_delimiter = ',', global = true)]
+    pub fail_on_nulls: Vec<String>,
+
+    /// Exit with code 4 if the output has no rows (e.g., a filter or join matched nothing)
+    ///
+    /// This is rabbet's strict mode, so it can also be given as --strict.
+    #[arg(long, visible_alias = "strict", global = true)]
+    pub fail_if_empty: bool,
+
+    /// Order of the output columns (comma separated), with `*` for all remaining columns
+    ///
+    /// Columns not listed are dropped unless `*` is given.
+    /// Examples: --output-columns "id,name,*" or --output-columns "*,total"
+    #[arg(long, value_delimiter = ',', global = true)]
+    pub output_columns: Vec<String>,
+
+    /// Prepend a column numbering the output rows from 1, named `row` unless given
+    ///
+    /// Examples: --row-numbers or --row-numbers=line
+    #[arg(
+        long,
+        global = true,
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "row",
+        value_name = "NAME"
+    )]
+    pub row_numbers: Option<String>,
+
+    /// Treat the first row as data and name columns `column_1..column_n`
+    #[arg(long, global = true)]
+    pub no_header: bool,
+
+    /// Explicit column names for the input tables (comma separated)
+    ///
+    /// Combine with --no-header for files without a header row.
+    /// Examples: --header-names "id,name,email"
+    #[arg(long, value_delimiter = ',', global = true)]
+    pub header_names: Vec<String>,
+
+    /// Number repeated column names in the input tables `name`, `name_2`, `name_3`, ...
+    /// instead of failing
+    #[arg(long, global = true)]
+    pub dedupe_headers: bool,
+
+    /// Values to read as nulls in the input tables (comma separated)
+    ///
+    /// Examples: --null-values "NA,N/A,null,-"
+    #[arg(long, value_delimiter = ',', global = true)]
+    pub null_values: Vec<String>,
+
+    /// Column types for the input tables instead of the inferred ones (comma separated column=type pairs)
+    ///
+    /// Types: str, int, float, bool, date, datetime. Dates and datetimes accept an optional
+    /// strftime format.
+    /// Examples: --dtypes "id=int,amount=float" or --dtypes "`order_date=date:%d/%m/%Y`"
+    #[arg(long, value_delimiter = ',', global = true)]
+    pub dtypes: Vec<String>,
+
+    /// Date and datetime columns to parse with a strftime format (comma separated column:format pairs)
+    ///
+    /// Formats with a time (e.g., %H or %+) make datetimes, others make dates.
+    /// Examples: --parse-dates "`order_date:%Y-%m-%d`" or --parse-dates "`created_at:%+`"
+    #[arg(long, value_delimiter = ',', global = true)]
+    pub parse_dates: Vec<String>,
+
+    /// Parse columns that look like dates or datetimes into temporal types
+    #[arg(long, global = true)]
+    pub try_parse_dates: bool,
+
+    /// Number of lines to skip at the start of the input tables (e.g., preamble banners)
+    #[arg(long, default_value = "0", global = true)]
+    pub skip_rows: usize,
+
+    /// Ignore lines in the input tables starting with this character
+    ///
+    /// Examples: --comment-char '#'
+    #[arg(long, global = true)]
+    pub comment_char: Option<char>,
+
+    /// Read the table given as `-` from the clipboard instead of stdin, e.g. cells copied
+    /// from a spreadsheet
+    #[arg(long, global = true)]
+    pub from_clipboard: bool,
+
+    /// Format of the input tables, instead of detecting it
+    ///
+    /// Files are detected by their extension and stdin by its first bytes. Giving it also
+    /// turns off detecting the CSV delimiter when the header lacks --delimiter.
+    #[arg(long, value_enum, global = true)]
+    pub input_format: Option<InputFormat>,
+
+    /// Character encoding of the input tables
+    #[arg(long, value_enum, default_value = "utf-8", global = true)]
+    pub encoding: Encoding,
+
+    /// Check that input rows have as many fields as the header, and what to do with those that don't
+    ///
+    /// Without this, rows with extra fields are an error and missing trailing fields are nulls.
+    #[arg(long, value_enum, global = true)]
+    pub on_bad_lines: Option<BadLines>,
+
+    /// Read the input tables as fixed-width text with these column widths, in characters
+    ///
+    /// The first line holds the column names unless --no-header is given. Fields are trimmed
+    /// and empty ones are nulls. Examples: --fwf-widths 10,5,20
+    #[arg(long, value_delimiter = ',', global = true)]
+    pub fwf_widths: Vec<usize>,
+
+    /// Read the input tables as fixed-width text with the columns listed in this CSV file
+    ///
+    /// The file needs `name` and `width` columns, one row per column in order. The names
+    /// replace a header line, so the input tables are read as having none (use --skip-rows 1
+    /// to drop one).
+    #[arg(long, conflicts_with = "fwf_widths", value_hint = ValueHint::FilePath, global = true)]
+    pub fwf_spec: Option<String>,
+
+    /// Match column names case-insensitively (e.g., `--on ID` matches a column named `id`)
+    #[arg(long, global = true)]
+    pub ignore_column_case: bool,
+
+    /// Replace line breaks embedded in output values with spaces, so each record is one line
+    #[arg(long, global = true)]
+    pub normalize_newlines: bool,
+
+    /// Print how many rows lossy operations dropped (and a sample of them) to stderr
+    #[arg(long, global = true)]
+    pub report: bool,
+
+    /// Show each column's data type under its name in table output
+    #[arg(long, global = true)]
+    pub show_dtypes: bool,
+
+    /// Show every row, column and character in table output instead of fitting the terminal
+    #[arg(long, global = true)]
+    pub full: bool,
+
+    /// Maximum characters shown per value in table output before truncating [default: 16]
+    #[arg(long, global = true)]
+    pub max_col_width: Option<usize>,
+
+    /// Maximum rows shown in table output, with the middle rows elided [default: fits the terminal]
+    #[arg(long, global = true)]
+    pub max_rows: Option<usize>,
+
+    /// Don't page long or wide table output through $PAGER
+    #[arg(long, global = true)]
+    pub no_pager: bool,
+
+    /// Borders of table output [default: rounded]
+    #[arg(long, value_enum, global = true)]
+    pub style: Option<TableStyle>,
+
+    /// Only print ASCII characters in tables and charts, for logs and terminals that mangle
+    /// box drawing characters
+    #[arg(long, global = true)]
+    pub no_unicode: bool,
+
+    /// Print diagnostics to stderr (-v for files read, row counts and timings, -vv for schemas and plans)
+    #[arg(short, long, action = ArgAction::Count, global = true, conflicts_with = "quiet")]
+    pub verbose: u8,
+
+    /// Only print errors to stderr
+    #[arg(short, long, global = true)]
+    pub quiet: bool,
+
+    /// Print how long reading, parsing, computing and writing took, and the peak memory use, to stderr
+    #[arg(long, global = true)]
+    pub timings: bool,
+
+    /// Trade speed for lower memory use: read in smaller chunks and stream queries where possible
+    #[arg(long, global = true)]
+    pub low_memory: bool,
+
+    /// Stop with an error once rabbet uses more than this much memory, instead of running out
+    ///
+    /// Sizes are in bytes, or with a K, M, G or T suffix (powers of 1024).
+    /// Examples: --memory-limit 2G or --memory-limit 512M
+    #[arg(long, value_parser = parse_size, global = true)]
+    pub memory_limit: Option<u64>,
+
+    /// Seed for randomized output, such as partition and the random UUIDs of derive --uuid
+    #[arg(long, global = true)]
+    pub seed: Option<u64>,
+
+    /// Make repeated runs over the same inputs give identical output, e.g. for snapshot tests
+    ///
+    /// Randomized output is seeded with --seed, or 0 without it, and rows are kept in a
+    /// stable order as with --stable.
+    #[arg(long, global = true)]
+    pub deterministic: bool,
+
+    /// Keep joined and grouped rows in the order of the input rows instead of whatever order
+    /// threads finish in, so repeated runs give byte-identical output
+    #[arg(long, global = true)]
+    pub stable: bool,
+
+    /// Keep parsed CSV files as Parquet in this directory, so later commands on the same
+    /// unchanged file skip parsing it
+    ///
+    /// Files are looked up by path, size and modification time, along with the options used
+    /// to parse them. The directory can be deleted at any time.
+    #[arg(long, value_hint = ValueHint::DirPath, global = true)]
+    pub cache_dir: Option<String>,
+}
+
+impl GlobalArgs {
+    /// Seed for randomized output: --seed, or 0 with --deterministic
+    pub fn seed(&self) -> Option<u64> {
+        self.seed.or_else(|| self.deterministic.then_some(0))
+    }
+
+    /// Whether joins and group-bys keep the order of their input rows: --stable, or
+    /// --deterministic
+    pub const fn stable_order(&self) -> bool {
+        self.stable || self.deterministic
+    }
 }
 
 #[derive(clap::Subcommand, Debug)]
@@ -35,59 +439,293 @@ pub enum Commands {
     /// Aggregate
     Aggregate(AggregateArgs),
 
+    /// Anonymize
+    #[command(alias = "hash")]
+    Anonymize(AnonymizeArgs),
+
+    /// Bench
+    Bench(BenchArgs),
+
+    /// Bin
+    #[command(alias = "cut")]
+    Bin(BinArgs),
+
     /// Cat
     Cat(CatArgs),
 
+    /// Clean
+    Clean(CleanArgs),
+
+    /// Concat
+    #[command(alias = "stack")]
+    Concat(ConcatArgs),
+
+    /// Corr
+    Corr(CorrArgs),
+
+    /// Crosstab
+    Crosstab(CrosstabArgs),
+
+    /// Dates
+    Dates(DatesArgs),
+
+    /// Ddl
+    #[command(alias = "to-ddl")]
+    Ddl(DdlArgs),
+
+    /// Demo
+    Demo(DemoArgs),
+
+    /// Derive
+    Derive(DeriveArgs),
+
+    /// Diff
+    Diff(DiffArgs),
+
+    /// Explode
+    Explode(ExplodeArgs),
+
+    /// Extract
+    Extract(ExtractArgs),
+
+    /// Freq
+    Freq(FreqArgs),
+
+    /// From sqlite
+    FromSqlite(FromSqliteArgs),
+
+    /// Grep
+    #[command(alias = "search")]
+    Grep(GrepArgs),
+
     /// Head
     Head(HeadArgs),
 
+    /// Hist
+    Hist(HistArgs),
+
     /// Join
     Join(JoinArgs),
 
+    /// Keys
+    Keys(KeysArgs),
+
+    /// Partition
+    #[command(alias = "train-test-split")]
+    Partition(PartitionArgs),
+
+    /// Profile
+    #[command(alias = "meta")]
+    Profile(ProfileArgs),
+
     /// Query
     Query(QueryArgs),
 
+    /// Rank
+    Rank(RankArgs),
+
+    /// Rolling
+    Rolling(RollingArgs),
+
+    /// Shift
+    Shift(ShiftArgs),
+
+    /// Split
+    Split(SplitArgs),
+
+    /// Suggest types
+    SuggestTypes(SuggestTypesArgs),
+
     /// Tail
     Tail(TailArgs),
 
+    /// Top
+    Top(TopArgs),
+
+    /// Unpack
+    Unpack(UnpackArgs),
+
+    /// Validate
+    Validate(ValidateArgs),
+
+    /// Window
+    Window(WindowArgs),
+
     /// Completions
     Completions {
         /// The shell to generate completions for
         #[arg(value_enum)]
         shell: Shell,
     },
+
+    /// Complete column names for the shell completions
+    #[command(name = "__complete", hide = true)]
+    Complete(CompleteArgs),
+}
+
+/// Tags a failed `validate` as a usage error, so it exits with the same code as clap's
+fn usage<E: Into<anyhow::Error>>(validated: Result<(), E>) -> Result<()> {
+    validated.map_err(|e| Failure::Usage.tag(e.into()))
 }
 
 impl Args {
+    #[allow(clippy::too_many_lines)]
     pub fn run(&self) -> Result<()> {
         match &self.command {
             Commands::Aggregate(aggregate_args) => {
-                aggregate_args.validate()?;
-                aggregate_args.execute(&self.format)?;
+                usage(aggregate_args.validate())?;
+                aggregate_args.execute(&self.global)?;
+            }
+            Commands::Anonymize(anonymize_args) => {
+                usage(anonymize_args.validate())?;
+                anonymize_args.execute(&self.global)?;
+            }
+            Commands::Bench(bench_args) => {
+                usage(bench_args.validate())?;
+                bench_args.execute(&self.global)?;
+            }
+            Commands::Bin(bin_args) => {
+                usage(bin_args.validate())?;
+                bin_args.execute(&self.global)?;
             }
             Commands::Join(join_args) => {
-                join_args.validate()?;
-                join_args.execute(&self.format)?;
+                usage(join_args.validate())?;
+                join_args.execute(&self.global)?;
             }
             Commands::Cat(cat_args) => {
-                cat_args.validate()?;
-                cat_args.execute(&self.format)?;
+                usage(cat_args.validate())?;
+                cat_args.execute(&self.global)?;
+            }
+            Commands::Clean(clean_args) => {
+                usage(clean_args.validate())?;
+                clean_args.execute(&self.global)?;
+            }
+            Commands::Concat(concat_args) => {
+                usage(concat_args.validate())?;
+                concat_args.execute(&self.global)?;
+            }
+            Commands::Corr(corr_args) => {
+                usage(corr_args.validate())?;
+                corr_args.execute(&self.global)?;
+            }
+            Commands::Crosstab(crosstab_args) => {
+                usage(crosstab_args.validate())?;
+                crosstab_args.execute(&self.global)?;
+            }
+            Commands::Dates(dates_args) => {
+                usage(dates_args.validate())?;
+                dates_args.execute(&self.global)?;
+            }
+            Commands::Ddl(ddl_args) => {
+                usage(ddl_args.validate())?;
+                ddl_args.execute(&self.global)?;
+            }
+            Commands::Demo(demo_args) => {
+                usage(demo_args.validate())?;
+                demo_args.execute(&self.global)?;
+            }
+            Commands::Derive(derive_args) => {
+                usage(derive_args.validate())?;
+                derive_args.execute(&self.global)?;
+            }
+            Commands::Diff(diff_args) => {
+                usage(diff_args.validate())?;
+                diff_args.execute(&self.global)?;
+            }
+            Commands::Explode(explode_args) => {
+                usage(explode_args.validate())?;
+                explode_args.execute(&self.global)?;
+            }
+            Commands::Extract(extract_args) => {
+                usage(extract_args.validate())?;
+                extract_args.execute(&self.global)?;
+            }
+            Commands::Freq(freq_args) => {
+                usage(freq_args.validate())?;
+                freq_args.execute(&self.global)?;
+            }
+            Commands::FromSqlite(from_sqlite_args) => {
+                usage(from_sqlite_args.validate())?;
+                from_sqlite_args.execute(&self.global)?;
+            }
+            Commands::Grep(grep_args) => {
+                usage(grep_args.validate())?;
+                grep_args.execute(&self.global)?;
             }
             Commands::Head(head_args) => {
-                head_args.validate()?;
-                head_args.execute(&self.format)?;
+                usage(head_args.validate())?;
+                head_args.execute(&self.global)?;
+            }
+            Commands::Hist(hist_args) => {
+                usage(hist_args.validate())?;
+                hist_args.execute(&self.global)?;
+            }
+            Commands::Keys(keys_args) => {
+                usage(keys_args.validate())?;
+                keys_args.execute(&self.global)?;
+            }
+            Commands::Partition(partition_args) => {
+                usage(partition_args.validate())?;
+                partition_args.execute(&self.global)?;
+            }
+            Commands::Profile(profile_args) => {
+                usage(profile_args.validate())?;
+                profile_args.execute(&self.global)?;
             }
             Commands::Query(query_args) => {
-                query_args.validate()?;
-                query_args.execute(&self.format)?;
+                usage(query_args.validate())?;
+                query_args.execute(&self.global)?;
+            }
+            Commands::Rank(rank_args) => {
+                usage(rank_args.validate())?;
+                rank_args.execute(&self.global)?;
+            }
+            Commands::Rolling(rolling_args) => {
+                usage(rolling_args.validate())?;
+                rolling_args.execute(&self.global)?;
+            }
+            Commands::Shift(shift_args) => {
+                usage(shift_args.validate())?;
+                shift_args.execute(&self.global)?;
+            }
+            Commands::Split(split_args) => {
+                usage(split_args.validate())?;
+                split_args.execute(&self.global)?;
+            }
+            Commands::SuggestTypes(suggest_types_args) => {
+                usage(suggest_types_args.validate())?;
+                suggest_types_args.execute(&self.global)?;
             }
             Commands::Tail(tail_args) => {
-                tail_args.validate()?;
-                tail_args.execute(&self.format)?;
+                usage(tail_args.validate())?;
+                tail_args.execute(&self.global)?;
+            }
+            Commands::Top(top_args) => {
+                usage(top_args.validate())?;
+                top_args.execute(&self.global)?;
+            }
+            Commands::Unpack(unpack_args) => {
+                usage(unpack_args.validate())?;
+                unpack_args.execute(&self.global)?;
+            }
+            Commands::Validate(validate_args) => {
+                usage(validate_args.validate())?;
+                validate_args.execute(&self.global)?;
+            }
+            Commands::Window(window_args) => {
+                usage(window_args.validate())?;
+                window_args.execute(&self.global)?;
             }
             Commands::Completions { shell } => {
                 let mut cmd = Self::command();
                 generate(*shell, &mut cmd, "rabbet", &mut std::io::stdout());
+                if let Some(script) = dynamic_script(*shell) {
+                    print!("{script}");
+                }
+            }
+            Commands::Complete(complete_args) => {
+                usage(complete_args.validate())?;
+                complete_args.execute(&self.global)?;
             }
         }
         Ok(())
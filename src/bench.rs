@@ -0,0 +1,169 @@
+// Run a rabbet command repeatedly and report how long it takes, e.g. to compare flags or versions.
+use anyhow::{Context, Result, ensure};
+use clap::Args;
+use polars::prelude::*;
+use std::env;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+use crate::args::GlobalArgs;
+use crate::io::write_data;
+use crate::timings::{Usage, peak_rss_kib};
+
+#[derive(Args, Debug)]
+pub struct BenchArgs {
+    /// Number of timed runs
+    #[arg(long, default_value = "5")]
+    pub iterations: usize,
+
+    /// Number of untimed runs before timing starts (e.g., to warm the file cache)
+    #[arg(long, default_value = "1")]
+    pub warmup: usize,
+
+    /// The rabbet command to benchmark, without the leading `rabbet`
+    ///
+    /// Examples: rabbet bench --iterations 10 join users.csv orders.csv --on `user_id`
+    #[arg(required = true, trailing_var_arg = true, allow_hyphen_values = true)]
+    pub command: Vec<String>,
+}
+
+impl BenchArgs {
+    pub fn validate(&self) -> Result<()> {
+        ensure!(self.iterations > 0, "--iterations must be at least 1");
+        ensure!(
+            self.command.first().is_some_and(|c| c != "bench"),
+            "bench - cannot benchmark the bench command"
+        );
+        ensure!(
+            !self.command.iter().any(|arg| arg == "-"),
+            "bench - commands reading from stdin ('-') cannot be run repeatedly"
+        );
+
+        Ok(())
+    }
+
+    pub fn execute(&self, global: &GlobalArgs) -> Result<()> {
+        let exe =
+            env::current_exe().context("bench - failed to locate the rabbet executable")?;
+
+        for _ in 0..self.warmup {
+            self.run_once(&exe)?;
+        }
+
+        let timings = (0..self.iterations)
+            .map(|_| self.run_once(&exe))
+            .collect::<Result<Vec<Duration>>>()?;
+
+        write_data(
+            summarize(
+                &self.command.join(" "),
+                &timings,
+                peak_rss_kib(Usage::Children),
+            )?,
+            global,
+        )
+        .with_context(|| "bench - failed to write data to stdout".to_string())?;
+
+        Ok(())
+    }
+
+    /// Runs the command once with its output discarded, returning the wall clock time
+    fn run_once(&self, exe: &std::path::Path) -> Result<Duration> {
+        let start = Instant::now();
+        let status = Command::new(exe)
+            .args(&self.command)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .status()
+            .with_context(|| {
+                format!("bench - failed to run '{}'", self.command.join(" "))
+            })?;
+        let elapsed = start.elapsed();
+
+        ensure!(
+            status.success(),
+            "bench - '{}' failed with {status}",
+            self.command.join(" ")
+        );
+
+        Ok(elapsed)
+    }
+}
+
+/// Builds a single row summary of the timings in milliseconds
+fn summarize(
+    command: &str,
+    timings: &[Duration],
+    peak_rss: Option<u64>,
+) -> Result<DataFrame> {
+    // Microsecond resolution is plenty for comparing runs
+    let mut millis: Vec<f64> = timings
+        .iter()
+        .map(|d| (d.as_secs_f64() * 1_000_000.0).round() / 1000.0)
+        .collect();
+    millis.sort_by(f64::total_cmp);
+
+    let mid = millis.len() / 2;
+    let median = if millis.len() % 2 == 0 {
+        f64::midpoint(millis[mid - 1], millis[mid])
+    } else {
+        millis[mid]
+    };
+
+    let df = df! {
+        "command" => [command],
+        "iterations" => [millis.len() as u64],
+        "min_ms" => [millis[0]],
+        "median_ms" => [median],
+        "max_ms" => [millis[millis.len() - 1]],
+        "peak_rss_kib" => [peak_rss],
+    }?;
+
+    Ok(df)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate() {
+        let mut args = BenchArgs {
+            iterations: 5,
+            warmup: 1,
+            command: vec!["cat".to_string(), "data/orders/orders.csv".to_string()],
+        };
+        assert!(args.validate().is_ok());
+
+        args.iterations = 0;
+        assert!(args.validate().is_err());
+
+        args.iterations = 5;
+        args.command = vec!["cat".to_string(), "-".to_string()];
+        assert!(args.validate().is_err());
+
+        args.command = vec!["bench".to_string(), "cat".to_string()];
+        assert!(args.validate().is_err());
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn test_summarize() {
+        let timings = [30, 10, 20, 40].map(Duration::from_millis);
+        let df = summarize("cat orders.csv", &timings, Some(2048)).unwrap();
+
+        assert_eq!(df.shape(), (1, 6));
+        assert_eq!(
+            df.column("min_ms").unwrap().f64().unwrap().get(0),
+            Some(10.0)
+        );
+        assert_eq!(
+            df.column("median_ms").unwrap().f64().unwrap().get(0),
+            Some(25.0)
+        );
+        assert_eq!(
+            df.column("max_ms").unwrap().f64().unwrap().get(0),
+            Some(40.0)
+        );
+    }
+}
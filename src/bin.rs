@@ -0,0 +1,228 @@
+// Bin a numeric column into labelled ranges, by explicit breaks or by quantiles.
+use anyhow::{Context, Result, ensure};
+use clap::{Args, ValueHint};
+use itertools::Itertools;
+use polars::prelude::*;
+
+use crate::args::GlobalArgs;
+use crate::io::{resolve_columns, scan_data, write_data};
+
+#[derive(Args, Debug)]
+pub struct BinArgs {
+    /// Input table (file or '-' for stdin)
+    #[arg(required = true, value_hint = ValueHint::FilePath)]
+    pub table: String,
+
+    /// Numeric column to bin
+    #[arg(long, required = true)]
+    pub column: String,
+
+    /// Edges between the bins, in increasing order (comma separated)
+    ///
+    /// n breaks make n + 1 bins, the first and last open ended.
+    /// Examples: --breaks 18,30,45,65
+    #[arg(
+        long,
+        value_delimiter = ',',
+        required_unless_present = "quantiles",
+        conflicts_with = "quantiles"
+    )]
+    pub breaks: Vec<f64>,
+
+    /// Split the column into this many bins holding about as many rows each
+    ///
+    /// Examples: --quantiles 4 for quartiles
+    #[arg(long)]
+    pub quantiles: Option<usize>,
+
+    /// Names of the bins, lowest first (comma separated)
+    ///
+    /// Defaults to the ranges, e.g. `(18, 30]`.
+    /// Examples: --labels "minor,young,adult,middle,senior"
+    #[arg(long, value_delimiter = ',')]
+    pub labels: Vec<String>,
+
+    /// Put values on a break in the bin above it, `[18, 30)`, instead of the one below
+    #[arg(long)]
+    pub left_closed: bool,
+
+    /// Name of the column holding the bins [default: <column>_bin]
+    #[arg(long)]
+    pub name: Option<String>,
+
+    /// Delimiter for input files
+    #[arg(long, default_value = ",")]
+    pub delimiter: char,
+}
+
+impl BinArgs {
+    pub fn validate(&self) -> Result<()> {
+        ensure!(
+            self.breaks.iter().tuple_windows().all(|(a, b)| a < b),
+            "--breaks must be in increasing order without repeats"
+        );
+        ensure!(
+            self.quantiles.is_none_or(|n| n >= 2),
+            "--quantiles needs at least 2 bins"
+        );
+        ensure!(
+            self.labels.iter().all_unique(),
+            "--labels can't repeat a label"
+        );
+        let bins = self.quantiles.unwrap_or(self.breaks.len() + 1);
+        ensure!(
+            self.labels.is_empty() || self.labels.len() == bins,
+            "--labels names {} bin(s) but there are {bins}",
+            self.labels.len()
+        );
+
+        Ok(())
+    }
+
+    pub fn execute(&self, global: &GlobalArgs) -> Result<()> {
+        let mut lf =
+            scan_data(&self.table, Some(self.delimiter), global).with_context(|| {
+                format!("bin - failed to read csv data from {}", self.table)
+            })?;
+        let schema = lf.collect_schema()?;
+        let header = DataFrame::empty_with_schema(&schema);
+
+        let column = resolve_columns(
+            &header,
+            std::slice::from_ref(&self.column),
+            global.ignore_column_case,
+        )?
+        .remove(0);
+        let dtype = schema.get(&column).unwrap_or(&DataType::Null);
+        ensure!(
+            dtype.is_primitive_numeric(),
+            "bin - column '{column}' must be numeric, found {dtype}"
+        );
+
+        let result = lf
+            .with_column(self.bin(&column))
+            .collect()
+            .with_context(|| format!("bin - failed to bin '{column}' in {}", self.table))?;
+
+        write_data(result, global)
+            .with_context(|| "bin - failed to write data to stdout".to_string())?;
+
+        Ok(())
+    }
+
+    /// The bin of each row's `column` value as a categorical column, null for nulls
+    fn bin(&self, column: &str) -> Expr {
+        let name = self.name.clone().unwrap_or_else(|| format!("{column}_bin"));
+        let labels = (!self.labels.is_empty()).then(|| self.labels.clone());
+        let values = col(column).cast(DataType::Float64);
+        let bins = match self.quantiles {
+            // Repeated quantiles collapse into one bin, unless the bins are named
+            Some(n) => values.qcut(
+                quantile_probs(n),
+                labels.clone(),
+                self.left_closed,
+                labels.is_none(),
+                false,
+            ),
+            None => values.cut(self.breaks.clone(), labels, self.left_closed, false),
+        };
+
+        bins.alias(name)
+    }
+}
+
+/// The probabilities splitting values into `n` equally sized bins
+fn quantile_probs(n: usize) -> Vec<f64> {
+    let n = u32::try_from(n).unwrap_or(u32::MAX);
+    (1..n).map(|b| f64::from(b) / f64::from(n)).collect()
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn args(breaks: &[f64], quantiles: Option<usize>, labels: &[&str]) -> BinArgs {
+        BinArgs {
+            table: "data/iris/iris.csv".to_string(),
+            column: "SepalLength".to_string(),
+            breaks: breaks.to_vec(),
+            quantiles,
+            labels: labels.iter().map(ToString::to_string).collect(),
+            left_closed: false,
+            name: None,
+            delimiter: ',',
+        }
+    }
+
+    fn bins(args: &BinArgs) -> Vec<Option<String>> {
+        let df =
+            df!("age" => [Some(12), Some(18), Some(30), None, Some(44), Some(70)]).unwrap();
+        df.lazy()
+            .with_column(args.bin("age"))
+            .collect()
+            .unwrap()
+            .column("age_bin")
+            .unwrap()
+            .cast(&DataType::String)
+            .unwrap()
+            .str()
+            .unwrap()
+            .into_iter()
+            .map(|bin| bin.map(str::to_string))
+            .collect()
+    }
+
+    #[test]
+    fn test_bin_breaks() {
+        let ranges = bins(&args(&[18.0, 30.0, 65.0], None, &[]));
+        assert_eq!(
+            ranges,
+            [
+                Some("(-inf, 18]".to_string()),
+                Some("(-inf, 18]".to_string()),
+                Some("(18, 30]".to_string()),
+                None,
+                Some("(30, 65]".to_string()),
+                Some("(65, inf]".to_string()),
+            ]
+        );
+
+        let mut args = args(
+            &[18.0, 30.0, 65.0],
+            None,
+            &["minor", "young", "adult", "senior"],
+        );
+        args.left_closed = true;
+        let labels: Vec<_> = bins(&args).into_iter().flatten().collect();
+        assert_eq!(labels, ["minor", "young", "adult", "adult", "senior"]);
+    }
+
+    #[test]
+    fn test_bin_quantiles() {
+        assert_eq!(quantile_probs(4), [0.25, 0.5, 0.75]);
+        let halves = bins(&args(&[], Some(2), &["low", "high"]));
+        assert_eq!(halves.iter().flatten().filter(|b| *b == "low").count(), 3);
+        assert_eq!(halves[3], None);
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(
+            args(&[18.0, 30.0], None, &["a", "b", "c"])
+                .validate()
+                .is_ok()
+        );
+        assert!(args(&[18.0, 30.0], None, &["a", "b"]).validate().is_err());
+        assert!(args(&[30.0, 18.0], None, &[]).validate().is_err());
+        assert!(args(&[], Some(1), &[]).validate().is_err());
+        assert!(args(&[], Some(4), &["a", "b", "c", "d"]).validate().is_ok());
+    }
+
+    #[test]
+    fn test_bin_iris_csv() {
+        let args = args(&[], Some(4), &[]);
+        assert!(args.validate().is_ok());
+        assert!(args.execute(&GlobalArgs::default()).is_ok());
+    }
+}
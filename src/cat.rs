@@ -2,7 +2,7 @@ use anyhow::{Context, Result};
 use clap::{Args, ValueHint};
 use std::io;
 
-use crate::args::OutputFormat;
+use crate::args::GlobalArgs;
 use crate::io::{read_data, write_data};
 
 #[derive(Args, Debug)]
@@ -20,12 +20,13 @@ impl CatArgs {
     }
 
     #[allow(clippy::expect_used)]
-    pub fn execute(&self, format: &OutputFormat) -> Result<()> {
-        let data = read_data(self.table.as_str(), Some(',')).with_context(|| {
-            format!("cat - failed to read csv data from {}", self.table)
-        })?;
+    pub fn execute(&self, global: &GlobalArgs) -> Result<()> {
+        let data =
+            read_data(self.table.as_str(), Some(','), global).with_context(|| {
+                format!("cat - failed to read csv data from {}", self.table)
+            })?;
 
-        write_data(data, format)
+        write_data(data, global)
             .with_context(|| "cat - failed to write data to stdout".to_string())?;
 
         Ok(())
@@ -52,7 +53,7 @@ mod tests {
             table: "nonexistent_file.csv".to_string(),
         };
 
-        args.execute(&crate::args::OutputFormat::Auto).unwrap();
+        args.execute(&GlobalArgs::default()).unwrap();
     }
 
     #[test]
@@ -62,6 +63,6 @@ mod tests {
         };
 
         assert!(args.validate().is_ok());
-        assert!(args.execute(&crate::args::OutputFormat::Auto).is_ok());
+        assert!(args.execute(&GlobalArgs::default()).is_ok());
     }
 }
@@ -0,0 +1,272 @@
+// Tidy a table in one pass: trim values, drop empty rows and columns and repair headers.
+use anyhow::{Context, Result, ensure};
+use clap::{Args, ValueHint};
+use itertools::Itertools;
+use log::info;
+use polars::prelude::*;
+
+use crate::args::GlobalArgs;
+use crate::io::{read_data, report_dropped, unique_names, write_data};
+
+#[derive(Args, Debug)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct CleanArgs {
+    /// Input table (file or '-' for stdin)
+    #[arg(required = true, value_hint = ValueHint::FilePath)]
+    pub table: String,
+
+    /// Remove leading and trailing whitespace from text values, making blank values null
+    #[arg(long)]
+    pub trim_whitespace: bool,
+
+    /// Drop rows where every value is null or blank
+    #[arg(long)]
+    pub drop_empty_rows: bool,
+
+    /// Drop columns where every value is null or blank
+    #[arg(long)]
+    pub drop_empty_cols: bool,
+
+    /// Rewrite column names in `snake_case`, e.g. ` Order ID ` becomes `order_id`
+    #[arg(long)]
+    pub normalize_headers: bool,
+
+    /// Delimiter for input files
+    #[arg(long, default_value = ",")]
+    pub delimiter: char,
+}
+
+impl CleanArgs {
+    pub fn validate(&self) -> Result<()> {
+        ensure!(
+            self.trim_whitespace
+                || self.drop_empty_rows
+                || self.drop_empty_cols
+                || self.normalize_headers,
+            "Nothing to clean. Pass at least one of --trim-whitespace, --drop-empty-rows, --drop-empty-cols or --normalize-headers"
+        );
+
+        Ok(())
+    }
+
+    pub fn execute(&self, global: &GlobalArgs) -> Result<()> {
+        let df =
+            read_data(&self.table, Some(self.delimiter), global).with_context(|| {
+                format!("clean - failed to read csv data from {}", self.table)
+            })?;
+
+        let result = self
+            .clean(df, global.dedupe_headers, global.report)
+            .with_context(|| format!("clean - failed to clean {}", self.table))?;
+
+        write_data(result, global)
+            .with_context(|| "clean - failed to write data to stdout".to_string())?;
+
+        Ok(())
+    }
+
+    /// Applies each requested cleanup, values first so blanks left by trimming count as
+    /// empty
+    ///
+    /// Repeated names were already numbered while reading with `dedupe`, which also numbers
+    /// names made the same by normalizing. With `report`, the dropped empty rows are listed.
+    fn clean(&self, mut df: DataFrame, dedupe: bool, report: bool) -> Result<DataFrame> {
+        if self.trim_whitespace {
+            let trimmed: Vec<Expr> = text_columns(&df)
+                .into_iter()
+                .map(|c| {
+                    let trimmed = col(c.as_str()).str().strip_chars(lit(NULL));
+                    when(trimmed.clone().eq(lit("")))
+                        .then(lit(NULL).cast(DataType::String))
+                        .otherwise(trimmed)
+                        .alias(c.as_str())
+                })
+                .collect();
+            df = df.lazy().with_columns(trimmed).collect()?;
+        }
+
+        if self.drop_empty_rows && df.width() > 0 {
+            let empty: Vec<Expr> = df
+                .get_column_names_str()
+                .into_iter()
+                .map(|c| blank(&df, c))
+                .collect();
+            let empty = all_horizontal(empty)?;
+            if report {
+                let removed = df.clone().lazy().filter(empty.clone()).collect()?;
+                report_dropped("clean - empty rows", &removed);
+            }
+            let before = df.height();
+            df = df.lazy().filter(empty.not()).collect()?;
+            info!("dropped {} empty row(s)", before - df.height());
+        }
+
+        if self.drop_empty_cols {
+            let empty: Vec<String> = df
+                .get_column_names_str()
+                .into_iter()
+                .filter(|c| {
+                    let column = df.column(c).map(|c| c.as_materialized_series().clone());
+                    column.is_ok_and(|c| is_blank(&c))
+                })
+                .map(ToString::to_string)
+                .collect();
+            if !empty.is_empty() {
+                info!("dropped empty column(s) {}", empty.join(", "));
+                df = df.drop_many(empty);
+            }
+        }
+
+        if self.normalize_headers {
+            let mut headers: Vec<String> = df
+                .get_column_names_str()
+                .iter()
+                .enumerate()
+                .map(|(idx, name)| normalize_header(name, idx))
+                .collect();
+            if dedupe {
+                headers = unique_names(&headers);
+            }
+            let repeated: Vec<&String> = headers.iter().duplicates().collect();
+            ensure!(
+                repeated.is_empty(),
+                "several columns would be named {}. Pass --dedupe-headers to number them",
+                repeated.iter().join(", ")
+            );
+            df.set_column_names(headers)?;
+        }
+
+        Ok(df)
+    }
+}
+
+/// Names of the text columns of `df`
+fn text_columns(df: &DataFrame) -> Vec<String> {
+    df.get_columns()
+        .iter()
+        .filter(|c| c.dtype() == &DataType::String)
+        .map(|c| c.name().to_string())
+        .collect()
+}
+
+/// Whether each value of `column` is null, or blank text
+fn blank(df: &DataFrame, column: &str) -> Expr {
+    let is_text = df
+        .column(column)
+        .is_ok_and(|c| c.dtype() == &DataType::String);
+    if is_text {
+        col(column)
+            .is_null()
+            .or(col(column).str().strip_chars(lit(NULL)).eq(lit("")))
+    } else {
+        col(column).is_null()
+    }
+}
+
+/// Whether every value of `series` is null, or blank text
+fn is_blank(series: &Series) -> bool {
+    series.str().map_or_else(
+        |_| series.null_count() == series.len(),
+        |values| {
+            values
+                .into_iter()
+                .all(|v| v.is_none_or(|v| v.trim().is_empty()))
+        },
+    )
+}
+
+/// `name` in `snake_case`, e.g. ` Order ID ` becomes `order_id`, or `column_<n>` if it has
+/// no letters or digits
+fn normalize_header(name: &str, idx: usize) -> String {
+    let mut normalized = String::new();
+    for c in name.chars() {
+        if c.is_alphanumeric() {
+            normalized.extend(c.to_lowercase());
+        } else if !normalized.is_empty() && !normalized.ends_with('_') {
+            normalized.push('_');
+        }
+    }
+    let normalized = normalized.trim_end_matches('_');
+
+    if normalized.is_empty() {
+        format!("column_{}", idx + 1)
+    } else {
+        normalized.to_string()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn clean() -> CleanArgs {
+        CleanArgs {
+            table: "data/sales/january.csv".to_string(),
+            trim_whitespace: false,
+            drop_empty_rows: false,
+            drop_empty_cols: false,
+            normalize_headers: false,
+            delimiter: ',',
+        }
+    }
+
+    fn messy() -> DataFrame {
+        df!(
+            "Order ID" => [Some(1), None, Some(2)],
+            "name" => [Some(" a "), Some("  "), None],
+            "name_2" => [Some("b"), None, Some("c")],
+            " Note " => [None::<&str>, None, Some(" ")],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_clean() {
+        let mut args = clean();
+        args.trim_whitespace = true;
+        args.drop_empty_rows = true;
+        args.drop_empty_cols = true;
+        let df = args.clean(messy(), false, false).unwrap();
+        assert_eq!(df.get_column_names_str(), ["Order ID", "name", "name_2"]);
+        assert_eq!(df.height(), 2);
+        let names: Vec<_> = df
+            .column("name")
+            .unwrap()
+            .str()
+            .unwrap()
+            .into_iter()
+            .collect();
+        assert_eq!(names, [Some("a"), None]);
+    }
+
+    #[test]
+    fn test_clean_headers() {
+        let mut args = clean();
+        args.normalize_headers = true;
+        let df = args.clean(messy(), false, false).unwrap();
+        assert_eq!(
+            df.get_column_names_str(),
+            ["order_id", "name", "name_2", "note"]
+        );
+
+        // Normalizing can make names collide, which needs --dedupe-headers
+        let df = df!("a b" => [1], "A-B" => [2]).unwrap();
+        assert!(args.clean(df.clone(), false, false).is_err());
+        let df = args.clean(df, true, false).unwrap();
+        assert_eq!(df.get_column_names_str(), ["a_b", "a_b_2"]);
+
+        assert_eq!(normalize_header("  Total ($) ", 0), "total");
+        assert_eq!(normalize_header("%", 2), "column_3");
+    }
+
+    #[test]
+    fn test_clean_january_csv() {
+        assert!(clean().validate().is_err());
+
+        let mut args = clean();
+        args.normalize_headers = true;
+        assert!(args.validate().is_ok());
+        assert!(args.execute(&GlobalArgs::default()).is_ok());
+    }
+}
@@ -0,0 +1,30 @@
+// Reading tables from and writing output to the system clipboard, for `--from-clipboard` and `--to-clipboard`.
+use anyhow::{Context, Result, ensure};
+use arboard::Clipboard;
+
+/// The text on the clipboard, e.g. cells copied from a spreadsheet as tab separated lines
+pub fn read_clipboard() -> Result<Vec<u8>> {
+    let text = Clipboard::new()
+        .context("Failed to open the clipboard")?
+        .get_text()
+        .context("Failed to read text from the clipboard")?;
+    ensure!(
+        !text.trim().is_empty(),
+        "the clipboard is empty - copy some cells before passing --from-clipboard"
+    );
+
+    Ok(text.into_bytes())
+}
+
+/// Replaces the text on the clipboard with `text`
+///
+/// On Linux the clipboard belongs to the program that set it, so the text is handed over
+/// to the desktop's clipboard manager when rabbet exits.
+pub fn write_clipboard(text: String) -> Result<()> {
+    Clipboard::new()
+        .context("Failed to open the clipboard")?
+        .set_text(text)
+        .context("Failed to copy the output to the clipboard")?;
+
+    Ok(())
+}
@@ -0,0 +1,230 @@
+// Complete column names for --on, --by and --columns from the tables already on the command line.
+use anyhow::Result;
+use clap::{Args, CommandFactory};
+use clap_complete::Shell;
+use itertools::Itertools;
+use std::path::Path;
+
+use crate::args::{Args as RabbetArgs, GlobalArgs};
+use crate::io::scan_data;
+
+/// Options whose values are column names
+const COLUMN_OPTIONS: [&str; 3] = ["--on", "--by", "--columns"];
+
+/// Asks `rabbet __complete` for column names, falling back to the generated completions
+const BASH: &str = r#"
+_rabbet_columns() {
+    local IFS=$'\n'
+    local columns=($(rabbet __complete --line "${COMP_LINE:0:COMP_POINT}" 2>/dev/null))
+    if [[ ${#columns[@]} -gt 0 ]]; then
+        # Bash splits words at = and :, so only complete what follows them
+        COMPREPLY=("${columns[@]##*[=:]}")
+        return 0
+    fi
+    _rabbet "$@"
+}
+complete -F _rabbet_columns -o nosort -o bashdefault -o default rabbet
+"#;
+
+const ZSH: &str = r#"
+_rabbet_columns() {
+    local -a columns
+    columns=("${(@f)$(rabbet __complete --line "$LBUFFER" 2>/dev/null)}")
+    if [[ -n "${columns[1]}" ]]; then
+        compadd -U -Q -S '' -- "${columns[@]}"
+    else
+        _rabbet "$@"
+    fi
+}
+compdef _rabbet_columns rabbet
+"#;
+
+const FISH: &str = "
+complete -c rabbet -a '(rabbet __complete --line (commandline -cp) 2>/dev/null)'
+";
+
+#[derive(Args, Debug)]
+pub struct CompleteArgs {
+    /// The command line up to the cursor
+    #[arg(long, allow_hyphen_values = true)]
+    pub line: String,
+}
+
+impl CompleteArgs {
+    #[allow(clippy::unused_self)]
+    #[allow(clippy::unnecessary_wraps)]
+    pub const fn validate(&self) -> Result<()> {
+        Ok(())
+    }
+
+    #[allow(clippy::unnecessary_wraps)]
+    pub fn execute(&self, _global: &GlobalArgs) -> Result<()> {
+        for candidate in candidates(&split_words(&self.line)) {
+            println!("{candidate}");
+        }
+
+        Ok(())
+    }
+}
+
+/// The shell code which hooks `rabbet __complete` into the completions for `shell`, if any
+pub const fn dynamic_script(shell: Shell) -> Option<&'static str> {
+    match shell {
+        Shell::Bash => Some(BASH),
+        Shell::Zsh => Some(ZSH),
+        Shell::Fish => Some(FISH),
+        _ => None,
+    }
+}
+
+/// Splits a command line into words like a shell would, roughly
+///
+/// Quotes group words and are dropped. A line ending in whitespace ends with an empty word,
+/// the one being completed.
+fn split_words(line: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut quote = None;
+    for c in line.chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (None, '"' | '\'') => quote = Some(c),
+            (None, c) if c.is_whitespace() => {
+                if !word.is_empty() {
+                    words.push(std::mem::take(&mut word));
+                }
+            }
+            (_, c) => word.push(c),
+        }
+    }
+    words.push(word);
+    words
+}
+
+/// Completions for the last of `words` if it's a column option's value, or nothing
+///
+/// Columns come from the headers of the tables among the earlier words. Each candidate
+/// replaces the whole last word, keeping the columns already given before a `,` or `=`.
+fn candidates(words: &[String]) -> Vec<String> {
+    let Some((current, earlier)) = words.split_last() else {
+        return Vec::new();
+    };
+    let option_value = current
+        .split_once('=')
+        .filter(|(option, _)| COLUMN_OPTIONS.contains(option));
+    let (option, value) = match option_value {
+        Some((option, value)) => (format!("{option}="), value),
+        None if earlier
+            .last()
+            .is_some_and(|w| COLUMN_OPTIONS.contains(&w.as_str())) =>
+        {
+            (String::new(), current.as_str())
+        }
+        None => return Vec::new(),
+    };
+    let (done, partial) = value
+        .rfind([',', '='])
+        .map_or(("", value), |i| value.split_at(i + 1));
+
+    table_columns(earlier)
+        .into_iter()
+        .filter(|column| column.starts_with(partial))
+        .map(|column| format!("{option}{done}{column}"))
+        .collect()
+}
+
+/// The columns of the tables given as arguments in `words`, in order without repeats
+fn table_columns(words: &[String]) -> Vec<String> {
+    let takes_value = value_options(words);
+    let delimiter = words
+        .iter()
+        .position(|w| w == "--delimiter")
+        .and_then(|i| words.get(i + 1))
+        .and_then(|d| d.chars().exactly_one().ok())
+        .unwrap_or(',');
+
+    words
+        .iter()
+        .enumerate()
+        .skip(1)
+        .filter(|(i, word)| {
+            !word.starts_with('-')
+                && !takes_value.contains(&words[i - 1])
+                && Path::new(word.as_str()).is_file()
+        })
+        .filter_map(|(_, path)| {
+            scan_data(path, Some(delimiter), &GlobalArgs::default())
+                .ok()?
+                .collect_schema()
+                .ok()
+        })
+        .flat_map(|schema| {
+            schema
+                .iter_names()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+        })
+        .unique()
+        .collect()
+}
+
+/// Long options which take a value, for the subcommand among `words` and globally
+fn value_options(words: &[String]) -> Vec<String> {
+    let cmd = RabbetArgs::command();
+    let subcommand = words.iter().find_map(|w| cmd.find_subcommand(w));
+    cmd.get_arguments()
+        .chain(
+            subcommand
+                .into_iter()
+                .flat_map(clap::Command::get_arguments),
+        )
+        .filter(|arg| arg.get_action().takes_values())
+        .filter_map(|arg| arg.get_long().map(|long| format!("--{long}")))
+        .collect()
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn complete(line: &str) -> Vec<String> {
+        candidates(&split_words(line))
+    }
+
+    #[test]
+    fn test_split_words() {
+        assert_eq!(split_words("rabbet head"), ["rabbet", "head"]);
+        assert_eq!(split_words("rabbet head "), ["rabbet", "head", ""]);
+        assert_eq!(
+            split_words("rabbet  grep 'a b' \"c"),
+            ["rabbet", "grep", "a b", "c"]
+        );
+    }
+
+    #[test]
+    fn test_candidates() {
+        let line = "rabbet join data/orders/orders.csv data/orders/customers.csv --on ";
+        let columns = complete(&format!("{line}customer_"));
+        assert_eq!(columns[0], "customer_id");
+        assert!(columns.contains(&"customer_email".to_string()));
+        assert!(!columns.contains(&"order_id".to_string()));
+
+        assert_eq!(
+            complete(&format!("{line}customer_id=ord")),
+            ["customer_id=order_id", "customer_id=order_date"]
+        );
+        assert_eq!(
+            complete("rabbet grep data/orders/orders.csv x --columns=order_id,pri"),
+            ["--columns=order_id,price"]
+        );
+
+        // Only values of the column options, and only from tables
+        assert!(complete("rabbet grep data/orders/orders.csv ").is_empty());
+        assert!(complete("rabbet grep data/orders/missing.csv x --columns ").is_empty());
+        assert!(
+            complete("rabbet head --fwf-spec data/bank/transactions-spec.csv --columns ")
+                .is_empty()
+        );
+    }
+}
@@ -0,0 +1,214 @@
+// Stack several tables into one, e.g. to combine monthly exports without a join.
+use anyhow::{Context, Result, bail, ensure};
+use clap::{Args, ValueEnum, ValueHint};
+use polars::prelude::{
+    DataFrame, IntoLazy, LazyFrame, UnionArgs, concat, concat_lf_diagonal,
+    concat_lf_horizontal,
+};
+
+use crate::args::GlobalArgs;
+use crate::io::{read_data, write_data};
+
+#[derive(Debug, Clone, Copy, ValueEnum, PartialEq, Eq)]
+pub enum ConcatMethod {
+    /// Append rows, tables must have the same columns
+    Vertical,
+    /// Append rows, filling columns missing from a table with nulls
+    Diagonal,
+    /// Place tables side by side, padding shorter tables with nulls
+    Horizontal,
+}
+
+#[derive(Args, Debug)]
+pub struct ConcatArgs {
+    /// Input tables (files or '-' for stdin)
+    ///
+    /// Examples: january.csv february.csv march.csv
+    #[arg(required = true, value_hint = ValueHint::FilePath)]
+    pub tables: Vec<String>,
+
+    /// How to combine the tables
+    #[arg(long, value_enum, default_value = "vertical")]
+    pub how: ConcatMethod,
+
+    /// Fail if column types differ between tables instead of casting to a common type
+    ///
+    /// By default mismatched columns are widened (e.g., integers and floats become floats).
+    #[arg(long)]
+    pub strict_schema: bool,
+
+    /// Delimiter for input files
+    #[arg(long, default_value = ",")]
+    pub delimiter: char,
+}
+
+impl ConcatArgs {
+    pub fn validate(&self) -> Result<()> {
+        ensure!(
+            !self.tables.is_empty(),
+            "At least one table is required for concatenation"
+        );
+        ensure!(
+            self.tables.iter().filter(|t| *t == "-").count() <= 1,
+            "stdin ('-') can only be used for one table"
+        );
+
+        Ok(())
+    }
+
+    pub fn execute(&self, global: &GlobalArgs) -> Result<()> {
+        let frames = self
+            .tables
+            .iter()
+            .map(|table| {
+                read_data(table, Some(self.delimiter), global)
+                    .with_context(|| format!("concat - failed to read table '{table}'"))
+            })
+            .collect::<Result<Vec<DataFrame>>>()?;
+
+        if self.how == ConcatMethod::Vertical {
+            check_columns(&self.tables, &frames)?;
+        }
+
+        let frames: Vec<LazyFrame> = frames.into_iter().map(IntoLazy::lazy).collect();
+        let result = stack(&frames, self.how, self.strict_schema)?
+            .collect()
+            .with_context(|| {
+                format!("concat - failed to stack tables {:?}", self.tables)
+            })?;
+
+        write_data(result, global)
+            .with_context(|| "concat - failed to write data to stdout".to_string())?;
+
+        Ok(())
+    }
+}
+
+/// Ensures every table has the same columns as the first, as vertical stacking requires
+fn check_columns(tables: &[String], frames: &[DataFrame]) -> Result<()> {
+    let Some(first) = frames.first() else {
+        return Ok(());
+    };
+    let expected = first.get_column_names();
+
+    for (table, df) in tables.iter().zip(frames).skip(1) {
+        let columns = df.get_column_names();
+        if columns != expected {
+            bail!(
+                "concat - '{table}' has columns [{}] but '{}' has [{}]; use --how diagonal to fill missing columns with nulls",
+                columns
+                    .iter()
+                    .map(|c| c.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                tables[0],
+                expected
+                    .iter()
+                    .map(|c| c.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Combines the frames according to `how`
+///
+/// Unless `strict` is set, vertical and diagonal stacking cast mismatched columns to
+/// their common supertype.
+fn stack(frames: &[LazyFrame], how: ConcatMethod, strict: bool) -> Result<LazyFrame> {
+    let args = UnionArgs {
+        to_supertypes: !strict,
+        ..UnionArgs::default()
+    };
+
+    let lf = match how {
+        ConcatMethod::Vertical => concat(frames, args),
+        ConcatMethod::Diagonal => concat_lf_diagonal(frames, args),
+        ConcatMethod::Horizontal => concat_lf_horizontal(frames, args),
+    };
+
+    Ok(lf?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use polars::df;
+    use polars::prelude::DataType;
+
+    #[test]
+    fn test_validate_multiple_stdin() {
+        let args = ConcatArgs {
+            tables: vec!["-".to_string(), "-".to_string()],
+            how: ConcatMethod::Vertical,
+            strict_schema: false,
+            delimiter: ',',
+        };
+        assert!(args.validate().is_err());
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn test_check_columns() {
+        let tables = vec!["a.csv".to_string(), "b.csv".to_string()];
+        let a = df! { "id" => [1], "amount" => [10] }.unwrap();
+        let b = df! { "id" => [2], "note" => ["late"] }.unwrap();
+
+        assert!(check_columns(&tables, &[a.clone(), a.clone()]).is_ok());
+        assert_eq!(
+            check_columns(&tables, &[a, b]).unwrap_err().to_string(),
+            "concat - 'b.csv' has columns [id, note] but 'a.csv' has [id, amount]; use --how diagonal to fill missing columns with nulls"
+        );
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn test_stack_vertical_coerces_types() {
+        let frames = [
+            df! { "id" => [1, 2], "amount" => [10, 20] }.unwrap().lazy(),
+            df! { "id" => [3], "amount" => [2.5] }.unwrap().lazy(),
+        ];
+
+        let df = stack(&frames, ConcatMethod::Vertical, false)
+            .unwrap()
+            .collect()
+            .unwrap();
+        assert_eq!(df.shape(), (3, 2));
+        assert_eq!(df.column("amount").unwrap().dtype(), &DataType::Float64);
+
+        let strict = stack(&frames, ConcatMethod::Vertical, true)
+            .unwrap()
+            .collect();
+        assert!(strict.is_err());
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn test_stack_diagonal_and_horizontal() {
+        let frames = [
+            df! { "id" => [1, 2] }.unwrap().lazy(),
+            df! { "id" => [3], "note" => ["late"] }.unwrap().lazy(),
+        ];
+
+        let df = stack(&frames, ConcatMethod::Diagonal, false)
+            .unwrap()
+            .collect()
+            .unwrap();
+        assert_eq!(df.get_column_names(), &["id", "note"]);
+        assert_eq!(df.column("note").unwrap().null_count(), 2);
+
+        let frames = [
+            df! { "id" => [1, 2] }.unwrap().lazy(),
+            df! { "note" => ["late"] }.unwrap().lazy(),
+        ];
+        let df = stack(&frames, ConcatMethod::Horizontal, false)
+            .unwrap()
+            .collect()
+            .unwrap();
+        assert_eq!(df.shape(), (2, 2));
+        assert_eq!(df.column("note").unwrap().null_count(), 1);
+    }
+}
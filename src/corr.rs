@@ -0,0 +1,212 @@
+// Correlation matrix between the numeric columns of a table.
+use anyhow::{Context, Result, ensure};
+use clap::{Args, ValueEnum, ValueHint};
+use itertools::Itertools;
+use polars::prelude::*;
+
+use crate::args::GlobalArgs;
+use crate::io::{resolve_columns, scan_data, write_data};
+
+#[derive(Debug, Clone, Copy, ValueEnum, PartialEq, Eq)]
+pub enum Method {
+    /// Linear correlation of the values
+    Pearson,
+    /// Correlation of the ranks of the values, for any monotonic relationship
+    Spearman,
+}
+
+#[derive(Args, Debug)]
+pub struct CorrArgs {
+    /// Input table (file or '-' for stdin)
+    #[arg(required = true, value_hint = ValueHint::FilePath)]
+    pub table: String,
+
+    /// Numeric columns to correlate (comma separated) [default: every numeric column]
+    ///
+    /// Examples: --columns "SepalLength,PetalLength,PetalWidth"
+    #[arg(long, value_delimiter = ',')]
+    pub columns: Vec<String>,
+
+    /// Correlation coefficient to compute
+    #[arg(long, value_enum, default_value = "pearson")]
+    pub method: Method,
+
+    /// Delimiter for input files
+    #[arg(long, default_value = ",")]
+    pub delimiter: char,
+}
+
+impl CorrArgs {
+    pub fn validate(&self) -> Result<()> {
+        ensure!(
+            self.columns.iter().all_unique(),
+            "--columns can't repeat a column"
+        );
+        ensure!(
+            self.columns.is_empty() || self.columns.len() >= 2,
+            "--columns needs at least 2 columns to correlate"
+        );
+
+        Ok(())
+    }
+
+    pub fn execute(&self, global: &GlobalArgs) -> Result<()> {
+        let mut lf =
+            scan_data(&self.table, Some(self.delimiter), global).with_context(|| {
+                format!("corr - failed to read csv data from {}", self.table)
+            })?;
+        let schema = lf.collect_schema()?;
+        let header = DataFrame::empty_with_schema(&schema);
+
+        let columns = if self.columns.is_empty() {
+            let numeric: Vec<String> = header
+                .get_columns()
+                .iter()
+                .filter(|c| c.dtype().is_primitive_numeric())
+                .map(|c| c.name().to_string())
+                .collect();
+            ensure!(
+                numeric.len() >= 2,
+                "corr - {} has fewer than 2 numeric columns to correlate",
+                self.table
+            );
+            numeric
+        } else {
+            resolve_columns(&header, &self.columns, global.ignore_column_case)?
+        };
+        for column in &columns {
+            let dtype = header.column(column)?.dtype();
+            ensure!(
+                dtype.is_primitive_numeric(),
+                "corr - column '{column}' must be numeric, found {dtype}"
+            );
+        }
+
+        let coefficients = lf
+            .select(self.coefficients(&columns))
+            .collect()
+            .with_context(|| {
+                format!("corr - failed to correlate columns of {}", self.table)
+            })?;
+
+        write_data(matrix(&coefficients, &columns)?, global)
+            .with_context(|| "corr - failed to write data to stdout".to_string())?;
+
+        Ok(())
+    }
+
+    /// The coefficient of each pair of `columns`, named `a|b`, over the rows where both
+    /// have values
+    fn coefficients(&self, columns: &[String]) -> Vec<Expr> {
+        columns
+            .iter()
+            .tuple_combinations()
+            .map(|(a, b)| {
+                let both = col(a.as_str())
+                    .is_not_null()
+                    .and(col(b.as_str()).is_not_null());
+                let x = col(a.as_str()).filter(both.clone()).cast(DataType::Float64);
+                let y = col(b.as_str()).filter(both).cast(DataType::Float64);
+                let coefficient = match self.method {
+                    Method::Pearson => pearson_corr(x, y),
+                    Method::Spearman => spearman_rank_corr(x, y, false),
+                };
+                coefficient.alias(format!("{a}|{b}"))
+            })
+            .collect()
+    }
+}
+
+/// The symmetric matrix of the pairwise `coefficients`, one row per column, led by a
+/// `column` column naming it
+fn matrix(coefficients: &DataFrame, columns: &[String]) -> Result<DataFrame> {
+    let coefficient = |a: &str, b: &str| -> Result<Option<f64>> {
+        Ok(coefficients.column(&format!("{a}|{b}"))?.f64()?.get(0))
+    };
+    let mut matrix = vec![Column::new("column".into(), columns)];
+    for (j, b) in columns.iter().enumerate() {
+        let values = columns
+            .iter()
+            .enumerate()
+            .map(|(i, a)| match i.cmp(&j) {
+                std::cmp::Ordering::Equal => Ok(Some(1.0)),
+                std::cmp::Ordering::Less => coefficient(a, b),
+                std::cmp::Ordering::Greater => coefficient(b, a),
+            })
+            .collect::<Result<Vec<_>>>()?;
+        matrix.push(Column::new(b.as_str().into(), values));
+    }
+
+    Ok(DataFrame::new(matrix)?)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn args(method: Method) -> CorrArgs {
+        CorrArgs {
+            table: "data/iris/iris.csv".to_string(),
+            columns: vec![],
+            method,
+            delimiter: ',',
+        }
+    }
+
+    fn correlate(method: Method) -> DataFrame {
+        let df = df!(
+            "x" => [Some(1.0), Some(2.0), Some(3.0), Some(4.0), None],
+            "y" => [Some(2.0), Some(4.0), Some(6.0), Some(20.0), Some(1.0)],
+            "z" => [4, 3, 2, 1, 0],
+        )
+        .unwrap();
+        let columns = ["x".to_string(), "y".to_string(), "z".to_string()];
+        let coefficients = df
+            .lazy()
+            .select(args(method).coefficients(&columns))
+            .collect()
+            .unwrap();
+        matrix(&coefficients, &columns).unwrap()
+    }
+
+    fn values(df: &DataFrame, column: &str) -> Vec<f64> {
+        df.column(column)
+            .unwrap()
+            .f64()
+            .unwrap()
+            .into_iter()
+            .map(|v| (v.unwrap() * 1000.0).round() / 1000.0)
+            .collect()
+    }
+
+    #[test]
+    fn test_spearman() {
+        let df = correlate(Method::Spearman);
+        assert_eq!(df.get_column_names_str(), ["column", "x", "y", "z"]);
+        assert_eq!(values(&df, "x"), [1.0, 1.0, -1.0]);
+        assert_eq!(values(&df, "z"), [-1.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn test_pearson() {
+        let df = correlate(Method::Pearson);
+        assert_eq!(values(&df, "x"), [1.0, 0.885, -1.0]);
+        assert_eq!(values(&df, "y"), [0.885, 1.0, -0.286]);
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut args = args(Method::Pearson);
+        assert!(args.validate().is_ok());
+        args.columns = vec!["SepalLength".to_string()];
+        assert!(args.validate().is_err());
+    }
+
+    #[test]
+    fn test_corr_iris_csv() {
+        let args = args(Method::Spearman);
+        assert!(args.validate().is_ok());
+        assert!(args.execute(&GlobalArgs::default()).is_ok());
+    }
+}
@@ -0,0 +1,330 @@
+// Cross tabulate two sets of columns, counting (or aggregating) the rows of each combination.
+use anyhow::{Context, Result, ensure};
+use clap::{Args, ValueEnum, ValueHint};
+use itertools::Itertools;
+use polars::prelude::*;
+
+use crate::args::GlobalArgs;
+use crate::io::{resolve_columns, scan_data, write_data};
+
+/// Name of the margin row and column added by --margins
+const TOTAL: &str = "Total";
+
+#[derive(Debug, Clone, Copy, ValueEnum, PartialEq, Eq)]
+pub enum Agg {
+    Count,
+    Sum,
+    Mean,
+    Median,
+    Min,
+    Max,
+}
+
+impl Agg {
+    /// The aggregation of `values`
+    fn apply(self, values: Expr) -> Expr {
+        match self {
+            Self::Count => values.count(),
+            Self::Sum => values.sum(),
+            Self::Mean => values.mean(),
+            Self::Median => values.median(),
+            Self::Min => values.min(),
+            Self::Max => values.max(),
+        }
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct CrosstabArgs {
+    /// Input table (file or '-' for stdin)
+    #[arg(required = true, value_hint = ValueHint::FilePath)]
+    pub table: String,
+
+    /// Columns whose values (or combinations of values) become the rows (comma separated)
+    ///
+    /// Examples: --rows region or --rows "region,month"
+    #[arg(long, required = true, value_delimiter = ',')]
+    pub rows: Vec<String>,
+
+    /// Column whose values become the columns
+    ///
+    /// Examples: --cols product
+    #[arg(long, required = true)]
+    pub cols: String,
+
+    /// Column to aggregate in each cell, instead of counting rows
+    ///
+    /// Examples: --values amount --agg mean
+    #[arg(long)]
+    pub values: Option<String>,
+
+    /// How to aggregate --values in each cell [default: sum with --values, otherwise count]
+    #[arg(long, value_enum, requires = "values")]
+    pub agg: Option<Agg>,
+
+    /// Add a Total column and row aggregating each row, each column and the whole table
+    #[arg(long)]
+    pub margins: bool,
+
+    /// Delimiter for input files
+    #[arg(long, default_value = ",")]
+    pub delimiter: char,
+}
+
+impl CrosstabArgs {
+    pub fn validate(&self) -> Result<()> {
+        ensure!(
+            self.rows.iter().all_unique(),
+            "--rows can't repeat a column"
+        );
+        ensure!(
+            !self.rows.contains(&self.cols),
+            "--cols '{}' can't also be one of --rows",
+            self.cols
+        );
+        if let Some(values) = &self.values {
+            ensure!(
+                *values != self.cols && !self.rows.contains(values),
+                "--values '{values}' can't also be --rows or --cols"
+            );
+        }
+
+        Ok(())
+    }
+
+    pub fn execute(&self, global: &GlobalArgs) -> Result<()> {
+        let mut lf =
+            scan_data(&self.table, Some(self.delimiter), global).with_context(|| {
+                format!("crosstab - failed to read csv data from {}", self.table)
+            })?;
+        let schema = lf.collect_schema()?;
+        let header = DataFrame::empty_with_schema(&schema);
+
+        let rows = resolve_columns(&header, &self.rows, global.ignore_column_case)?;
+        let cols = resolve_columns(
+            &header,
+            std::slice::from_ref(&self.cols),
+            global.ignore_column_case,
+        )?
+        .remove(0);
+        let values =
+            resolve_columns(&header, self.values.as_slice(), global.ignore_column_case)?
+                .pop();
+
+        let labels = labels(lf.clone(), &cols).with_context(|| {
+            format!(
+                "crosstab - failed to read the values of {cols} in {}",
+                self.table
+            )
+        })?;
+        for label in &labels {
+            ensure!(
+                !rows.contains(label) && (!self.margins || label != TOTAL),
+                "crosstab - {cols} value '{label}' would clash with a column of the same name"
+            );
+        }
+
+        let result = self
+            .crosstab(lf, &rows, &cols, values.as_deref(), &labels)?
+            .collect()
+            .with_context(|| {
+                format!("crosstab - failed to cross tabulate {}", self.table)
+            })?;
+
+        write_data(result, global)
+            .with_context(|| "crosstab - failed to write data to stdout".to_string())?;
+
+        Ok(())
+    }
+
+    /// One row for each combination of `rows` values and one column for each of `labels`,
+    /// holding the count of rows (or the aggregated `values`) with those values
+    ///
+    /// Rows are ordered by value, with nulls last. Cells without any rows are null, except
+    /// for counts which are 0. With --margins, the rows become text so the last row can be
+    /// labelled Total.
+    fn crosstab(
+        &self,
+        lf: LazyFrame,
+        rows: &[String],
+        cols: &str,
+        values: Option<&str>,
+        labels: &[String],
+    ) -> Result<LazyFrame> {
+        // Without --values the cells count rows, so --agg only applies to --values
+        let agg = self.agg.unwrap_or(Agg::Sum);
+        let cell = |mask: Option<Expr>| -> Expr {
+            match (values, mask) {
+                (None, Some(mask)) => mask.cast(DataType::UInt32).sum(),
+                (None, None) => len(),
+                (Some(values), Some(mask)) if agg != Agg::Count => {
+                    when(mask.clone().any(true))
+                        .then(agg.apply(col(values).filter(mask)))
+                        .otherwise(lit(NULL))
+                }
+                (Some(values), Some(mask)) => agg.apply(col(values).filter(mask)),
+                (Some(values), None) => agg.apply(col(values)),
+            }
+        };
+
+        let mut cells: Vec<Expr> = labels
+            .iter()
+            .map(|label| cell(Some(label_mask(cols, label))).alias(label.as_str()))
+            .collect();
+        if self.margins {
+            cells.push(cell(None).alias(TOTAL));
+        }
+
+        let table = lf
+            .clone()
+            .group_by(rows.iter().map(|c| col(c.as_str())).collect::<Vec<_>>())
+            .agg(cells.clone())
+            .sort(
+                rows,
+                SortMultipleOptions::default()
+                    .with_nulls_last(true)
+                    .with_maintain_order(true),
+            );
+        if !self.margins {
+            return Ok(table);
+        }
+
+        let text: Vec<Expr> = rows
+            .iter()
+            .map(|c| col(c.as_str()).cast(DataType::String))
+            .collect();
+        let mut names: Vec<Expr> = rows
+            .iter()
+            .map(|c| lit(NULL).cast(DataType::String).alias(c.as_str()))
+            .collect();
+        names[0] = lit(TOTAL).alias(rows[0].as_str());
+        let totals = lf.select(cells).with_columns(names);
+        let order: Vec<Expr> = rows
+            .iter()
+            .map(String::as_str)
+            .chain(labels.iter().map(String::as_str))
+            .chain([TOTAL])
+            .map(col)
+            .collect();
+
+        Ok(concat(
+            [
+                table.with_columns(text).select(&order),
+                totals.select(&order),
+            ],
+            UnionArgs {
+                to_supertypes: true,
+                ..UnionArgs::default()
+            },
+        )?)
+    }
+}
+
+/// The distinct values of `cols` as text, ordered by value with nulls last as "null"
+pub fn labels(lf: LazyFrame, cols: &str) -> Result<Vec<String>> {
+    let distinct = lf
+        .select([col(cols).unique().sort(SortOptions {
+            nulls_last: true,
+            ..SortOptions::default()
+        })])
+        .collect()?;
+    let distinct = distinct.column(cols)?.cast(&DataType::String)?;
+
+    Ok(distinct
+        .str()?
+        .into_iter()
+        .map(|v| v.unwrap_or("null").to_string())
+        .collect())
+}
+
+/// Mask of the rows where `cols` has the value `label`, from `labels`
+pub fn label_mask(cols: &str, label: &str) -> Expr {
+    let key = col(cols).cast(DataType::String);
+    if label == "null" {
+        key.is_null()
+    } else {
+        key.eq(lit(label)).fill_null(lit(false))
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn crosstab(values: Option<&str>, agg: Option<Agg>, margins: bool) -> CrosstabArgs {
+        CrosstabArgs {
+            table: "data/sales/q1.csv".to_string(),
+            rows: vec!["region".to_string()],
+            cols: "product".to_string(),
+            values: values.map(str::to_string),
+            agg,
+            margins,
+            delimiter: ',',
+        }
+    }
+
+    fn cells(args: &CrosstabArgs, name: &str) -> Vec<Option<f64>> {
+        let df = df!(
+            "region" => ["north", "south", "north", "north"],
+            "product" => [Some("a"), Some("a"), Some("b"), None],
+            "amount" => [1.0, 2.0, 4.0, 8.0],
+        )
+        .unwrap();
+        let rows = ["region".to_string()];
+        let values = args.values.as_deref();
+        let labels = labels(df.clone().lazy(), "product").unwrap();
+        assert_eq!(labels, ["a", "b", "null"]);
+
+        args.crosstab(df.lazy(), &rows, "product", values, &labels)
+            .unwrap()
+            .collect()
+            .unwrap()
+            .column(name)
+            .unwrap()
+            .cast(&DataType::Float64)
+            .unwrap()
+            .f64()
+            .unwrap()
+            .into_iter()
+            .collect()
+    }
+
+    #[test]
+    fn test_crosstab() {
+        let args = crosstab(None, None, true);
+        assert_eq!(cells(&args, "a"), [Some(1.0), Some(1.0), Some(2.0)]);
+        assert_eq!(cells(&args, "null"), [Some(1.0), Some(0.0), Some(1.0)]);
+        assert_eq!(cells(&args, TOTAL), [Some(3.0), Some(1.0), Some(4.0)]);
+
+        let args = crosstab(Some("amount"), None, false);
+        assert_eq!(cells(&args, "b"), [Some(4.0), None]);
+
+        let args = crosstab(Some("amount"), Some(Agg::Mean), true);
+        assert_eq!(cells(&args, "a"), [Some(1.0), Some(2.0), Some(1.5)]);
+        assert_eq!(
+            cells(&args, TOTAL),
+            [Some(13.0 / 3.0), Some(2.0), Some(3.75)]
+        );
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(crosstab(Some("amount"), None, false).validate().is_ok());
+
+        let mut args = crosstab(Some("product"), None, false);
+        assert!(args.validate().is_err());
+        args.values = None;
+        args.rows.push("product".to_string());
+        assert!(args.validate().is_err());
+        args.rows = vec!["region".to_string(), "region".to_string()];
+        assert!(args.validate().is_err());
+    }
+
+    #[test]
+    fn test_crosstab_q1_csv() {
+        let args = crosstab(Some("amount"), Some(Agg::Max), true);
+        assert!(args.validate().is_ok());
+        assert!(args.execute(&GlobalArgs::default()).is_ok());
+    }
+}
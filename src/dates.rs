@@ -0,0 +1,260 @@
+// Derive date parts and time buckets from a date or datetime column, e.g. for aggregating by month.
+use anyhow::{Context, Result, bail, ensure};
+use clap::{Args, ValueEnum, ValueHint};
+use polars::prelude::*;
+use regex::Regex;
+
+use crate::args::GlobalArgs;
+use crate::io::{convert_expr, read_data, resolve_columns, write_data};
+use crate::suggest_types::detect_format;
+
+#[allow(clippy::expect_used)]
+static EVERY: std::sync::LazyLock<Regex> = std::sync::LazyLock::new(|| {
+    Regex::new(r"^(\d+(ns|us|ms|s|m|h|d|w|mo|q|y))+$").expect("Invalid regex pattern")
+});
+
+#[derive(Debug, Clone, Copy, ValueEnum, PartialEq, Eq)]
+pub enum DatePart {
+    Year,
+    /// Quarter of the year (1-4)
+    Quarter,
+    Month,
+    /// ISO week of the year (1-53)
+    Week,
+    /// Day of the month
+    Day,
+    /// ISO day of the week, from 1 (Monday) to 7 (Sunday)
+    Weekday,
+    /// Day of the year (1-366)
+    Yearday,
+    Hour,
+    Minute,
+    Second,
+    /// The date without the time of day
+    Date,
+}
+
+impl DatePart {
+    /// Expression computing this part of `column`
+    fn expr(self, column: &str) -> Expr {
+        let dt = col(column).dt();
+        match self {
+            Self::Year => dt.year(),
+            Self::Quarter => dt.quarter(),
+            Self::Month => dt.month(),
+            Self::Week => dt.week(),
+            Self::Day => dt.day(),
+            Self::Weekday => dt.weekday(),
+            Self::Yearday => dt.ordinal_day(),
+            Self::Hour => dt.hour(),
+            Self::Minute => dt.minute(),
+            Self::Second => dt.second(),
+            Self::Date => dt.date(),
+        }
+    }
+
+    /// Suffix for the derived column's name
+    fn name(self) -> String {
+        self.to_possible_value()
+            .map(|v| v.get_name().to_string())
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct DatesArgs {
+    /// Input table (file or '-' for stdin)
+    #[arg(required = true, value_hint = ValueHint::FilePath)]
+    pub table: String,
+
+    /// Date or datetime column to derive from (strings are parsed with an inferred format)
+    #[arg(long, required = true)]
+    pub column: String,
+
+    /// Parts to extract into new `<column>_<part>` columns (comma separated)
+    ///
+    /// Examples: --extract year,month or --extract "weekday,hour"
+    #[arg(long, value_enum, value_delimiter = ',')]
+    pub extract: Vec<DatePart>,
+
+    /// Intervals to truncate to, each into a new `<column>_<interval>` column (comma separated)
+    ///
+    /// Units: ns, us, ms, s, m (minutes), h, d, w, mo, q, y.
+    /// Examples: --truncate 1h or --truncate "15m,1d"
+    #[arg(long, value_delimiter = ',')]
+    pub truncate: Vec<String>,
+
+    /// Delimiter for input files
+    #[arg(long, default_value = ",")]
+    pub delimiter: char,
+}
+
+impl DatesArgs {
+    pub fn validate(&self) -> Result<()> {
+        if self.extract.is_empty() && self.truncate.is_empty() {
+            bail!("Nothing to derive. Pass --extract and/or --truncate");
+        }
+
+        for every in &self.truncate {
+            ensure!(
+                EVERY.is_match(every),
+                "Invalid --truncate interval '{every}'. Expected a number and a unit, e.g. 15m, 1h, 1d or 1mo"
+            );
+        }
+
+        Ok(())
+    }
+
+    pub fn execute(&self, global: &GlobalArgs) -> Result<()> {
+        let df =
+            read_data(&self.table, Some(self.delimiter), global).with_context(|| {
+                format!("dates - failed to read csv data from {}", self.table)
+            })?;
+
+        let column = resolve_columns(
+            &df,
+            std::slice::from_ref(&self.column),
+            global.ignore_column_case,
+        )?
+        .remove(0);
+        let result =
+            derive(df, &column, &self.extract, &self.truncate).with_context(|| {
+                format!("dates - failed to derive from '{column}' in {}", self.table)
+            })?;
+
+        write_data(result, global)
+            .with_context(|| "dates - failed to write data to stdout".to_string())?;
+
+        Ok(())
+    }
+}
+
+/// Adds a column for each part of `column` in `extract` and each interval in `truncate`
+///
+/// String columns are parsed first, with the date or datetime format `suggest-types` detects.
+fn derive(
+    df: DataFrame,
+    column: &str,
+    extract: &[DatePart],
+    truncate: &[String],
+) -> Result<DataFrame> {
+    let dtype = df.column(column)?.dtype().clone();
+    let parse = match dtype {
+        DataType::Date | DataType::Datetime(..) => None,
+        DataType::String => {
+            let (temporal, format) = match detect_format(&df, column)? {
+                ("date", Some(format)) => (DataType::Date, format),
+                ("datetime", Some(format)) => {
+                    (DataType::Datetime(TimeUnit::Microseconds, None), format)
+                }
+                _ => bail!(
+                    "Couldn't detect a date format in column '{column}'. Parse it with --parse-dates"
+                ),
+            };
+            Some(convert_expr(column, &dtype, &temporal, Some(format), true))
+        }
+        _ => bail!(
+            "Column '{column}' must be a date, datetime or string column, found {dtype}"
+        ),
+    };
+
+    let mut lf = df.lazy();
+    if let Some(parse) = parse {
+        lf = lf.with_column(parse);
+    }
+
+    let exprs: Vec<Expr> = extract
+        .iter()
+        .map(|part| part.expr(column).alias(format!("{column}_{}", part.name())))
+        .chain(truncate.iter().map(|every| {
+            col(column)
+                .dt()
+                .truncate(lit(every.as_str()))
+                .alias(format!("{column}_{every}"))
+        }))
+        .collect();
+
+    Ok(lf.with_columns(exprs).collect()?)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_derive() {
+        let df = df!(
+            "ts" => ["2024-03-01T10:15:00", "2024-03-03T23:59:59"],
+        )
+        .unwrap();
+
+        let result = derive(
+            df,
+            "ts",
+            &[DatePart::Month, DatePart::Weekday, DatePart::Hour],
+            &["1h".to_string()],
+        )
+        .unwrap();
+        assert_eq!(
+            result.get_column_names(),
+            &["ts", "ts_month", "ts_weekday", "ts_hour", "ts_1h"]
+        );
+        let weekdays: Vec<_> = result
+            .column("ts_weekday")
+            .unwrap()
+            .cast(&DataType::Int64)
+            .unwrap()
+            .i64()
+            .unwrap()
+            .to_vec();
+        assert_eq!(weekdays, [Some(5), Some(7)]);
+        let hours = result
+            .column("ts_1h")
+            .unwrap()
+            .cast(&DataType::String)
+            .unwrap();
+        assert_eq!(
+            hours.str().unwrap().get(1),
+            Some("2024-03-03 23:00:00.000000")
+        );
+    }
+
+    #[test]
+    fn test_derive_requires_temporal() {
+        let df = df!("id" => [1, 2]).unwrap();
+        assert!(derive(df, "id", &[DatePart::Year], &[]).is_err());
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut args = DatesArgs {
+            table: "data/sensors/readings.csv".to_string(),
+            column: "timestamp".to_string(),
+            extract: vec![],
+            truncate: vec![],
+            delimiter: ',',
+        };
+        assert!(args.validate().is_err());
+
+        args.truncate = vec!["1h".to_string(), "15m".to_string()];
+        assert!(args.validate().is_ok());
+
+        args.truncate = vec!["hourly".to_string()];
+        assert!(args.validate().is_err());
+    }
+
+    #[test]
+    fn test_dates_readings_csv() {
+        let args = DatesArgs {
+            table: "data/sensors/readings.csv".to_string(),
+            column: "timestamp".to_string(),
+            extract: vec![DatePart::Date, DatePart::Hour],
+            truncate: vec!["6h".to_string()],
+            delimiter: ',',
+        };
+
+        assert!(args.validate().is_ok());
+        assert!(args.execute(&GlobalArgs::default()).is_ok());
+    }
+}
@@ -0,0 +1,222 @@
+// Print a CREATE TABLE statement for the schema of a table, to load it into a database.
+use std::path::Path;
+
+use anyhow::{Context, Result, ensure};
+use clap::{Args, ValueEnum, ValueHint};
+use polars::prelude::*;
+
+use crate::args::GlobalArgs;
+use crate::io::{scan_data, write_text};
+use crate::sqlite::{affinity, quote_identifier};
+
+#[derive(Debug, Clone, Copy, ValueEnum, PartialEq, Eq)]
+#[allow(clippy::doc_markdown)]
+pub enum Database {
+    /// PostgreSQL
+    Postgres,
+    /// MySQL and MariaDB
+    Mysql,
+    /// SQLite, with the types `--format sqlite` writes
+    Sqlite,
+    /// DuckDB
+    Duckdb,
+}
+
+#[derive(Args, Debug)]
+pub struct DdlArgs {
+    /// Input table (file or '-' for stdin)
+    #[arg(required = true, value_hint = ValueHint::FilePath)]
+    pub table: String,
+
+    /// Database to write the statement for
+    #[arg(long, value_enum, default_value = "postgres")]
+    pub database: Database,
+
+    /// Name of the table to create [default: the file name without its extension]
+    #[arg(long)]
+    pub name: Option<String>,
+
+    /// Delimiter for input files
+    #[arg(long, default_value = ",")]
+    pub delimiter: char,
+}
+
+impl DdlArgs {
+    pub fn validate(&self) -> Result<()> {
+        ensure!(
+            self.name.as_ref().is_none_or(|name| !name.is_empty()),
+            "--name can't be empty"
+        );
+
+        Ok(())
+    }
+
+    pub fn execute(&self, global: &GlobalArgs) -> Result<()> {
+        let mut lf =
+            scan_data(&self.table, Some(self.delimiter), global).with_context(|| {
+                format!("ddl - failed to read csv data from {}", self.table)
+            })?;
+        let schema = lf.collect_schema().with_context(|| {
+            format!("ddl - failed to read the schema of {}", self.table)
+        })?;
+
+        write_text(&self.create_table(&schema), global)
+            .with_context(|| "ddl - failed to write data to stdout".to_string())?;
+
+        Ok(())
+    }
+
+    /// The table name, from --name or the input file
+    fn name(&self) -> String {
+        self.name.clone().unwrap_or_else(|| {
+            Path::new(&self.table)
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .filter(|stem| self.table != "-" && !stem.is_empty())
+                .unwrap_or_else(|| "data".to_string())
+        })
+    }
+
+    /// The CREATE TABLE statement for `schema`, one column per line
+    fn create_table(&self, schema: &Schema) -> String {
+        let columns: Vec<String> = schema
+            .iter()
+            .map(|(name, dtype)| {
+                format!(
+                    "    {} {}",
+                    self.quote(name),
+                    sql_type(dtype, self.database)
+                )
+            })
+            .collect();
+
+        format!(
+            "CREATE TABLE {} (\n{}\n);",
+            self.quote(&self.name()),
+            columns.join(",\n")
+        )
+    }
+
+    /// Quotes a table or column name, so reserved words such as `date` and `order` work
+    fn quote(&self, name: &str) -> String {
+        match self.database {
+            Database::Mysql => format!("`{}`", name.replace('`', "``")),
+            _ => quote_identifier(name),
+        }
+    }
+}
+
+/// The column type holding values of `dtype` in `database`, falling back to text
+fn sql_type(dtype: &DataType, database: Database) -> String {
+    use Database::{Duckdb, Mysql, Postgres, Sqlite};
+
+    if database == Sqlite {
+        return affinity(dtype).to_string();
+    }
+    let name = match (dtype, database) {
+        (DataType::Boolean, _) => "BOOLEAN",
+        (DataType::Int8 | DataType::UInt8, Postgres) | (DataType::Int16, _) => "SMALLINT",
+        (DataType::Int8, _) => "TINYINT",
+        (DataType::Int32 | DataType::UInt16, Postgres) | (DataType::Int32, Duckdb) => {
+            "INTEGER"
+        }
+        (DataType::Int32, Mysql) => "INT",
+        (DataType::Int64 | DataType::UInt32, Postgres) | (DataType::Int64, _) => "BIGINT",
+        (DataType::UInt64, Postgres) => "NUMERIC(20)",
+        (DataType::UInt8, Mysql) => "TINYINT UNSIGNED",
+        (DataType::UInt16, Mysql) => "SMALLINT UNSIGNED",
+        (DataType::UInt32, Mysql) => "INT UNSIGNED",
+        (DataType::UInt64, Mysql) => "BIGINT UNSIGNED",
+        (DataType::UInt8, Duckdb) => "UTINYINT",
+        (DataType::UInt16, Duckdb) => "USMALLINT",
+        (DataType::UInt32, Duckdb) => "UINTEGER",
+        (DataType::UInt64, Duckdb) => "UBIGINT",
+        (DataType::Float32, Mysql) => "FLOAT",
+        (DataType::Float32, _) => "REAL",
+        (DataType::Float64, Postgres) => "DOUBLE PRECISION",
+        (DataType::Float64, _) => "DOUBLE",
+        (DataType::Date, _) => "DATE",
+        (DataType::Datetime(_, Some(_)), Postgres | Duckdb) => "TIMESTAMPTZ",
+        (DataType::Datetime(_, _), Mysql) => "DATETIME",
+        (DataType::Datetime(_, _), _) => "TIMESTAMP",
+        (DataType::List(inner), Postgres | Duckdb) => {
+            return format!("{}[]", sql_type(inner, database));
+        }
+        (DataType::List(_), Mysql) => "JSON",
+        (_, Duckdb) => "VARCHAR",
+        _ => "TEXT",
+    };
+
+    name.to_string()
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn args(database: Database) -> DdlArgs {
+        DdlArgs {
+            table: "data/stocks/prices.csv".to_string(),
+            database,
+            name: None,
+            delimiter: ',',
+        }
+    }
+
+    fn schema() -> Schema {
+        Schema::from_iter([
+            Field::new("id".into(), DataType::Int64),
+            Field::new("order".into(), DataType::String),
+            Field::new("amount".into(), DataType::Float64),
+            Field::new("paid".into(), DataType::Boolean),
+            Field::new(
+                "at".into(),
+                DataType::Datetime(TimeUnit::Microseconds, None),
+            ),
+        ])
+    }
+
+    #[test]
+    fn test_create_table() {
+        assert_eq!(
+            args(Database::Postgres).create_table(&schema()),
+            "CREATE TABLE \"prices\" (\n    \"id\" BIGINT,\n    \"order\" TEXT,\n    \
+             \"amount\" DOUBLE PRECISION,\n    \"paid\" BOOLEAN,\n    \"at\" TIMESTAMP\n);"
+        );
+
+        let mut mysql = args(Database::Mysql);
+        mysql.name = Some("sales`2024".to_string());
+        let statement = mysql.create_table(&schema());
+        assert!(statement.starts_with("CREATE TABLE `sales``2024` (\n    `id` BIGINT,"));
+        assert!(statement.contains("`at` DATETIME"));
+    }
+
+    #[test]
+    fn test_sql_type() {
+        let list = DataType::List(Box::new(DataType::Int32));
+        assert_eq!(sql_type(&list, Database::Postgres), "INTEGER[]");
+        assert_eq!(sql_type(&list, Database::Mysql), "JSON");
+        assert_eq!(sql_type(&list, Database::Sqlite), "TEXT");
+        assert_eq!(sql_type(&DataType::UInt64, Database::Duckdb), "UBIGINT");
+        assert_eq!(sql_type(&DataType::String, Database::Duckdb), "VARCHAR");
+        assert_eq!(sql_type(&DataType::Boolean, Database::Sqlite), "INTEGER");
+    }
+
+    #[test]
+    fn test_name() {
+        let mut args = args(Database::Sqlite);
+        assert_eq!(args.name(), "prices");
+        args.table = "-".to_string();
+        assert_eq!(args.name(), "data");
+        args.name = Some(String::new());
+        assert!(args.validate().is_err());
+    }
+
+    #[test]
+    fn test_ddl_prices_csv() {
+        let args = args(Database::Duckdb);
+        assert!(args.validate().is_ok());
+        assert!(args.execute(&GlobalArgs::default()).is_ok());
+    }
+}
@@ -0,0 +1,154 @@
+// Write the bundled sample tables somewhere and walk through commands to try on them.
+use anyhow::{Context, Result};
+use clap::{Args, ValueHint};
+use std::fs;
+use std::path::PathBuf;
+
+use crate::args::GlobalArgs;
+
+/// Sample tables bundled into the binary, as (file name, description, contents)
+const TABLES: [(&str, &str, &str); 3] = [
+    (
+        "customers.csv",
+        "names and contact details, keyed by customer_id",
+        include_str!("../data/orders/customers.csv"),
+    ),
+    (
+        "orders.csv",
+        "one row per order, with customer_id and product_id",
+        include_str!("../data/orders/orders.csv"),
+    ),
+    (
+        "products.csv",
+        "names, prices and categories, keyed by product_id",
+        include_str!("../data/orders/products.csv"),
+    ),
+];
+
+#[derive(Args, Debug)]
+pub struct DemoArgs {
+    /// Directory to write the sample tables to [default: rabbet-demo in the temp directory]
+    #[arg(long, value_hint = ValueHint::DirPath)]
+    pub dir: Option<String>,
+}
+
+impl DemoArgs {
+    #[allow(clippy::unused_self)]
+    #[allow(clippy::unnecessary_wraps)]
+    pub const fn validate(&self) -> Result<()> {
+        Ok(())
+    }
+
+    pub fn execute(&self, _global: &GlobalArgs) -> Result<()> {
+        let dir = self
+            .dir
+            .as_ref()
+            .map_or_else(|| std::env::temp_dir().join("rabbet-demo"), PathBuf::from);
+        fs::create_dir_all(&dir)
+            .with_context(|| format!("demo - failed to create {}", dir.display()))?;
+
+        println!("Wrote the sample tables to {}:", dir.display());
+        for (name, description, contents) in TABLES {
+            let path = dir.join(name);
+            fs::write(&path, contents)
+                .with_context(|| format!("demo - failed to write {}", path.display()))?;
+            println!("  {name:<15}{description}");
+        }
+
+        let path = |name: &str| shell_quote(&dir.join(name).display().to_string());
+        let (customers, orders, products) = (
+            path("customers.csv"),
+            path("orders.csv"),
+            path("products.csv"),
+        );
+        let steps = [
+            (
+                "Peek at the first few orders",
+                format!("rabbet head {orders} -n 3"),
+            ),
+            (
+                "Join customers to their orders, keeping a few columns",
+                format!(
+                    "rabbet join {customers} {orders} --on customer_id --select \"order_id,customer_name,price\""
+                ),
+            ),
+            (
+                "Chain a third table with labeled join keys",
+                format!(
+                    "rabbet join {orders} {products} --as orders,products --on \"orders.product_id=products.product_id\" --select \"order_id,product_name,quantity\""
+                ),
+            ),
+            (
+                "Count orders and total spend per customer",
+                format!(
+                    "rabbet aggregate {orders} --by customer_id --with \"_=count,price=sum\""
+                ),
+            ),
+            (
+                "Keep a running total of spend per customer",
+                format!(
+                    "rabbet window {orders} --over customer_id --sort-by order_date --with price=cumsum"
+                ),
+            ),
+            (
+                "Rank customers by total spend with SQL",
+                format!(
+                    "rabbet query {customers} {orders} --as customers,orders -- \"SELECT customer_name, SUM(price) AS spend FROM customers JOIN orders USING (customer_id) GROUP BY customer_name ORDER BY spend DESC\""
+                ),
+            ),
+        ];
+
+        for (idx, (description, command)) in steps.iter().enumerate() {
+            println!();
+            println!("{}. {description}", idx + 1);
+            println!("   {command}");
+        }
+        println!();
+        println!("Run `rabbet <command> --help` to see every option.");
+
+        Ok(())
+    }
+}
+
+/// Quotes `arg` for a POSIX shell when it contains anything but plain path characters
+fn shell_quote(arg: &str) -> String {
+    let plain = arg
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || "/._-:\\".contains(c));
+    if plain {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', r"'\''"))
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_shell_quote() {
+        assert_eq!(
+            shell_quote("/tmp/rabbet-demo/orders.csv"),
+            "/tmp/rabbet-demo/orders.csv"
+        );
+        assert_eq!(shell_quote("/my data/orders.csv"), "'/my data/orders.csv'");
+        assert_eq!(shell_quote("it's.csv"), r"'it'\''s.csv'");
+    }
+
+    #[test]
+    fn test_demo_writes_tables() {
+        let dir = tempdir().unwrap();
+        let args = DemoArgs {
+            dir: Some(dir.path().to_string_lossy().to_string()),
+        };
+
+        assert!(args.validate().is_ok());
+        assert!(args.execute(&GlobalArgs::default()).is_ok());
+        for (name, _, contents) in TABLES {
+            assert_eq!(fs::read_to_string(dir.path().join(name)).unwrap(), contents);
+        }
+    }
+}
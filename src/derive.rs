@@ -0,0 +1,255 @@
+// Add or replace columns computed from SQL expressions, for feature engineering without a full query.
+use anyhow::{Context, Result, bail, ensure};
+use clap::{Args, ValueHint};
+use polars::prelude::*;
+use polars::sql::sql_expr;
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::args::GlobalArgs;
+use crate::io::{resolve_columns, scan_data, write_data};
+
+#[derive(Args, Debug)]
+pub struct DeriveArgs {
+    /// Input table (file or '-' for stdin)
+    #[arg(required = true, value_hint = ValueHint::FilePath)]
+    pub table: String,
+
+    /// A column to add (or replace) as name=expression, with the expression in SQL
+    ///
+    /// Later columns can use earlier ones. Repeat the option for several columns.
+    /// Examples: --set "total=price * quantity" --set "name=upper(name)"
+    /// --set "size=IF(quantity > 3, 'large', 'small')"
+    #[arg(long, required_unless_present = "uuid")]
+    pub set: Vec<String>,
+
+    /// Add a column with this name holding a random UUID for each row, as a surrogate key
+    #[arg(long, value_name = "NAME")]
+    pub uuid: Option<String>,
+
+    /// Make the --uuid column a hash of these columns instead (comma separated)
+    ///
+    /// Rows with the same values get the same UUID, so keys are stable across runs and files.
+    /// Examples: --uuid key --uuid-from "customer,date"
+    #[arg(long, value_delimiter = ',', requires = "uuid")]
+    pub uuid_from: Vec<String>,
+
+    /// Delimiter for input files
+    #[arg(long, default_value = ",")]
+    pub delimiter: char,
+}
+
+impl DeriveArgs {
+    pub fn validate(&self) -> Result<()> {
+        for spec in &self.set {
+            let _ = derived_column(spec, "--set")?;
+        }
+        if let Some(name) = &self.uuid {
+            ensure!(!name.trim().is_empty(), "--uuid needs a column name");
+        }
+
+        Ok(())
+    }
+
+    pub fn execute(&self, global: &GlobalArgs) -> Result<()> {
+        let mut lf =
+            scan_data(&self.table, Some(self.delimiter), global).with_context(|| {
+                format!("derive - failed to read csv data from {}", self.table)
+            })?;
+        for spec in &self.set {
+            lf = lf.with_column(derived_column(spec, "--set")?);
+        }
+
+        let mut result = lf.collect().with_context(|| {
+            format!("derive - failed to compute columns for {}", self.table)
+        })?;
+        if let Some(name) = &self.uuid {
+            let from =
+                resolve_columns(&result, &self.uuid_from, global.ignore_column_case)?;
+            let keys = uuids(&result, &from, global.seed())
+                .with_context(|| format!("derive - failed to generate --uuid {name}"))?;
+            result.with_column(keys.with_name(name.trim().into()).into_column())?;
+        }
+
+        write_data(result, global)
+            .with_context(|| "derive - failed to write data to stdout".to_string())?;
+
+        Ok(())
+    }
+}
+
+/// Parses a `name=expression` spec given to `option` into the SQL expression aliased as `name`
+pub fn derived_column(spec: &str, option: &str) -> Result<Expr> {
+    let Some((name, expr)) = spec.split_once('=') else {
+        bail!("Invalid {option} '{spec}'. Expected format: name=expression");
+    };
+    let name = name.trim();
+    ensure!(!name.is_empty(), "Missing column name in {option} '{spec}'");
+    let expr = sql_expr(expr)
+        .with_context(|| format!("Invalid expression in {option} '{spec}'"))?;
+
+    Ok(expr.alias(name))
+}
+
+/// A UUID for each row of `df`: random (version 4) without `from` columns, and otherwise
+/// built (as version 8) from the SHA-256 hash of the row's values in those columns
+///
+/// Random UUIDs are drawn from `seed` when given, so they repeat from run to run.
+fn uuids(df: &DataFrame, from: &[String], seed: Option<u64>) -> Result<StringChunked> {
+    if from.is_empty() {
+        return Ok((0..df.height() as u64)
+            .map(|row| {
+                let uuid = seed.map_or_else(Uuid::new_v4, |seed| seeded_uuid(seed, row));
+                Some(uuid.to_string())
+            })
+            .collect());
+    }
+
+    let columns = from
+        .iter()
+        .map(|c| df.column(c)?.cast(&DataType::String))
+        .collect::<PolarsResult<Vec<_>>>()?;
+    let columns = columns
+        .iter()
+        .map(Column::str)
+        .collect::<PolarsResult<Vec<_>>>()?;
+
+    Ok((0..df.height())
+        .map(|row| {
+            let mut hasher = Sha256::new();
+            for column in &columns {
+                // Length prefixes keep ("ab", "c") and ("a", "bc") apart, and nulls apart from ""
+                match column.get(row) {
+                    Some(value) => {
+                        hasher.update((value.len() as u64 + 1).to_le_bytes());
+                        hasher.update(value.as_bytes());
+                    }
+                    None => hasher.update(0_u64.to_le_bytes()),
+                }
+            }
+            let mut bytes = [0; 16];
+            bytes.copy_from_slice(&hasher.finalize()[..16]);
+            Some(Uuid::new_v8(bytes).to_string())
+        })
+        .collect())
+}
+
+/// A version 4 UUID whose random bits come from the SHA-256 hash of `seed` and `row`
+fn seeded_uuid(seed: u64, row: u64) -> Uuid {
+    let digest = Sha256::new()
+        .chain_update(seed.to_le_bytes())
+        .chain_update(row.to_le_bytes())
+        .finalize();
+    let mut bytes = [0; 16];
+    bytes.copy_from_slice(&digest[..16]);
+
+    uuid::Builder::from_random_bytes(bytes).into_uuid()
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_derived_column() {
+        let df = df!(
+            "name" => ["ada", "grace"],
+            "price" => [2.5, 4.0],
+            "quantity" => [2, 5],
+        )
+        .unwrap();
+
+        let result = df
+            .lazy()
+            .with_column(derived_column("total=price * quantity", "--set").unwrap())
+            .with_column(derived_column("name=upper(name)", "--set").unwrap())
+            .with_column(
+                derived_column("size = IF(total > 10, 'large', 'small')", "--set").unwrap(),
+            )
+            .collect()
+            .unwrap();
+        assert_eq!(
+            result.get_column_names(),
+            &["name", "price", "quantity", "total", "size"]
+        );
+        let names: Vec<_> = result
+            .column("name")
+            .unwrap()
+            .str()
+            .unwrap()
+            .into_iter()
+            .collect();
+        assert_eq!(names, [Some("ADA"), Some("GRACE")]);
+        let sizes: Vec<_> = result
+            .column("size")
+            .unwrap()
+            .str()
+            .unwrap()
+            .into_iter()
+            .collect();
+        assert_eq!(sizes, [Some("small"), Some("large")]);
+
+        assert!(derived_column("price * quantity", "--set").is_err());
+        assert!(derived_column("=price", "--set").is_err());
+        assert!(derived_column("total=price *", "--set").is_err());
+    }
+
+    #[test]
+    fn test_uuids() {
+        let df = df!(
+            "customer" => [Some("a"), Some("a"), Some("ab"), None],
+            "code" => [Some("bc"), Some("bc"), Some("c"), Some("")],
+        )
+        .unwrap();
+
+        let random = uuids(&df, &[], None).unwrap();
+        assert_eq!(random.len(), 4);
+        assert_eq!(random.n_unique().unwrap(), 4);
+        let parsed = Uuid::parse_str(random.get(0).unwrap()).unwrap();
+        assert_eq!(parsed.get_version_num(), 4);
+
+        let seeded = uuids(&df, &[], Some(7)).unwrap();
+        assert_eq!(seeded.n_unique().unwrap(), 4);
+        assert!(
+            uuids(&df, &[], Some(7))
+                .unwrap()
+                .into_iter()
+                .eq(seeded.into_iter())
+        );
+        assert_ne!(uuids(&df, &[], Some(8)).unwrap().get(0), seeded.get(0));
+        let parsed = Uuid::parse_str(seeded.get(0).unwrap()).unwrap();
+        assert_eq!(parsed.get_version_num(), 4);
+
+        let from = ["customer".to_string(), "code".to_string()];
+        let keys = uuids(&df, &from, None).unwrap();
+        assert_eq!(keys.get(0), keys.get(1));
+        assert_ne!(keys.get(1), keys.get(2));
+        assert_eq!(keys.n_unique().unwrap(), 3);
+        assert!(
+            uuids(&df, &from, None)
+                .unwrap()
+                .into_iter()
+                .eq(keys.into_iter())
+        );
+        let parsed = Uuid::parse_str(keys.get(0).unwrap()).unwrap();
+        assert_eq!(parsed.get_version_num(), 8);
+    }
+
+    #[test]
+    fn test_derive_orders_csv() {
+        let args = DeriveArgs {
+            table: "data/orders/orders.csv".to_string(),
+            set: vec![
+                "total=price * quantity".to_string(),
+                "bulk=CASE WHEN quantity >= 5 THEN 'yes' ELSE 'no' END".to_string(),
+            ],
+            uuid: Some("order_key".to_string()),
+            uuid_from: vec!["order_id".to_string()],
+            delimiter: ',',
+        };
+
+        assert!(args.validate().is_ok());
+        assert!(args.execute(&GlobalArgs::default()).is_ok());
+    }
+}
@@ -0,0 +1,407 @@
+// Compare two versions of a table by key, reporting added, removed and changed rows.
+use anyhow::{Context, Result, ensure};
+use clap::{Args, ValueHint};
+use polars::prelude::*;
+
+use crate::args::GlobalArgs;
+use crate::io::{read_data, resolve_columns, write_data};
+use crate::join::join_args;
+
+/// Suffix given to the new table's columns while comparing the two tables
+const NEW_SUFFIX: &str = "__rabbet_new";
+
+/// Temporary column used to keep changed values in row order
+const ROW_INDEX: &str = "__rabbet_row";
+
+#[derive(Args, Debug)]
+pub struct DiffArgs {
+    /// The original table (file or '-' for stdin)
+    #[arg(required = true, value_hint = ValueHint::FilePath)]
+    pub old: String,
+
+    /// The updated table (file or '-' for stdin)
+    #[arg(required = true, value_hint = ValueHint::FilePath)]
+    pub new: String,
+
+    /// Key columns identifying a row in both tables (comma separated)
+    ///
+    /// Examples: --on "id" or --on "`user_id,region`"
+    #[arg(long, value_delimiter = ',', required = true)]
+    pub on: Vec<String>,
+
+    /// Output one row per changed value (key, status, column, old, new) instead of one row per key
+    #[arg(long)]
+    pub changes: bool,
+
+    /// Delimiter for input files
+    #[arg(long, default_value = ",")]
+    pub delimiter: char,
+}
+
+impl DiffArgs {
+    pub fn validate(&self) -> Result<()> {
+        ensure!(
+            !(self.old == "-" && self.new == "-"),
+            "stdin ('-') can only be used for one table"
+        );
+        ensure!(!self.on.is_empty(), "At least one key column is required");
+
+        Ok(())
+    }
+
+    pub fn execute(&self, global: &GlobalArgs) -> Result<()> {
+        let old = read_data(&self.old, Some(self.delimiter), global)
+            .with_context(|| format!("diff - failed to read table '{}'", self.old))?;
+        let mut new = read_data(&self.new, Some(self.delimiter), global)
+            .with_context(|| format!("diff - failed to read table '{}'", self.new))?;
+
+        let old_on = resolve_columns(&old, &self.on, global.ignore_column_case)?;
+        let new_on = resolve_columns(&new, &self.on, global.ignore_column_case)?;
+        // Line the new table's keys up with the old key names
+        for (old_key, new_key) in old_on.iter().zip(&new_on) {
+            if old_key != new_key {
+                new.rename(new_key, old_key.into())?;
+            }
+        }
+
+        check_unique(&old, &old_on, &self.old)?;
+        check_unique(&new, &old_on, &self.new)?;
+
+        let diff = compare(&old, &new, &old_on, global.stable_order())?;
+        if !global.quiet {
+            eprintln!(
+                "diff - {} row(s) added, {} removed, {} changed",
+                diff.added.height(),
+                diff.removed.height(),
+                diff.changed.height()
+            );
+            for (name, df, other) in [(&self.old, &old, &new), (&self.new, &new, &old)] {
+                let only = exclusive_columns(df, other, &old_on);
+                if !only.is_empty() {
+                    eprintln!("diff - columns only in '{name}': {}", only.join(", "));
+                }
+            }
+        }
+
+        let result = if self.changes {
+            diff.by_value(&old_on)?
+        } else {
+            diff.by_key(&old_on)?
+        };
+
+        write_data(result, global)
+            .with_context(|| "diff - failed to write data to stdout".to_string())?;
+
+        Ok(())
+    }
+}
+
+/// Differences between two tables with matching key column names
+struct Diff {
+    /// Keys only found in the old table
+    removed: DataFrame,
+    /// Keys only found in the new table
+    added: DataFrame,
+    /// Keys found in both tables with a `changes` description of what differs
+    changed: DataFrame,
+    /// Keys with one row per differing value in `column`, `old` and `new`
+    values: DataFrame,
+}
+
+impl Diff {
+    /// One row per added, removed or changed key, with changes as `column: old -> new`
+    fn by_key(&self, on: &[String]) -> Result<DataFrame> {
+        let frames = [
+            with_status(&self.removed, on, "removed", &["changes"]),
+            with_status(&self.added, on, "added", &["changes"]),
+            with_status(&self.changed, on, "changed", &[]),
+        ];
+
+        Ok(concat(frames, UnionArgs::default())?.collect()?)
+    }
+
+    /// One row per changed value, plus one row per added or removed key
+    fn by_value(&self, on: &[String]) -> Result<DataFrame> {
+        let empty = ["column", "old", "new"];
+        let frames = [
+            with_status(&self.removed, on, "removed", &empty),
+            with_status(&self.added, on, "added", &empty),
+            with_status(&self.values, on, "changed", &[]),
+        ];
+
+        Ok(concat(frames, UnionArgs::default())?.collect()?)
+    }
+}
+
+/// Selects the key columns, a `status` column and the remaining columns of `df`,
+/// adding each of the `missing` columns as nulls
+fn with_status(df: &DataFrame, on: &[String], status: &str, missing: &[&str]) -> LazyFrame {
+    let mut exprs: Vec<Expr> = on.iter().map(|c| col(c.as_str())).collect();
+    exprs.push(lit(status).alias("status"));
+    exprs.extend(
+        df.get_column_names_str()
+            .into_iter()
+            .filter(|c| !on.iter().any(|k| k == c))
+            .map(col),
+    );
+    exprs.extend(
+        missing
+            .iter()
+            .map(|c| lit(NULL).cast(DataType::String).alias(*c)),
+    );
+
+    df.clone().lazy().select(exprs)
+}
+
+/// Returns the non-key columns of `df` which aren't in `other`
+fn exclusive_columns<'a>(
+    df: &'a DataFrame,
+    other: &DataFrame,
+    on: &[String],
+) -> Vec<&'a str> {
+    df.get_column_names_str()
+        .into_iter()
+        .filter(|c| !on.iter().any(|k| k == c) && other.column(c).is_err())
+        .collect()
+}
+
+/// Fails if the key columns don't uniquely identify each row of `df`
+fn check_unique(df: &DataFrame, on: &[String], name: &str) -> Result<()> {
+    let keys: Vec<Expr> = on.iter().map(|c| col(c.as_str())).collect();
+    let duplicates = df
+        .clone()
+        .lazy()
+        .group_by(keys)
+        .agg([len()])
+        .filter(col("len").gt(lit(1)))
+        .collect()?
+        .height();
+
+    ensure!(
+        duplicates == 0,
+        "diff - key columns [{}] are not unique in '{name}' ({duplicates} duplicated key(s))",
+        on.join(", ")
+    );
+
+    Ok(())
+}
+
+/// Compares two tables whose key columns share the same names
+///
+/// Values are compared as strings, so `1` and `1.0` in columns of different types differ.
+/// With `stable`, changed rows are listed in the order of the old table.
+fn compare(old: &DataFrame, new: &DataFrame, on: &[String], stable: bool) -> Result<Diff> {
+    let removed = old
+        .join(new, on, on, JoinArgs::new(JoinType::Anti), None)?
+        .select(on)?;
+    let added = new
+        .join(old, on, on, JoinArgs::new(JoinType::Anti), None)?
+        .select(on)?;
+    let joined = old
+        .join(
+            new,
+            on,
+            on,
+            join_args(JoinType::Inner, stable).with_suffix(Some(NEW_SUFFIX.into())),
+            None,
+        )?
+        .lazy();
+
+    let keys: Vec<Expr> = on.iter().map(|c| col(c.as_str())).collect();
+    let compared: Vec<&str> = old
+        .get_column_names_str()
+        .into_iter()
+        .filter(|c| !on.iter().any(|k| k == c) && new.column(c).is_ok())
+        .collect();
+
+    let (changed, values) = if compared.is_empty() {
+        // Nothing to compare, but the outputs should still have the right columns
+        let empty = |columns: &[&str]| {
+            removed.clear().lazy().with_columns(
+                columns
+                    .iter()
+                    .map(|c| lit(NULL).cast(DataType::String).alias(*c))
+                    .collect::<Vec<_>>(),
+            )
+        };
+        (
+            empty(&["changes"]).collect()?,
+            empty(&["column", "old", "new"]).collect()?,
+        )
+    } else {
+        (
+            changed_keys(&joined, &keys, &compared)?,
+            changed_values(&joined, &keys, &compared)?,
+        )
+    };
+
+    Ok(Diff {
+        removed,
+        added,
+        changed,
+        values,
+    })
+}
+
+/// Returns the old and new values of `column` in a joined table, cast to strings
+fn value_pair(column: &str) -> (Expr, Expr) {
+    (
+        col(column).cast(DataType::String),
+        col(format!("{column}{NEW_SUFFIX}")).cast(DataType::String),
+    )
+}
+
+/// Returns the keys of joined rows with any differing values, along with a description
+/// of the changes (e.g., "price: 24.5 -> 27.0, stock: 3 -> 1")
+fn changed_keys(joined: &LazyFrame, keys: &[Expr], columns: &[&str]) -> Result<DataFrame> {
+    let descriptions: Vec<Expr> = columns
+        .iter()
+        .map(|column| {
+            let (before, after) = value_pair(column);
+            when(before.clone().neq_missing(after.clone()))
+                .then(concat_str(
+                    [
+                        lit(format!("{column}: ")),
+                        before.fill_null(lit("null")),
+                        lit(" -> "),
+                        after.fill_null(lit("null")),
+                    ],
+                    "",
+                    false,
+                ))
+                .otherwise(lit(NULL).cast(DataType::String))
+        })
+        .collect();
+
+    let changes = concat_str(descriptions, ", ", true).alias("changes");
+    let df = joined
+        .clone()
+        .select(keys.iter().cloned().chain([changes]).collect::<Vec<_>>())
+        .filter(
+            col("changes")
+                .is_not_null()
+                .and(col("changes").neq(lit(""))),
+        )
+        .collect()?;
+
+    Ok(df)
+}
+
+/// Returns one row per differing value in the joined rows, in row order
+fn changed_values(
+    joined: &LazyFrame,
+    keys: &[Expr],
+    columns: &[&str],
+) -> Result<DataFrame> {
+    let frames: Vec<LazyFrame> = columns
+        .iter()
+        .map(|column| {
+            let (before, after) = value_pair(column);
+            joined
+                .clone()
+                .with_row_index(ROW_INDEX, None)
+                .filter(before.clone().neq_missing(after.clone()))
+                .select(
+                    keys.iter()
+                        .cloned()
+                        .chain([
+                            col(ROW_INDEX),
+                            lit(*column).alias("column"),
+                            before.alias("old"),
+                            after.alias("new"),
+                        ])
+                        .collect::<Vec<_>>(),
+                )
+        })
+        .collect();
+
+    let df = concat(frames, UnionArgs::default())?
+        .sort(
+            [ROW_INDEX],
+            SortMultipleOptions::default().with_maintain_order(true),
+        )
+        .collect()?
+        .drop(ROW_INDEX)?;
+
+    Ok(df)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_both_stdin() {
+        let args = DiffArgs {
+            old: "-".to_string(),
+            new: "-".to_string(),
+            on: vec!["id".to_string()],
+            changes: false,
+            delimiter: ',',
+        };
+        assert!(args.validate().is_err());
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn test_check_unique() {
+        let df = df! { "id" => [1, 2, 2], "name" => ["a", "b", "c"] }.unwrap();
+
+        assert!(check_unique(&df, &["name".to_string()], "t.csv").is_ok());
+        assert_eq!(
+            check_unique(&df, &["id".to_string()], "t.csv")
+                .unwrap_err()
+                .to_string(),
+            "diff - key columns [id] are not unique in 't.csv' (1 duplicated key(s))"
+        );
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn test_compare() {
+        let old = df! {
+            "id" => [1, 2, 3],
+            "name" => ["a", "b", "c"],
+            "score" => [Some(1.0), Some(2.0), None]
+        }
+        .unwrap();
+        let new = df! {
+            "id" => [2, 3, 4],
+            "name" => ["b", "C", "d"],
+            "score" => [Some(2.5), Some(3.0), None]
+        }
+        .unwrap();
+        let on = vec!["id".to_string()];
+
+        let diff = compare(&old, &new, &on, false).unwrap();
+        assert_eq!(
+            diff.removed.column("id").unwrap().i32().unwrap().get(0),
+            Some(1)
+        );
+        assert_eq!(
+            diff.added.column("id").unwrap().i32().unwrap().get(0),
+            Some(4)
+        );
+        assert_eq!(
+            diff.changed
+                .column("changes")
+                .unwrap()
+                .str()
+                .unwrap()
+                .into_iter()
+                .collect::<Vec<_>>(),
+            [
+                Some("score: 2.0 -> 2.5"),
+                Some("name: c -> C, score: null -> 3.0")
+            ]
+        );
+
+        let values = diff.by_value(&on).unwrap();
+        assert_eq!(
+            values.get_column_names(),
+            &["id", "status", "column", "old", "new"]
+        );
+        assert_eq!(values.height(), 5);
+        assert_eq!(diff.by_key(&on).unwrap().height(), 4);
+    }
+}
@@ -0,0 +1,105 @@
+// Exit codes for the kinds of failure scripts and CI jobs may want to branch on.
+use std::error::Error;
+use std::fmt;
+use std::process::ExitCode;
+
+/// What went wrong, deciding the process exit code (1 for anything else)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Failure {
+    /// Invalid arguments or option combinations (2, like clap's own usage errors)
+    Usage,
+    /// An input table couldn't be read or parsed (3)
+    Input,
+    /// The output had no rows with --fail-if-empty (4)
+    Empty,
+    /// The data broke a `validate` rule (5)
+    Invalid,
+    /// Nothing but whitespace arrived on stdin, usually because the upstream command failed (6)
+    NoInput,
+    /// The process used more memory than --memory-limit (7)
+    MemoryLimit,
+}
+
+impl Failure {
+    pub const fn code(self) -> u8 {
+        match self {
+            Self::Usage => 2,
+            Self::Input => 3,
+            Self::Empty => 4,
+            Self::Invalid => 5,
+            Self::NoInput => 6,
+            Self::MemoryLimit => 7,
+        }
+    }
+
+    /// Tags `error` with this kind of failure, leaving its message unchanged
+    ///
+    /// Errors already tagged keep their more specific failure.
+    pub fn tag(self, error: anyhow::Error) -> anyhow::Error {
+        if error.is::<Tagged>() {
+            return error;
+        }
+        anyhow::Error::new(Tagged {
+            failure: self,
+            error,
+        })
+    }
+}
+
+/// An error tagged with a `Failure`, which displays as the error it wraps
+#[derive(Debug)]
+struct Tagged {
+    failure: Failure,
+    error: anyhow::Error,
+}
+
+impl fmt::Display for Tagged {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.error, f)
+    }
+}
+
+impl Error for Tagged {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.error.source()
+    }
+}
+
+/// Exit code for `error`, from the outermost `Failure` it was tagged with
+pub fn exit_code(error: &anyhow::Error) -> ExitCode {
+    error
+        .chain()
+        .find_map(|e| e.downcast_ref::<Tagged>())
+        .map_or(ExitCode::FAILURE, |tagged| {
+            ExitCode::from(tagged.failure.code())
+        })
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use anyhow::{Context, anyhow};
+
+    #[test]
+    fn test_exit_code() {
+        let error = Failure::Input.tag(anyhow!("bad row"));
+        let error = Err::<(), _>(error)
+            .context("cat - failed to read csv data")
+            .unwrap_err();
+
+        assert_eq!(exit_code(&error), ExitCode::from(3));
+        assert_eq!(
+            format!("{error:#}"),
+            "cat - failed to read csv data: bad row"
+        );
+        assert_eq!(exit_code(&anyhow!("other")), ExitCode::FAILURE);
+
+        let error = Failure::Input.tag(Failure::NoInput.tag(anyhow!("empty")));
+        assert_eq!(exit_code(&error), ExitCode::from(6));
+        assert_eq!(
+            exit_code(&Failure::MemoryLimit.tag(anyhow!("too big"))),
+            ExitCode::from(7)
+        );
+    }
+}
@@ -0,0 +1,132 @@
+// Split a delimited string column (e.g., "gift;sale") into one row per value.
+use anyhow::{Context, Result, ensure};
+use clap::{Args, ValueHint};
+use polars::prelude::*;
+
+use crate::args::GlobalArgs;
+use crate::io::{read_data, resolve_columns, write_data};
+
+#[derive(Args, Debug)]
+pub struct ExplodeArgs {
+    /// Input table (file or '-' for stdin)
+    #[arg(required = true, value_hint = ValueHint::FilePath)]
+    pub table: String,
+
+    /// String column holding the delimited values to split into rows
+    #[arg(long, required = true)]
+    pub column: String,
+
+    /// Separator between the values in the column
+    ///
+    /// Examples: --separator ';' or --separator ' | '
+    #[arg(long, default_value = ",")]
+    pub separator: String,
+
+    /// Delimiter for input files
+    #[arg(long, default_value = ",")]
+    pub delimiter: char,
+}
+
+impl ExplodeArgs {
+    pub fn validate(&self) -> Result<()> {
+        ensure!(!self.separator.is_empty(), "--separator cannot be empty");
+
+        Ok(())
+    }
+
+    pub fn execute(&self, global: &GlobalArgs) -> Result<()> {
+        let df =
+            read_data(&self.table, Some(self.delimiter), global).with_context(|| {
+                format!("explode - failed to read csv data from {}", self.table)
+            })?;
+
+        let column = resolve_columns(
+            &df,
+            std::slice::from_ref(&self.column),
+            global.ignore_column_case,
+        )?
+        .remove(0);
+        let result = explode(df, &column, &self.separator).with_context(|| {
+            format!("explode - failed to split '{column}' in {}", self.table)
+        })?;
+
+        write_data(result, global)
+            .with_context(|| "explode - failed to write data to stdout".to_string())?;
+
+        Ok(())
+    }
+}
+
+/// Splits `column` on `separator`, repeating the rest of the row for each value
+///
+/// Nulls and empty strings stay as a single row.
+fn explode(df: DataFrame, column: &str, separator: &str) -> Result<DataFrame> {
+    let dtype = df.column(column)?.dtype();
+    ensure!(
+        dtype == &DataType::String,
+        "Column '{column}' must be a string column to split, found {dtype}"
+    );
+
+    let split = df
+        .lazy()
+        .with_column(col(column).str().split(lit(separator)))
+        .collect()?;
+
+    Ok(split.explode([column])?)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_explode() {
+        let df = df!(
+            "sku" => ["A100", "A200", "A300"],
+            "tags" => [Some("gift;sale"), None, Some("bulk")],
+        )
+        .unwrap();
+
+        let result = explode(df, "tags", ";").unwrap();
+        let skus: Vec<_> = result
+            .column("sku")
+            .unwrap()
+            .str()
+            .unwrap()
+            .into_iter()
+            .collect();
+        let tags: Vec<_> = result
+            .column("tags")
+            .unwrap()
+            .str()
+            .unwrap()
+            .into_iter()
+            .collect();
+
+        assert_eq!(
+            skus,
+            [Some("A100"), Some("A100"), Some("A200"), Some("A300")]
+        );
+        assert_eq!(tags, [Some("gift"), Some("sale"), None, Some("bulk")]);
+    }
+
+    #[test]
+    fn test_explode_requires_strings() {
+        let df = df!("id" => [1, 2]).unwrap();
+        assert!(explode(df, "id", ";").is_err());
+    }
+
+    #[test]
+    fn test_explode_tags_csv() {
+        let args = ExplodeArgs {
+            table: "data/catalog/tags.csv".to_string(),
+            column: "tags".to_string(),
+            separator: ";".to_string(),
+            delimiter: ',',
+        };
+
+        assert!(args.validate().is_ok());
+        assert!(args.execute(&GlobalArgs::default()).is_ok());
+    }
+}
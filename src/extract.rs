@@ -0,0 +1,183 @@
+// Create new columns from the capture groups of a regex matched against a string column.
+use anyhow::{Context, Result, bail, ensure};
+use clap::{Args, ValueHint};
+use polars::prelude::*;
+use regex::Regex;
+
+use crate::args::GlobalArgs;
+use crate::io::{read_data, report_dropped, resolve_columns, write_data};
+
+#[derive(Args, Debug)]
+pub struct ExtractArgs {
+    /// Input table (file or '-' for stdin)
+    #[arg(required = true, value_hint = ValueHint::FilePath)]
+    pub table: String,
+
+    /// String column to match the pattern against
+    #[arg(long, required = true)]
+    pub column: String,
+
+    /// Regular expression with one capture group per new column
+    ///
+    /// Examples: --pattern 'id=(\d+)' or --pattern '(?<user>\w+)@(?<domain>[\w.]+)'
+    #[arg(long, required = true)]
+    pub pattern: String,
+
+    /// Names of the new columns, one per capture group (comma separated)
+    ///
+    /// Defaults to the names of named groups, or `<column>_<n>` for unnamed ones.
+    /// Examples: --into "`item_id`" or --into "user,domain"
+    #[arg(long, value_delimiter = ',')]
+    pub into: Vec<String>,
+
+    /// Drop rows where the pattern doesn't match
+    #[arg(long)]
+    pub drop_unmatched: bool,
+
+    /// Delimiter for input files
+    #[arg(long, default_value = ",")]
+    pub delimiter: char,
+}
+
+impl ExtractArgs {
+    pub fn validate(&self) -> Result<()> {
+        self.targets()?;
+
+        Ok(())
+    }
+
+    pub fn execute(&self, global: &GlobalArgs) -> Result<()> {
+        let df =
+            read_data(&self.table, Some(self.delimiter), global).with_context(|| {
+                format!("extract - failed to read csv data from {}", self.table)
+            })?;
+
+        let column = resolve_columns(
+            &df,
+            std::slice::from_ref(&self.column),
+            global.ignore_column_case,
+        )?
+        .remove(0);
+        let dtype = df.column(&column)?.dtype();
+        ensure!(
+            dtype == &DataType::String,
+            "extract - column '{column}' must be a string column, found {dtype}"
+        );
+
+        let exprs: Vec<Expr> = self
+            .targets()?
+            .iter()
+            .enumerate()
+            .map(|(idx, name)| {
+                col(column.as_str())
+                    .str()
+                    .extract(lit(self.pattern.as_str()), idx + 1)
+                    .alias(name.as_str())
+            })
+            .collect();
+
+        let mut lf = df.lazy();
+        if self.drop_unmatched {
+            let matched = col(column.as_str())
+                .str()
+                .extract(lit(self.pattern.as_str()), 0)
+                .is_not_null();
+            if global.report {
+                let removed = lf.clone().filter(matched.clone().not()).collect()?;
+                report_dropped(
+                    &format!("extract - rows not matching '{}'", self.pattern),
+                    &removed,
+                );
+            }
+            lf = lf.filter(matched);
+        }
+        let result = lf.with_columns(exprs).collect().with_context(|| {
+            format!(
+                "extract - failed to match '{}' in {}",
+                self.pattern, self.table
+            )
+        })?;
+
+        write_data(result, global)
+            .with_context(|| "extract - failed to write data to stdout".to_string())?;
+
+        Ok(())
+    }
+
+    /// Names of the new columns, in capture group order
+    fn targets(&self) -> Result<Vec<String>> {
+        let regex = Regex::new(&self.pattern)
+            .with_context(|| format!("Invalid pattern '{}'", self.pattern))?;
+        let names: Vec<Option<&str>> = regex.capture_names().skip(1).collect();
+        ensure!(
+            !names.is_empty(),
+            "Pattern '{}' has no capture groups. Wrap the part to extract in parentheses, e.g. 'id=(\\d+)'",
+            self.pattern
+        );
+
+        if self.into.is_empty() {
+            return Ok(names
+                .iter()
+                .enumerate()
+                .map(|(idx, name)| {
+                    name.map_or_else(
+                        || format!("{}_{}", self.column, idx + 1),
+                        String::from,
+                    )
+                })
+                .collect());
+        }
+
+        if self.into.len() != names.len() {
+            bail!(
+                "--into names {} column(s) but pattern '{}' has {} capture group(s)",
+                self.into.len(),
+                self.pattern,
+                names.len()
+            );
+        }
+
+        Ok(self.into.clone())
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn args(pattern: &str, into: &[&str]) -> ExtractArgs {
+        ExtractArgs {
+            table: "data/web/access.csv".to_string(),
+            column: "url".to_string(),
+            pattern: pattern.to_string(),
+            into: into.iter().map(ToString::to_string).collect(),
+            drop_unmatched: false,
+            delimiter: ',',
+        }
+    }
+
+    #[test]
+    fn test_targets() {
+        assert_eq!(
+            args(r"id=(\d+)", &["item_id"]).targets().unwrap(),
+            ["item_id"]
+        );
+        assert_eq!(
+            args(r"id=(\d+)&ref=(?<ref>\w+)", &[]).targets().unwrap(),
+            ["url_1", "ref"]
+        );
+        assert!(args(r"id=\d+", &[]).targets().is_err());
+        assert!(args(r"id=(\d+)", &["a", "b"]).targets().is_err());
+        assert!(args(r"id=(\d+", &[]).targets().is_err());
+    }
+
+    #[test]
+    fn test_extract_access_csv() {
+        let mut args = args(r"id=(\d+)", &["item_id"]);
+        args.drop_unmatched = true;
+
+        assert!(args.validate().is_ok());
+        assert!(args.execute(&GlobalArgs::default()).is_ok());
+    }
+}
@@ -0,0 +1,180 @@
+// Count how often each value (or combination of values) appears, most common first.
+use anyhow::{Context, Result, ensure};
+use clap::{Args, ValueHint};
+use polars::prelude::*;
+
+use crate::args::GlobalArgs;
+use crate::io::{resolve_columns, scan_data, write_data};
+
+#[derive(Args, Debug)]
+pub struct FreqArgs {
+    /// Input table (file or '-' for stdin)
+    #[arg(required = true, value_hint = ValueHint::FilePath)]
+    pub table: String,
+
+    /// Columns whose values (or combinations of values) to count (comma separated)
+    ///
+    /// Examples: --column status or --column "region,status"
+    #[arg(long, required = true, value_delimiter = ',')]
+    pub column: Vec<String>,
+
+    /// Only show the most common values
+    #[arg(long)]
+    pub top: Option<usize>,
+
+    /// Add each value's percentage of the rows and the cumulative percentage
+    #[arg(long)]
+    pub percent: bool,
+
+    /// Delimiter for input files
+    #[arg(long, default_value = ",")]
+    pub delimiter: char,
+}
+
+impl FreqArgs {
+    pub fn validate(&self) -> Result<()> {
+        ensure!(
+            !self.column.iter().any(|c| c == "count"),
+            "Can't count a column named 'count', since it would clash with the counts. Rename it first"
+        );
+        ensure!(self.top != Some(0), "--top must be at least 1");
+
+        Ok(())
+    }
+
+    pub fn execute(&self, global: &GlobalArgs) -> Result<()> {
+        let mut lf =
+            scan_data(&self.table, Some(self.delimiter), global).with_context(|| {
+                format!("freq - failed to read csv data from {}", self.table)
+            })?;
+        let schema = lf.collect_schema()?;
+        let header = DataFrame::empty_with_schema(&schema);
+        let columns = resolve_columns(&header, &self.column, global.ignore_column_case)?;
+
+        let result = freq(lf, &columns, self.top, self.percent)?
+            .collect()
+            .with_context(|| {
+                format!(
+                    "freq - failed to count {} in {}",
+                    columns.join(", "),
+                    self.table
+                )
+            })?;
+
+        write_data(result, global)
+            .with_context(|| "freq - failed to write data to stdout".to_string())?;
+
+        Ok(())
+    }
+}
+
+/// Counts the rows of each combination of values in `columns`, most common first
+///
+/// Ties are ordered by value, and nulls are counted like any other value. With `percent`,
+/// the percentages are of all rows, so they still add up across `top`.
+fn freq(
+    lf: LazyFrame,
+    columns: &[String],
+    top: Option<usize>,
+    percent: bool,
+) -> Result<LazyFrame> {
+    let by: Vec<Expr> = columns.iter().map(|c| col(c.as_str())).collect();
+    let mut order = vec!["count".to_string()];
+    order.extend(columns.iter().cloned());
+    let mut descending = vec![false; order.len()];
+    descending[0] = true;
+
+    let mut lf = lf.group_by(by).agg([len().alias("count")]).sort(
+        order,
+        SortMultipleOptions::default()
+            .with_order_descending_multi(descending)
+            .with_nulls_last(true),
+    );
+
+    if percent {
+        let share = col("count").cast(DataType::Float64) * lit(100.0)
+            / col("count").sum().cast(DataType::Float64);
+        lf = lf
+            .with_column(share.alias("percent"))
+            .with_column(col("percent").cum_sum(false).alias("cum_percent"));
+    }
+    if let Some(top) = top {
+        lf = lf.limit(IdxSize::try_from(top)?);
+    }
+
+    Ok(lf)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_freq() {
+        let df = df!(
+            "status" => [Some("ok"), Some("error"), Some("ok"), None, Some("ok"), Some("error")],
+        )
+        .unwrap();
+
+        let result = freq(df.lazy(), &["status".to_string()], Some(2), true)
+            .unwrap()
+            .collect()
+            .unwrap();
+        let statuses: Vec<_> = result
+            .column("status")
+            .unwrap()
+            .str()
+            .unwrap()
+            .into_iter()
+            .collect();
+        assert_eq!(statuses, [Some("ok"), Some("error")]);
+        let counts: Vec<_> = result
+            .column("count")
+            .unwrap()
+            .cast(&DataType::Int64)
+            .unwrap()
+            .i64()
+            .unwrap()
+            .to_vec();
+        assert_eq!(counts, [Some(3), Some(2)]);
+        let cumulative: Vec<_> = result
+            .column("cum_percent")
+            .unwrap()
+            .f64()
+            .unwrap()
+            .into_iter()
+            .map(|p| p.map(f64::round))
+            .collect();
+        assert_eq!(cumulative, [Some(50.0), Some(83.0)]);
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut args = FreqArgs {
+            table: "data/orders/orders.csv".to_string(),
+            column: vec!["customer_id".to_string()],
+            top: None,
+            percent: false,
+            delimiter: ',',
+        };
+        assert!(args.validate().is_ok());
+
+        args.top = Some(0);
+        assert!(args.validate().is_err());
+    }
+
+    #[test]
+    fn test_freq_orders_csv() {
+        let args = FreqArgs {
+            table: "data/orders/orders.csv".to_string(),
+            column: vec!["customer_id".to_string(), "product_id".to_string()],
+            top: Some(3),
+            percent: true,
+            delimiter: ',',
+        };
+
+        assert!(args.validate().is_ok());
+        assert!(args.execute(&GlobalArgs::default()).is_ok());
+    }
+}
@@ -0,0 +1,159 @@
+// Match keys which are close rather than equal, for joining messy names and addresses.
+use clap::ValueEnum;
+use itertools::Itertools;
+
+use crate::io::edit_distance;
+
+#[derive(Debug, Clone, Copy, ValueEnum, PartialEq, Eq)]
+pub enum FuzzyMetric {
+    /// Fewest single character insertions, deletions and substitutions between keys
+    Levenshtein,
+    /// Similarity from 0 to 1 favouring keys with a common prefix, good for short names
+    JaroWinkler,
+}
+
+/// Picks the closest of a set of candidate keys, within a distance or similarity threshold
+#[derive(Debug, Clone, Copy)]
+pub struct Matcher {
+    pub metric: FuzzyMetric,
+    pub max_distance: usize,
+    pub min_similarity: f64,
+}
+
+impl Matcher {
+    /// The closest of `candidates` to `key` and its score, or `None` if none is close enough
+    ///
+    /// Keys are compared ignoring case and repeated whitespace. Scores are the edit distance
+    /// for Levenshtein (0 for an exact match) and the similarity for Jaro-Winkler (1 for an
+    /// exact match). Ties go to the earliest candidate.
+    #[allow(clippy::cast_precision_loss)]
+    pub fn closest<'a>(
+        &self,
+        key: &str,
+        candidates: &'a [String],
+    ) -> Option<(&'a str, f64)> {
+        let key = normalize(key);
+        let scored = candidates
+            .iter()
+            .map(|candidate| (candidate.as_str(), self.score(&key, &normalize(candidate))));
+
+        match self.metric {
+            FuzzyMetric::Levenshtein => scored
+                .filter(|(_, distance)| *distance <= self.max_distance as f64)
+                .min_set_by(|(_, a), (_, b)| a.total_cmp(b))
+                .into_iter()
+                .next(),
+            FuzzyMetric::JaroWinkler => scored
+                .filter(|(_, similarity)| *similarity >= self.min_similarity)
+                .max_set_by(|(_, a), (_, b)| a.total_cmp(b))
+                .into_iter()
+                .next(),
+        }
+    }
+
+    /// Score of two normalized keys under the metric
+    #[allow(clippy::cast_precision_loss)]
+    fn score(&self, a: &str, b: &str) -> f64 {
+        match self.metric {
+            FuzzyMetric::Levenshtein => edit_distance(a, b) as f64,
+            FuzzyMetric::JaroWinkler => jaro_winkler(a, b),
+        }
+    }
+}
+
+/// Lowercases `key` and collapses runs of whitespace, which rarely tell keys apart
+fn normalize(key: &str) -> String {
+    key.split_whitespace().join(" ").to_lowercase()
+}
+
+/// Jaro-Winkler similarity between two strings, from 0 (nothing in common) to 1 (equal)
+#[allow(clippy::cast_precision_loss)]
+pub fn jaro_winkler(a: &str, b: &str) -> f64 {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    if a.is_empty() || b.is_empty() {
+        return if a == b { 1.0 } else { 0.0 };
+    }
+
+    // Characters match if they're equal and not too far apart
+    let window = (a.len().max(b.len()) / 2).saturating_sub(1);
+    let mut a_matched = vec![false; a.len()];
+    let mut b_matched = vec![false; b.len()];
+    for (i, ca) in a.iter().enumerate() {
+        let end = (i + window + 1).min(b.len());
+        for j in i.saturating_sub(window)..end {
+            if !b_matched[j] && b[j] == *ca {
+                a_matched[i] = true;
+                b_matched[j] = true;
+                break;
+            }
+        }
+    }
+    let matches = a_matched.iter().filter(|m| **m).count();
+    if matches == 0 {
+        return 0.0;
+    }
+
+    // Half the matched characters which are out of order
+    let a_order = a.iter().zip(&a_matched).filter(|(_, m)| **m);
+    let b_order = b.iter().zip(&b_matched).filter(|(_, m)| **m);
+    let transpositions = a_order
+        .zip(b_order)
+        .filter(|((x, _), (y, _))| x != y)
+        .count()
+        / 2;
+
+    let m = matches as f64;
+    let jaro =
+        (m / a.len() as f64 + m / b.len() as f64 + (m - transpositions as f64) / m) / 3.0;
+    let prefix = a.iter().zip(&b).take(4).take_while(|(x, y)| x == y).count();
+
+    (prefix as f64).mul_add(0.1 * (1.0 - jaro), jaro)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_jaro_winkler() {
+        let close = |a, b, expected: f64| (jaro_winkler(a, b) - expected).abs() < 0.001;
+        assert!(close("martha", "marhta", 0.961));
+        assert!(close("dwayne", "duane", 0.840));
+        assert!(close("dixon", "dicksonx", 0.813));
+        assert!(close("same", "same", 1.0));
+        assert!(close("abc", "xyz", 0.0));
+        assert!(close("", "", 1.0));
+        assert!(close("", "a", 0.0));
+    }
+
+    #[test]
+    fn test_closest() {
+        let candidates: Vec<String> = ["John Doe", "Jane Smith", "Robert Brown"]
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        let mut matcher = Matcher {
+            metric: FuzzyMetric::Levenshtein,
+            max_distance: 2,
+            min_similarity: 0.9,
+        };
+
+        assert_eq!(
+            matcher.closest("jon doe", &candidates),
+            Some(("John Doe", 1.0))
+        );
+        assert_eq!(
+            matcher.closest(" JANE   SMITH ", &candidates),
+            Some(("Jane Smith", 0.0))
+        );
+        assert_eq!(matcher.closest("Rob Brown", &candidates), None);
+
+        matcher.metric = FuzzyMetric::JaroWinkler;
+        let (key, similarity) = matcher.closest("Rob Brown", &candidates).unwrap();
+        assert_eq!(key, "Robert Brown");
+        assert!(similarity > 0.9 && similarity < 1.0);
+        assert_eq!(matcher.closest("Alice", &candidates), None);
+    }
+}
@@ -0,0 +1,186 @@
+// Keep the rows of a table where a regular expression matches any (or some) of the columns.
+use anyhow::{Context, Result};
+use clap::{Args, ValueHint};
+use polars::prelude::*;
+use regex::Regex;
+
+use crate::args::GlobalArgs;
+use crate::io::{read_data, report_dropped, resolve_columns, write_data};
+
+#[derive(Args, Debug)]
+pub struct GrepArgs {
+    /// Input table (file or '-' for stdin)
+    #[arg(required = true, value_hint = ValueHint::FilePath)]
+    pub table: String,
+
+    /// Regular expression to search for
+    ///
+    /// Examples: 'Smith' or '^CUSTOMER-00[12]$' or '@example\.(com|org)'
+    #[arg(required = true)]
+    pub pattern: String,
+
+    /// Only search these columns (comma separated) instead of all of them
+    ///
+    /// Examples: --columns notes or --columns "name,notes"
+    #[arg(long, value_delimiter = ',')]
+    pub columns: Vec<String>,
+
+    /// Match letters regardless of case
+    #[arg(short, long)]
+    pub ignore_case: bool,
+
+    /// Keep the rows that don't match instead
+    #[arg(long)]
+    pub invert: bool,
+
+    /// Delimiter for input files
+    #[arg(long, default_value = ",")]
+    pub delimiter: char,
+}
+
+impl GrepArgs {
+    pub fn validate(&self) -> Result<()> {
+        Regex::new(&self.regex())
+            .with_context(|| format!("Invalid pattern '{}'", self.pattern))?;
+
+        Ok(())
+    }
+
+    pub fn execute(&self, global: &GlobalArgs) -> Result<()> {
+        let df =
+            read_data(&self.table, Some(self.delimiter), global).with_context(|| {
+                format!("grep - failed to read csv data from {}", self.table)
+            })?;
+
+        let columns = if self.columns.is_empty() {
+            df.get_column_names_owned()
+                .into_iter()
+                .map(|name| name.to_string())
+                .collect()
+        } else {
+            resolve_columns(&df, &self.columns, global.ignore_column_case)?
+        };
+
+        let mut matched = self.matches(&columns);
+        if self.invert {
+            matched = matched.not();
+        }
+        if global.report {
+            let removed = df.clone().lazy().filter(matched.clone().not()).collect()?;
+            report_dropped(
+                &format!("grep - rows not matching '{}'", self.pattern),
+                &removed,
+            );
+        }
+        let result = df.lazy().filter(matched).collect().with_context(|| {
+            format!(
+                "grep - failed to search {} for '{}'",
+                self.table, self.pattern
+            )
+        })?;
+
+        write_data(result, global)
+            .with_context(|| "grep - failed to write data to stdout".to_string())?;
+
+        Ok(())
+    }
+
+    /// The pattern, made case-insensitive with --ignore-case
+    fn regex(&self) -> String {
+        if self.ignore_case {
+            format!("(?i){}", self.pattern)
+        } else {
+            self.pattern.clone()
+        }
+    }
+
+    /// Whether the pattern matches any of `columns`, which are searched as text
+    ///
+    /// Nulls never match, so they're kept by --invert.
+    fn matches(&self, columns: &[String]) -> Expr {
+        let regex = self.regex();
+        columns
+            .iter()
+            .map(|column| {
+                col(column.as_str())
+                    .cast(DataType::String)
+                    .str()
+                    .contains(lit(regex.as_str()), true)
+                    .fill_null(lit(false))
+            })
+            .reduce(Expr::or)
+            .unwrap_or_else(|| lit(false))
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn grep(pattern: &str) -> GrepArgs {
+        GrepArgs {
+            table: "data/orders/customers.csv".to_string(),
+            pattern: pattern.to_string(),
+            columns: vec![],
+            ignore_case: false,
+            invert: false,
+            delimiter: ',',
+        }
+    }
+
+    fn ids(args: &GrepArgs, df: &DataFrame, columns: &[&str]) -> Vec<i32> {
+        let columns: Vec<String> = columns.iter().map(ToString::to_string).collect();
+        let result = df
+            .clone()
+            .lazy()
+            .filter(if args.invert {
+                args.matches(&columns).not()
+            } else {
+                args.matches(&columns)
+            })
+            .collect()
+            .unwrap();
+        result
+            .column("id")
+            .unwrap()
+            .i32()
+            .unwrap()
+            .into_no_null_iter()
+            .collect()
+    }
+
+    #[test]
+    fn test_grep_matches() {
+        let df = df!(
+            "id" => [1, 2, 3, 4],
+            "name" => [Some("Ada"), Some("grace"), None, Some("Alan")],
+            "notes" => [Some("vip"), None, Some("ada's friend"), Some("")],
+        )
+        .unwrap();
+
+        assert_eq!(ids(&grep("Ada"), &df, &["id", "name", "notes"]), [1]);
+        assert_eq!(ids(&grep("^A"), &df, &["name"]), [1, 4]);
+        assert_eq!(ids(&grep("^[34]$"), &df, &["id"]), [3, 4]);
+
+        let mut args = grep("ada");
+        args.ignore_case = true;
+        assert_eq!(ids(&args, &df, &["name", "notes"]), [1, 3]);
+
+        args.invert = true;
+        assert_eq!(ids(&args, &df, &["name", "notes"]), [2, 4]);
+        assert_eq!(ids(&args, &df, &[]), [1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_grep_customers_csv() {
+        let mut args = grep("smith|brown");
+        args.columns = vec!["customer_name".to_string()];
+        args.ignore_case = true;
+
+        assert!(args.validate().is_ok());
+        assert!(args.execute(&GlobalArgs::default()).is_ok());
+
+        assert!(grep("(unclosed").validate().is_err());
+    }
+}
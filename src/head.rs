@@ -1,38 +1,51 @@
 // Same behaviour as `head` in Unix, but pretty printed with polars.
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, ensure};
 use clap::{Args, ValueHint};
-use std::io;
 
-use crate::args::OutputFormat;
-use crate::io::{read_data, write_data};
+use crate::args::GlobalArgs;
+use crate::io::{read_data, write_files};
 
 #[derive(Args, Debug)]
 pub struct HeadArgs {
-    /// Input table (file or '-' for stdin)
+    /// Input tables (files or '-' for stdin), each shown under a `==> file <==` header
+    ///
+    /// Examples: january.csv february.csv march.csv
     #[arg(required = true, value_hint = ValueHint::FilePath)]
-    pub table: String,
+    pub tables: Vec<String>,
 
     /// Number of lines to display from the beginning
     #[arg(short, long, default_value = "5")]
     pub n: usize,
+
+    /// Stack the files into one table with a leading `__file` column instead of headers
+    #[arg(long)]
+    pub file_column: bool,
 }
 
 impl HeadArgs {
-    #[allow(clippy::unused_self)]
-    #[allow(clippy::unnecessary_wraps)]
-    pub const fn validate(&self) -> io::Result<()> {
+    pub fn validate(&self) -> Result<()> {
+        ensure!(
+            self.tables.iter().filter(|t| *t == "-").count() <= 1,
+            "stdin ('-') can only be used for one table"
+        );
+
         Ok(())
     }
 
     #[allow(clippy::expect_used)]
-    pub fn execute(&self, format: &OutputFormat) -> Result<()> {
-        let data = read_data(self.table.as_str(), Some(',')).with_context(|| {
-            format!("head - failed to read csv data from {}", self.table)
-        })?;
-
-        let head_data = data.head(Some(self.n));
+    pub fn execute(&self, global: &GlobalArgs) -> Result<()> {
+        let parts = self
+            .tables
+            .iter()
+            .map(|table| {
+                let data = read_data(table, Some(','), global).with_context(|| {
+                    format!("head - failed to read csv data from {table}")
+                })?;
+                Ok((table.clone(), data.head(Some(self.n))))
+            })
+            .collect::<Result<Vec<_>>>()?;
 
-        write_data(head_data, format)
+        write_files(parts, self.file_column, global)
             .with_context(|| "head - failed to write data to stdout".to_string())?;
 
         Ok(())
@@ -46,8 +59,9 @@ mod tests {
     #[test]
     fn test_validate_always_succeeds() {
         let args = HeadArgs {
-            table: "test.csv".to_string(),
+            tables: vec!["test.csv".to_string()],
             n: 5,
+            file_column: false,
         };
         assert!(args.validate().is_ok());
     }
@@ -57,21 +71,44 @@ mod tests {
     #[allow(clippy::unwrap_used)]
     fn test_head_nonexistent_file_panics() {
         let args = HeadArgs {
-            table: "nonexistent_file.csv".to_string(),
+            tables: vec!["nonexistent_file.csv".to_string()],
             n: 5,
+            file_column: false,
         };
 
-        args.execute(&crate::args::OutputFormat::Auto).unwrap();
+        args.execute(&GlobalArgs::default()).unwrap();
     }
 
     #[test]
     fn test_head_orders_csv() {
         let args = HeadArgs {
-            table: "data/orders/orders.csv".to_string(),
+            tables: vec!["data/orders/orders.csv".to_string()],
+            n: 2,
+            file_column: false,
+        };
+
+        assert!(args.validate().is_ok());
+        assert!(args.execute(&GlobalArgs::default()).is_ok());
+    }
+
+    #[test]
+    fn test_head_multiple_files() {
+        let mut args = HeadArgs {
+            tables: vec![
+                "data/catalog/v1.csv".to_string(),
+                "data/catalog/v2.csv".to_string(),
+            ],
             n: 2,
+            file_column: false,
         };
 
         assert!(args.validate().is_ok());
-        assert!(args.execute(&crate::args::OutputFormat::Auto).is_ok());
+        assert!(args.execute(&GlobalArgs::default()).is_ok());
+
+        args.file_column = true;
+        assert!(args.execute(&GlobalArgs::default()).is_ok());
+
+        args.tables = vec!["-".to_string(), "-".to_string()];
+        assert!(args.validate().is_err());
     }
 }
@@ -0,0 +1,322 @@
+// Print a histogram of a numeric column as a bar chart, optionally side by side per group.
+use anyhow::{Context, Result, ensure};
+use clap::{Args, ValueHint};
+use polars::prelude::*;
+use std::fmt::Write as _;
+use std::fs;
+use std::io::{self, Write};
+
+use crate::args::{GlobalArgs, OutputFormat};
+use crate::io::{read_data, resolve_columns, write_data};
+
+/// Partial blocks for drawing the fractional end of a bar, in eighths
+const EIGHTHS: [char; 8] = [' ', '▏', '▎', '▍', '▌', '▋', '▊', '▉'];
+
+#[derive(Args, Debug)]
+pub struct HistArgs {
+    /// Input table (file or '-' for stdin)
+    #[arg(required = true, value_hint = ValueHint::FilePath)]
+    pub table: String,
+
+    /// Numeric column to count the values of
+    #[arg(long, required = true)]
+    pub column: String,
+
+    /// Number of equal-width bins between the smallest and largest values
+    #[arg(long, default_value = "10")]
+    pub bins: usize,
+
+    /// Column to group by, drawing a histogram for each group side by side
+    #[arg(long)]
+    pub by: Option<String>,
+
+    /// Width of the chart's bars in characters, shared between the groups with --by
+    #[arg(long, default_value = "40")]
+    pub width: usize,
+
+    /// Delimiter for input files
+    #[arg(long, default_value = ",")]
+    pub delimiter: char,
+}
+
+/// Counts of values per bin, for every group
+#[derive(Debug)]
+struct Histogram {
+    /// The lower edge of each bin and the upper edge of the last one
+    edges: Vec<f64>,
+    /// Group names (a single empty name without --by) and their counts per bin
+    groups: Vec<(String, Vec<u64>)>,
+}
+
+impl HistArgs {
+    pub fn validate(&self) -> Result<()> {
+        ensure!(self.bins > 0, "--bins must be at least 1");
+        ensure!(self.width > 0, "--width must be at least 1");
+
+        Ok(())
+    }
+
+    pub fn execute(&self, global: &GlobalArgs) -> Result<()> {
+        let df =
+            read_data(&self.table, Some(self.delimiter), global).with_context(|| {
+                format!("hist - failed to read csv data from {}", self.table)
+            })?;
+
+        let mut names = vec![self.column.clone()];
+        names.extend(self.by.clone());
+        let names = resolve_columns(&df, &names, global.ignore_column_case)?;
+        let histogram =
+            histogram(&df, &names[0], names.get(1).map(String::as_str), self.bins)
+                .with_context(|| {
+                    format!("hist - failed to bin '{}' in {}", names[0], self.table)
+                })?;
+
+        // An explicit --format writes the counts as a table instead of drawing them
+        if !matches!(global.format, OutputFormat::Auto) {
+            return write_data(histogram.counts(names.get(1).map(String::as_str))?, global)
+                .with_context(|| "hist - failed to write data to stdout".to_string());
+        }
+
+        let chart = histogram.draw(self.width, global.no_unicode);
+        match &global.output {
+            Some(path) => fs::write(path, chart)
+                .with_context(|| format!("hist - failed to write output to {path}"))?,
+            None => io::stdout()
+                .write_all(chart.as_bytes())
+                .context("hist - failed to write chart to stdout")?,
+        }
+
+        Ok(())
+    }
+}
+
+/// Bins the non-null values of `column` into `bins` equal-width bins, per group of `by`
+fn histogram(
+    df: &DataFrame,
+    column: &str,
+    by: Option<&str>,
+    bins: usize,
+) -> Result<Histogram> {
+    let values = df.column(column)?;
+    ensure!(
+        values.dtype().is_primitive_numeric(),
+        "Column '{column}' must be numeric, found {}",
+        values.dtype()
+    );
+    let values = values.cast(&DataType::Float64)?;
+    let values = values.f64()?;
+
+    let min = values.min().unwrap_or_default();
+    let max = values.max().unwrap_or_default();
+    let step = if max > min {
+        (max - min) / f64_from(bins)
+    } else {
+        1.0
+    };
+    let edges: Vec<f64> = (0..=bins)
+        .map(|idx| f64_from(idx).mul_add(step, min))
+        .collect();
+
+    let labels: Vec<String> = match by {
+        Some(by) => {
+            let labels = df.column(by)?.cast(&DataType::String)?;
+            labels
+                .str()?
+                .into_iter()
+                .map(|label| label.unwrap_or("null").to_string())
+                .collect()
+        }
+        None => vec![String::new(); df.height()],
+    };
+
+    let mut groups: Vec<(String, Vec<u64>)> = vec![];
+    for (label, value) in labels.into_iter().zip(values) {
+        let idx = groups.iter().position(|(name, _)| *name == label);
+        let idx = idx.unwrap_or_else(|| {
+            groups.push((label, vec![0; bins]));
+            groups.len() - 1
+        });
+        if let Some(value) = value {
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            let bin = (((value - min) / step).floor() as usize).min(bins - 1);
+            groups[idx].1[bin] += 1;
+        }
+    }
+
+    Ok(Histogram { edges, groups })
+}
+
+#[allow(clippy::cast_precision_loss)]
+const fn f64_from(n: usize) -> f64 {
+    n as f64
+}
+
+impl Histogram {
+    /// Labels each bin as `[lower, upper)`, with the last bin closed
+    fn labels(&self) -> Vec<String> {
+        let step = self.edges.get(1).map_or(1.0, |edge| edge - self.edges[0]);
+        // Whole numbers where possible, otherwise enough decimal places to tell edges apart
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let precision = if self.edges.iter().all(|edge| edge.fract() == 0.0) {
+            0
+        } else {
+            (1.0 - step.log10().floor()).clamp(0.0, 6.0) as usize
+        };
+        let bins = self.edges.len() - 1;
+
+        self.edges
+            .windows(2)
+            .enumerate()
+            .map(|(idx, edge)| {
+                let close = if idx + 1 == bins { ']' } else { ')' };
+                format!("[{:.precision$}, {:.precision$}{close}", edge[0], edge[1])
+            })
+            .collect()
+    }
+
+    /// One row per bin and group with the bin's edges and count
+    fn counts(&self, by: Option<&str>) -> Result<DataFrame> {
+        let bins = self.edges.len() - 1;
+        let mut df = df!(
+            "lower" => self.edges[..bins].repeat(self.groups.len()),
+            "upper" => self.edges[1..].repeat(self.groups.len()),
+            "count" => self.groups.iter().flat_map(|(_, counts)| counts.clone()).collect::<Vec<_>>(),
+        )?;
+        if let Some(by) = by {
+            let names: Vec<&str> = self
+                .groups
+                .iter()
+                .flat_map(|(name, _)| std::iter::repeat_n(name.as_str(), bins))
+                .collect();
+            df.insert_column(0, Column::new(by.into(), names))?;
+        }
+
+        Ok(df)
+    }
+
+    /// Draws a bar per bin, with each group's bars side by side in `width` characters
+    ///
+    /// With `ascii`, bars are drawn with whole `#` characters and separated by `|`.
+    fn draw(&self, width: usize, ascii: bool) -> String {
+        let separator = if ascii { '|' } else { '│' };
+        let labels = self.labels();
+        let label_width = labels.iter().map(String::len).max().unwrap_or_default();
+        let most = self
+            .groups
+            .iter()
+            .flat_map(|(_, counts)| counts)
+            .max()
+            .copied()
+            .unwrap_or_default()
+            .max(1);
+        let count_width = most.to_string().len();
+        let bar_width = (width / self.groups.len().max(1)).max(1);
+        let column_width = bar_width + count_width + 3;
+
+        let mut lines = vec![];
+        if self.groups.len() > 1 || self.groups.iter().any(|(name, _)| !name.is_empty()) {
+            let mut line = format!("{:label_width$}", "");
+            for (name, _) in &self.groups {
+                let _ = write!(line, " {separator} {name:column_width$}");
+            }
+            lines.push(line);
+        }
+        for (bin, label) in labels.iter().enumerate() {
+            let mut line = format!("{label:>label_width$}");
+            for (_, counts) in &self.groups {
+                let count = counts[bin];
+                let bar = bar(count, most, bar_width, ascii);
+                let _ = write!(
+                    line,
+                    " {separator} {bar:bar_width$} {count:>count_width$}  "
+                );
+            }
+            lines.push(line);
+        }
+
+        let mut chart = String::new();
+        for line in lines {
+            chart.push_str(line.trim_end());
+            chart.push('\n');
+        }
+        chart
+    }
+}
+
+/// A bar of `count` relative to `most`, which fills `width` characters
+///
+/// Bars are drawn in eighths of a character with block characters, or in whole `#`
+/// characters with `ascii`.
+fn bar(count: u64, most: u64, width: usize, ascii: bool) -> String {
+    #[allow(clippy::cast_precision_loss)]
+    let eighths = (count as f64 / most as f64 * f64_from(width * 8)).round();
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let eighths = eighths as usize;
+    if ascii {
+        return "#".repeat((eighths + 4) / 8);
+    }
+
+    let mut bar = "█".repeat(eighths / 8);
+    if eighths % 8 > 0 {
+        bar.push(EIGHTHS[eighths % 8]);
+    }
+    bar
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_histogram() {
+        let df = df!(
+            "latency" => [Some(1.0), Some(2.0), Some(2.5), None, Some(5.0)],
+            "host" => ["a", "b", "a", "a", "b"],
+        )
+        .unwrap();
+
+        let result = histogram(&df, "latency", None, 2).unwrap();
+        assert_eq!(result.edges, [1.0, 3.0, 5.0]);
+        assert_eq!(result.groups, [(String::new(), vec![3, 1])]);
+        assert_eq!(result.labels(), ["[1, 3)", "[3, 5]"]);
+
+        let result = histogram(&df, "latency", Some("host"), 2).unwrap();
+        assert_eq!(
+            result.groups,
+            [("a".to_string(), vec![2, 0]), ("b".to_string(), vec![1, 1])]
+        );
+        let counts = result.counts(Some("host")).unwrap();
+        assert_eq!(
+            counts.get_column_names(),
+            &["host", "lower", "upper", "count"]
+        );
+        assert_eq!(counts.height(), 4);
+
+        assert!(histogram(&df, "host", None, 2).is_err());
+    }
+
+    #[test]
+    fn test_bar() {
+        assert_eq!(bar(10, 10, 4, false), "████");
+        assert_eq!(bar(5, 10, 3, false), "█▌");
+        assert_eq!(bar(0, 10, 4, false), "");
+        assert_eq!(bar(5, 10, 3, true), "##");
+        assert_eq!(bar(1, 10, 4, true), "");
+    }
+
+    #[test]
+    fn test_hist_iris_csv() {
+        let args = HistArgs {
+            table: "data/iris/iris.csv".to_string(),
+            column: "PetalLength".to_string(),
+            bins: 6,
+            by: Some("Species".to_string()),
+            width: 30,
+            delimiter: ',',
+        };
+
+        assert!(args.validate().is_ok());
+        assert!(args.execute(&GlobalArgs::default()).is_ok());
+    }
+}
@@ -1,20 +1,71 @@
-use anyhow::{Context, Result, bail};
+use anyhow::{Context, Result, bail, ensure};
 use clap::{Args, ValueEnum, ValueHint};
-use itertools::izip;
+use itertools::{Itertools, izip};
+use log::{debug, info, warn};
 use polars::prelude::{
-    DataFrame, DataFrameJoinOps, JoinArgs as PolarsJoinArgs, JoinType as PolarsJoinType,
+    ChunkAgg, Column, DataFrame, DataType, Expr, IdxSize, IntoLazy,
+    JoinArgs as PolarsJoinArgs, JoinCoalesce, JoinType as PolarsJoinType, LazyFrame,
+    MaintainOrderJoin, SortMultipleOptions, all_horizontal, col, concat_str, len, lit,
+    when,
 };
+use polars_core::utils::try_get_supertype;
 use regex::Regex;
 use std::collections::HashMap;
 
-use crate::args::OutputFormat;
-use crate::io::{read_data, write_data};
+use crate::args::GlobalArgs;
+use crate::fuzzy::{FuzzyMetric, Matcher};
+use crate::io::{
+    print_plan, report_dropped, resolve_columns, scan_data, suggest, table_delimiters,
+    write_data,
+};
 
 #[allow(clippy::expect_used)]
 static RE: std::sync::LazyLock<Regex> = std::sync::LazyLock::new(|| {
     Regex::new(r"\w+\.\w+(=\w+\.\w+)+").expect("Invalid regex pattern")
 });
 
+/// A range condition in `--on`, e.g. `events.ts>=sessions.start` or `ts<end`
+#[allow(clippy::expect_used)]
+static CONDITION: std::sync::LazyLock<Regex> = std::sync::LazyLock::new(|| {
+    Regex::new(r"^(?:(\w+)\.)?(\w+)\s*(<=|>=|<|>)\s*(?:(\w+)\.)?(\w+)$")
+        .expect("Invalid regex pattern")
+});
+
+/// Prefix for the temporary copies of `--select` columns carried through the joins
+const SELECT_PREFIX: &str = "__rabbet_select_";
+
+/// Temporary columns holding the left and right keys compared by fuzzy joins
+const FUZZY_LEFT: &str = "__rabbet_fuzzy_left";
+const FUZZY_RIGHT: &str = "__rabbet_fuzzy_right";
+
+/// Column added by fuzzy joins with how close each pair of keys was
+const MATCH_SCORE: &str = "match_score";
+
+/// Column holding the number of rows of each distinct key, when diagnosing a join
+const DIAGNOSE_ROWS: &str = "__rabbet_rows";
+
+/// Rows of the --diagnose report
+const DIAGNOSE_METRICS: [&str; 8] = [
+    "rows",
+    "rows with null keys",
+    "distinct keys",
+    "distinct keys matched",
+    "rows matched",
+    "rows unmatched",
+    "rows per key",
+    "top unmatched keys",
+];
+
+/// Number of unmatched keys listed by --diagnose
+const DIAGNOSE_TOP: IdxSize = 5;
+
+/// Column added by --indicator saying which tables each row came from
+const MERGE: &str = "_merge";
+
+/// Temporary columns marking the rows of each table, for --indicator
+const INDICATOR_LEFT: &str = "__rabbet_indicator_left";
+const INDICATOR_RIGHT: &str = "__rabbet_indicator_right";
+
 #[derive(Debug, Clone, Copy, ValueEnum, PartialEq, Eq)]
 pub enum JoinType {
     Inner,
@@ -24,6 +75,7 @@ pub enum JoinType {
 }
 
 #[derive(Args, Debug)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct JoinArgs {
     /// Input tables (files or '-' for stdin)
     ///
@@ -44,19 +96,92 @@ pub struct JoinArgs {
     /// Examples:
     /// - Single column: --on "`user_id`"
     /// - Multiple columns: --on "`user_id,region`"
-    /// - Different names: --on "`id:user_id`" (join table1.id with `table2.user_id`)
+    /// - Different names: --on "`id=user_id`" (join table1.id with `table2.user_id`)
+    /// - Labeled tables: --on "`users.id=orders.user_id`" (with --as "users,orders")
+    /// - Ranges: --on "`events.ts>=sessions.start,events.ts<sessions.end`" (with <, <=, >
+    ///   or >=, for inner joins of two tables)
     #[arg(long, value_delimiter = ',')]
     pub on: Vec<String>,
 
-    /// Type of join to perform
+    /// Type of join to perform, or one per pair of tables when joining 3+ (comma separated)
     ///
     /// Options: inner (default), left, right, outer
-    #[arg(long, value_enum, default_value = "inner")]
-    pub r#type: JoinType,
+    /// Examples: --type left or --type "inner,left" (inner join the first two tables, then
+    /// left join the third)
+    #[arg(long, value_enum, value_delimiter = ',', default_value = "inner")]
+    pub r#type: Vec<JoinType>,
+
+    /// Columns to output, in order (comma separated)
+    ///
+    /// Prefix a column with its table label to pick it from a specific table. Tables are
+    /// pruned to their join keys and selected columns before joining.
+    /// Examples: --select "`orders.id,users.name,amount`"
+    #[arg(long, value_delimiter = ',')]
+    pub select: Vec<String>,
 
-    /// Delimiter for input files
+    /// Merge the key columns of outer joins into one column instead of keeping both sides
+    ///
+    /// Without this an outer join on `id` outputs `id` and `id_right`, each null where the
+    /// other side had no match.
+    #[arg(long)]
+    pub coalesce: bool,
+
+    /// Convert join keys read as different types (e.g. integers in one table and text in
+    /// the other) to a common type, instead of failing
+    ///
+    /// Text keys are parsed as the other table's type if they all can be, and otherwise the
+    /// other key is compared as text, with a warning counting the values which can't be.
+    #[arg(long)]
+    pub coerce_keys: bool,
+
+    /// Delimiter for input files, for every table or per table
+    ///
+    /// Give one delimiter for every table, one per table in order, or label=delimiter pairs
+    /// (other tables use ','), with \t for a tab.
+    /// Examples: --delimiter ';' or --delimiter ",,\t" or --delimiter "users=,,events=\t"
     #[arg(long, default_value = ",")]
-    pub delimiter: char,
+    pub delimiter: String,
+
+    /// Print the optimized join plan instead of joining the tables
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Join keys that are close rather than equal, such as names typed differently
+    ///
+    /// Each key in the first table is matched with the closest key in the second, ignoring
+    /// case and repeated whitespace, and a `match_score` column says how close they were.
+    /// Joins two tables on one column each, with an inner or left join.
+    /// Options: levenshtein (default), jaro-winkler
+    /// Examples: --fuzzy or --fuzzy=jaro-winkler
+    #[arg(
+        long,
+        value_enum,
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "levenshtein"
+    )]
+    pub fuzzy: Option<FuzzyMetric>,
+
+    /// Largest edit distance between keys matched with --fuzzy=levenshtein
+    #[arg(long, default_value = "2", requires = "fuzzy")]
+    pub max_distance: usize,
+
+    /// Smallest similarity (0 to 1) between keys matched with --fuzzy=jaro-winkler
+    #[arg(long, default_value = "0.9", requires = "fuzzy")]
+    pub min_similarity: f64,
+
+    /// Add a `_merge` column saying where each row came from: `left_only`, `right_only` or `both`
+    ///
+    /// Useful with outer joins to count or filter the rows without a match. Joins two tables.
+    #[arg(long)]
+    pub indicator: bool,
+
+    /// Report how well the keys of two tables match instead of joining them
+    ///
+    /// Lists the rows, null keys and distinct keys of each table, how many rows find a match,
+    /// whether each side has one or many rows per key and the most common unmatched keys.
+    #[arg(long, conflicts_with_all = ["dry_run", "indicator", "select"])]
+    pub diagnose: bool,
 }
 
 impl JoinArgs {
@@ -73,95 +198,784 @@ impl JoinArgs {
             bail!("At least one column to join on is required");
         }
 
+        let pairs = self.tables.len() - 1;
+        if self.r#type.len() != 1 && self.r#type.len() != pairs {
+            bail!(
+                "--type lists {} join types but {} tables make {pairs} joins. Give one type for every join or one per pair of tables",
+                self.r#type.len(),
+                self.tables.len()
+            );
+        }
+
+        table_delimiters(&self.delimiter, &self.labels())?;
+
+        if self.coalesce && !self.r#type.contains(&JoinType::Outer) {
+            bail!("--coalesce only applies to outer joins. Add --type outer");
+        }
+
+        let conditions = self.conditions()?;
+        if !conditions.is_empty() {
+            ensure!(
+                self.r#type.iter().all(|t| *t == JoinType::Inner),
+                "Range conditions in --on (<, <=, > or >=) only support inner joins"
+            );
+            ensure!(
+                self.fuzzy.is_none(),
+                "--fuzzy can't be combined with range conditions in --on"
+            );
+        }
+
+        if self.fuzzy.is_some() {
+            ensure!(
+                self.tables.len() == 2 && self.on.len() == 1,
+                "--fuzzy joins two tables on one column each, e.g. --on name or --on contact=name"
+            );
+            ensure!(
+                self.r#type
+                    .iter()
+                    .all(|t| matches!(t, JoinType::Inner | JoinType::Left)),
+                "--fuzzy supports inner and left joins"
+            );
+            ensure!(
+                (0.0..=1.0).contains(&self.min_similarity),
+                "--min-similarity must be between 0 and 1"
+            );
+        }
+
+        ensure!(
+            !self.indicator || self.tables.len() == 2,
+            "--indicator joins two tables, but {} were given",
+            self.tables.len()
+        );
+        if self.diagnose {
+            ensure!(
+                self.tables.len() == 2,
+                "--diagnose checks the join of two tables, but {} were given",
+                self.tables.len()
+            );
+            ensure!(
+                self.fuzzy.is_none() && conditions.is_empty(),
+                "--diagnose checks joins on equal keys, so it can't be combined with --fuzzy or range conditions in --on"
+            );
+        }
+
+        for entry in self
+            .equalities()
+            .iter()
+            .filter(|e| !RE.is_match(e) && e.contains('='))
+        {
+            let columns = entry.split('=').count();
+            if columns != self.tables.len() {
+                bail!(
+                    "--on '{entry}' names {columns} columns but {} tables were given",
+                    self.tables.len()
+                );
+            }
+        }
+
         Ok(())
     }
 
-    pub fn execute(&self, format: &OutputFormat) -> Result<()> {
-        let on_map = parse_on_strings(&self.on);
-        let mut tables = create_tables(&self.tables, &self.r#as, &on_map)?;
+    #[allow(clippy::too_many_lines)]
+    pub fn execute(&self, global: &GlobalArgs) -> Result<()> {
+        let on_map = parse_on_strings(&self.equalities());
+        let conditions = self.conditions()?;
+        // Range condition columns are checked and kept through --select like join keys
+        let mut columns = on_map.clone();
+        for condition in &conditions {
+            for (idx, column) in [&condition.left, &condition.right].into_iter().enumerate()
+            {
+                columns
+                    .entry(position_key(idx))
+                    .or_default()
+                    .push(column.clone());
+            }
+        }
+        let delimiters = table_delimiters(&self.delimiter, &self.labels())?;
+        let mut tables =
+            create_tables(&self.tables, &self.r#as, &columns, &delimiters, global)?;
 
         if tables.is_empty() {
             bail!("No tables found");
         }
+        if self.diagnose {
+            return self.write_diagnosis(tables, global);
+        }
 
+        let selected =
+            select_columns(&mut tables, &self.select, global.ignore_column_case)?;
+        if self.indicator {
+            mark_rows(&mut tables)?;
+        }
         let mut result = tables.remove(0);
 
-        for table in tables {
-            result = result.join(&table, self.r#type)?;
+        for (idx, table) in tables.into_iter().enumerate() {
+            let method = self.join_type(idx);
+            let report = global.report && !self.dry_run;
+            let stable = global.stable_order();
+            info!(
+                "{method:?} join of {} on {} with {} on {}",
+                result.name,
+                result.on.join(","),
+                table.name,
+                table.on.join(",")
+            );
+            result = if let Some(metric) = self.fuzzy {
+                let matcher = Matcher {
+                    metric,
+                    max_distance: self.max_distance,
+                    min_similarity: self.min_similarity,
+                };
+                result.fuzzy_join(&table, method, &matcher, report, stable)?
+            } else if !conditions.is_empty() {
+                if report {
+                    warn!("--report doesn't list unmatched rows of range joins");
+                }
+                let keys = [
+                    on_columns(&on_map, &result.name, 0),
+                    on_columns(&on_map, &table.name, 1),
+                ];
+                result.range_join(&table, &keys, &conditions, global.ignore_column_case)?
+            } else {
+                let mut table = table;
+                result.reconcile_keys(&mut table, self.coerce_keys)?;
+                if report {
+                    result.report_unmatched(&table, method)?;
+                }
+                result.join(&table, method, self.coalesce, stable)?
+            };
+        }
+
+        let mut lf = result.lf;
+        if self.indicator {
+            lf = lf.with_column(merge_indicator());
+            result.header = result.header.drop_many([INDICATOR_LEFT, INDICATOR_RIGHT]);
+        }
+        if selected.is_empty() {
+            let mut columns = keys_first(&result.header, &result.keys);
+            if self.indicator {
+                columns.push(col(MERGE));
+            }
+            lf = lf.select(columns);
+        } else {
+            // Coalesced keys are picked from the result, so they're filled from every table
+            let mut columns: Vec<Expr> = selected
+                .iter()
+                .map(|(temporary, name)| {
+                    if self.coalesce && result.keys.contains(name) {
+                        col(name.as_str())
+                    } else {
+                        col(temporary.as_str()).alias(name.as_str())
+                    }
+                })
+                .collect();
+            if self.fuzzy.is_some() {
+                columns.push(col(MATCH_SCORE));
+            }
+            if self.indicator {
+                columns.push(col(MERGE));
+            }
+            lf = lf.select(columns);
+        }
+        if self.dry_run {
+            return print_plan(&lf);
         }
+        if log::log_enabled!(log::Level::Debug) {
+            debug!("join plan:\n{}", lf.describe_optimized_plan()?);
+        }
+        let df = lf
+            .collect()
+            .with_context(|| format!("Failed to join {}", self.tables.join(", ")))?;
 
-        write_data(result.df, format)?;
+        write_data(df, global)?;
 
         Ok(())
     }
+
+    /// Writes the --diagnose report on the keys of the two `tables`, instead of joining them
+    fn write_diagnosis(&self, mut tables: Vec<Table>, global: &GlobalArgs) -> Result<()> {
+        let mut right = tables.pop().context("No tables found")?;
+        let mut left = tables.remove(0);
+        left.reconcile_keys(&mut right, self.coerce_keys)?;
+        let report = left.diagnose(&right).with_context(|| {
+            format!("Failed to compare the keys of {}", self.tables.join(", "))
+        })?;
+
+        write_data(report, global)
+    }
+
+    /// The `--on` entries joining on equal columns
+    fn equalities(&self) -> Vec<String> {
+        self.on
+            .iter()
+            .filter(|entry| !entry.contains(['<', '>']))
+            .cloned()
+            .collect()
+    }
+
+    /// The range conditions in `--on`, comparing columns of the first table with the second
+    fn conditions(&self) -> Result<Vec<Condition>> {
+        let entries: Vec<&String> =
+            self.on.iter().filter(|e| e.contains(['<', '>'])).collect();
+        if entries.is_empty() {
+            return Ok(Vec::new());
+        }
+        ensure!(
+            self.tables.len() == 2,
+            "Range conditions in --on (<, <=, > or >=) join two tables, but {} were given",
+            self.tables.len()
+        );
+
+        let labels = self.labels();
+        entries
+            .into_iter()
+            .map(|entry| Condition::parse(entry, &labels))
+            .collect()
+    }
+
+    /// Labels of the tables, from --as or `T1`, `T2`, ...
+    fn labels(&self) -> Vec<String> {
+        if self.r#as.is_empty() {
+            (0..self.tables.len())
+                .map(|i| format!("T{}", i + 1))
+                .collect()
+        } else {
+            self.r#as.clone()
+        }
+    }
+
+    /// Join type for the `idx`th join, between the result so far and table `idx + 1`
+    fn join_type(&self, idx: usize) -> JoinType {
+        match self.r#type.as_slice() {
+            [method] => *method,
+            methods => methods[idx],
+        }
+    }
 }
 
+/// A lazily scanned input table, so only the columns and rows the join needs are read
 struct Table {
-    df: DataFrame,
+    lf: LazyFrame,
+    /// Empty frame with the table's columns, for resolving and checking names
+    header: DataFrame,
     name: String,
     on: Vec<String>,
+    /// Key columns in the output so far, which are moved to the front of the result
+    keys: Vec<String>,
 }
 
 impl Table {
-    fn load(path: &str, name: &str, on: &[String]) -> Result<Self> {
-        let df = read_data(path, Some(','))
+    fn load(
+        path: &str,
+        name: &str,
+        on: &[String],
+        delimiter: char,
+        global: &GlobalArgs,
+    ) -> Result<Self> {
+        let mut lf = scan_data(path, Some(delimiter), global)
+            .with_context(|| format!("Failed to read table {name} from {path}"))?;
+        let schema = lf
+            .collect_schema()
             .with_context(|| format!("Failed to read table {name} from {path}"))?;
+        let header = DataFrame::empty_with_schema(&schema);
+        let on = resolve_columns(&header, on, global.ignore_column_case)?;
 
         Ok(Self {
-            df,
+            lf,
+            header,
             name: name.to_string(),
-            on: on.to_vec(),
+            keys: on.clone(),
+            on,
         })
     }
 
-    fn join(&self, other: &Self, method: JoinType) -> Result<Self> {
-        let result = match method {
-            JoinType::Inner => self.df.join(
-                &other.df,
-                &self.on,
-                &other.on,
-                PolarsJoinArgs::new(PolarsJoinType::Inner),
-                None,
-            ),
-            JoinType::Left => self.df.join(
-                &other.df,
-                &self.on,
-                &other.on,
-                PolarsJoinArgs::new(PolarsJoinType::Left),
-                None,
-            ),
-            JoinType::Right => other.df.join(
-                &self.df,
-                &other.on,
-                &self.on,
-                PolarsJoinArgs::new(PolarsJoinType::Left),
-                None,
-            ),
-            JoinType::Outer => self.df.join(
-                &other.df,
-                &self.on,
-                &other.on,
-                PolarsJoinArgs::new(PolarsJoinType::Full),
-                None,
-            ),
+    /// Converts the keys of this table and `other` to common types where they differ, so
+    /// e.g. integer ids can match ids read as text
+    ///
+    /// Without `coerce`, mismatched keys fail, since no rows could match. Text keys are
+    /// matched with `text_or`, and other mismatches are converted to a type which can hold
+    /// both, or text if none can.
+    fn reconcile_keys(&mut self, other: &mut Self, coerce: bool) -> Result<()> {
+        let mut casts: [Vec<Expr>; 2] = [vec![], vec![]];
+        for (left, right) in self.on.iter().zip(&other.on) {
+            let left_type = self.header.column(left)?.dtype().clone();
+            let right_type = other.header.column(right)?.dtype().clone();
+            if left_type == right_type {
+                continue;
+            }
+            ensure!(
+                coerce,
+                "join - key '{left}' is {left_type} in '{}' but '{right}' is {right_type} in '{}', so no rows would match\n  pass --coerce-keys to convert them to a common type, or read them as one type with --dtypes",
+                self.name,
+                other.name
+            );
+
+            // Text keys are parsed as the other side's type when every value can be, and
+            // otherwise the other side is compared as text, so no key values are lost
+            let target = match (&left_type, &right_type) {
+                (DataType::String, _) => {
+                    self.text_or(left, right_type.clone(), (&other.name, right))?
+                }
+                (_, DataType::String) => {
+                    other.text_or(right, left_type.clone(), (&self.name, left))?
+                }
+                _ => try_get_supertype(&left_type, &right_type).unwrap_or(DataType::String),
+            };
+            let sides = [(&*self, left, &left_type), (&*other, right, &right_type)];
+            for (idx, (table, key, dtype)) in sides.into_iter().enumerate() {
+                if *dtype != target {
+                    info!(
+                        "converting join key {}.{key} from {dtype} to {target}",
+                        table.name
+                    );
+                    casts[idx].push(col(key.as_str()).cast(target.clone()));
+                }
+            }
+        }
+
+        let [left_casts, right_casts] = casts;
+        for (table, casts) in [(self, left_casts), (other, right_casts)] {
+            if casts.is_empty() {
+                continue;
+            }
+            table.lf = table.lf.clone().with_columns(casts);
+            let schema = table.lf.collect_schema()?;
+            table.header = DataFrame::empty_with_schema(&schema);
+        }
+
+        Ok(())
+    }
+
+    /// `dtype` if every value of the text `column` can be converted to it, and otherwise
+    /// text, warning how many values of `column` aren't like those of the `other` key
+    fn text_or(
+        &self,
+        column: &str,
+        dtype: DataType,
+        other: (&str, &str),
+    ) -> Result<DataType> {
+        let unparsed = self.unparsed(column, &dtype)?;
+        if unparsed == 0 {
+            return Ok(dtype);
+        }
+
+        warn!(
+            "join - {unparsed} value(s) of {}.{column} can't be read as {dtype}, so {}.{} is compared as text instead",
+            self.name, other.0, other.1
+        );
+        Ok(DataType::String)
+    }
+
+    /// Number of non-null values of `column` which can't be converted to `dtype`
+    fn unparsed(&self, column: &str, dtype: &DataType) -> Result<u64> {
+        let unparsed = self
+            .lf
+            .clone()
+            .select([col(column)
+                .is_not_null()
+                .and(col(column).cast(dtype.clone()).is_null())
+                .sum()
+                .cast(DataType::UInt64)])
+            .collect()
+            .with_context(|| format!("Failed to read {column} from {}", self.name))?;
+
+        Ok(unparsed.get_columns()[0].u64()?.get(0).unwrap_or(0))
+    }
+
+    /// Join key expressions
+    fn keys(&self) -> Vec<Expr> {
+        self.on.iter().map(|c| col(c.as_str())).collect()
+    }
+
+    /// Joins `other` onto this table, keeping the key columns of the left side of the join
+    ///
+    /// Outer joins also keep the right side's key columns (suffixed with `_right` when the
+    /// name is taken) unless `coalesce` is set. With `stable`, rows come out in the order of
+    /// the left table, then the right.
+    fn join(
+        &self,
+        other: &Self,
+        method: JoinType,
+        coalesce: bool,
+        stable: bool,
+    ) -> Result<Self> {
+        let (left, right, how) = match method {
+            JoinType::Inner => (self, other, PolarsJoinType::Inner),
+            JoinType::Left => (self, other, PolarsJoinType::Left),
+            JoinType::Right => (other, self, PolarsJoinType::Left),
+            JoinType::Outer => (self, other, PolarsJoinType::Full),
         };
+        // Full joins don't keep row order by default, which makes the output flap
+        let mut args = join_args(how, stable || method == JoinType::Outer);
+        if coalesce {
+            args = args.with_coalesce(JoinCoalesce::CoalesceColumns);
+        }
+        let mut lf =
+            left.lf
+                .clone()
+                .join(right.lf.clone(), left.keys(), right.keys(), args);
 
-        let df = result.with_context(|| {
-            format!("Failed to join '{}' with '{}'", self.name, other.name)
-        })?;
+        let mut keys = left.keys.clone();
+        if method == JoinType::Outer && !coalesce {
+            for key in &right.on {
+                if left.header.get_column_index(key).is_some() {
+                    keys.push(format!("{key}_right"));
+                } else {
+                    keys.push(key.clone());
+                }
+            }
+        }
+        let schema = lf
+            .collect_schema()
+            .with_context(|| format!("Failed to join {} with {}", self.name, other.name))?;
 
         Ok(Self {
-            df,
+            lf,
+            header: DataFrame::empty_with_schema(&schema),
+            name: self.name.clone(),
+            on: left.on.clone(),
+            keys,
+        })
+    }
+
+    /// Joins `other` onto this table where the range `conditions` hold and the `keys` of
+    /// each side (if any) are equal
+    ///
+    /// Right columns named like left ones get the usual `_right` suffix before joining, so
+    /// the conditions can tell them apart.
+    fn range_join(
+        &self,
+        other: &Self,
+        keys: &[Vec<String>; 2],
+        conditions: &[Condition],
+        ignore_case: bool,
+    ) -> Result<Self> {
+        let left_keys = resolve_columns(&self.header, &keys[0], ignore_case)?;
+        let right_keys = resolve_columns(&other.header, &keys[1], ignore_case)?;
+        if left_keys.len() != right_keys.len() {
+            bail!(
+                "Tables {} and {} have different numbers of join columns",
+                self.name,
+                other.name
+            );
+        }
+
+        // The tables may have been pruned to the columns --select needs
+        let schema = |table: &Self| {
+            table.lf.clone().collect_schema().with_context(|| {
+                format!("Failed to join {} with {}", self.name, other.name)
+            })
+        };
+        let left_schema = schema(self)?;
+        let clashing: Vec<String> = schema(other)?
+            .iter_names()
+            .filter(|c| left_schema.contains(c))
+            .map(ToString::to_string)
+            .collect();
+        let renamed: Vec<String> = clashing.iter().map(|c| format!("{c}_right")).collect();
+        let right_column = |name: &str| -> Result<Expr> {
+            let name =
+                resolve_columns(&other.header, &[name.to_string()], ignore_case)?.remove(0);
+            if clashing.contains(&name) {
+                Ok(col(format!("{name}_right")))
+            } else {
+                Ok(col(name))
+            }
+        };
+
+        let mut predicates = Vec::with_capacity(left_keys.len() + conditions.len());
+        for (left, right) in left_keys.iter().zip(&right_keys) {
+            predicates.push(col(left.as_str()).eq(right_column(right)?));
+        }
+        for condition in conditions {
+            let left = resolve_columns(
+                &self.header,
+                std::slice::from_ref(&condition.left),
+                ignore_case,
+            )?
+            .remove(0);
+            predicates.push(
+                condition
+                    .op
+                    .compare(col(left), right_column(&condition.right)?),
+            );
+        }
+
+        let right = other.lf.clone().rename(&clashing, &renamed, true);
+        let mut lf = self
+            .lf
+            .clone()
+            .join_builder()
+            .with(right)
+            .how(PolarsJoinType::Inner)
+            .join_where(predicates);
+        let schema = lf
+            .collect_schema()
+            .with_context(|| format!("Failed to join {} with {}", self.name, other.name))?;
+
+        Ok(Self {
+            lf,
+            header: DataFrame::empty_with_schema(&schema),
             name: self.name.clone(),
             on: self.on.clone(),
+            keys: Vec::new(),
         })
     }
+
+    /// Joins `other` onto this table by matching each key with the closest key in `other`
+    ///
+    /// Both tables have a single key column, compared as text. The output keeps both key
+    /// columns, followed by the `match_score` of each pair.
+    fn fuzzy_join(
+        &self,
+        other: &Self,
+        method: JoinType,
+        matcher: &Matcher,
+        report: bool,
+        stable: bool,
+    ) -> Result<Self> {
+        let (left_key, right_key) = (self.on[0].as_str(), other.on[0].as_str());
+        let pairs = self.fuzzy_pairs(other, matcher)?;
+
+        let left = self
+            .lf
+            .clone()
+            .with_column(col(left_key).cast(DataType::String).alias(FUZZY_LEFT))
+            .join(
+                pairs.clone().lazy(),
+                [col(FUZZY_LEFT)],
+                [col(FUZZY_LEFT)],
+                join_args(PolarsJoinType::Left, stable),
+            );
+        let left = without(left, &[FUZZY_LEFT])?;
+        let right = other
+            .lf
+            .clone()
+            .with_column(col(right_key).cast(DataType::String).alias(FUZZY_RIGHT));
+
+        if report {
+            if method == JoinType::Inner {
+                report_dropped(
+                    &format!(
+                        "join - '{}' rows without a fuzzy match in '{}'",
+                        self.name, other.name
+                    ),
+                    &without(
+                        left.clone().filter(col(FUZZY_RIGHT).is_null()),
+                        &[FUZZY_RIGHT, MATCH_SCORE],
+                    )?
+                    .collect()?,
+                );
+            }
+            report_dropped(
+                &format!(
+                    "join - '{}' rows without a fuzzy match in '{}'",
+                    other.name, self.name
+                ),
+                &without(
+                    right.clone().join(
+                        pairs.lazy().select([col(FUZZY_RIGHT)]),
+                        [col(FUZZY_RIGHT)],
+                        [col(FUZZY_RIGHT)],
+                        PolarsJoinArgs::new(PolarsJoinType::Anti),
+                    ),
+                    &[FUZZY_RIGHT],
+                )?
+                .collect()?,
+            );
+        }
+
+        let how = match method {
+            JoinType::Left => PolarsJoinType::Left,
+            _ => PolarsJoinType::Inner,
+        };
+        let lf = without(
+            left.join(
+                right,
+                [col(FUZZY_RIGHT)],
+                [col(FUZZY_RIGHT)],
+                join_args(how, stable),
+            ),
+            &[FUZZY_RIGHT],
+        )?;
+
+        let right_name = if self.header.get_column_index(right_key).is_some() {
+            format!("{right_key}_right")
+        } else {
+            right_key.to_string()
+        };
+        let mut keys = self.keys.clone();
+        keys.extend([right_name, MATCH_SCORE.to_string()]);
+        let schema = lf
+            .clone()
+            .collect_schema()
+            .with_context(|| format!("Failed to join {} with {}", self.name, other.name))?;
+
+        Ok(Self {
+            lf,
+            header: DataFrame::empty_with_schema(&schema),
+            name: self.name.clone(),
+            on: self.on.clone(),
+            keys,
+        })
+    }
+
+    /// Pairs each distinct key of this table with the closest key in `other` (if any is close
+    /// enough) and its score
+    fn fuzzy_pairs(&self, other: &Self, matcher: &Matcher) -> Result<DataFrame> {
+        let left_keys = distinct_text(&self.lf, &self.on[0])?;
+        let right_keys = distinct_text(&other.lf, &other.on[0])?;
+
+        let (mut lefts, mut rights, mut scores) = (vec![], vec![], vec![]);
+        for key in &left_keys {
+            if let Some((closest, score)) = matcher.closest(key, &right_keys) {
+                lefts.push(key.as_str());
+                rights.push(closest);
+                // Similarities are rounded, as their last digits only add noise
+                scores.push((score * 1000.0).round() / 1000.0);
+            }
+        }
+        info!(
+            "fuzzy matched {} of {} key(s) in {} with {}",
+            lefts.len(),
+            left_keys.len(),
+            self.name,
+            other.name
+        );
+        let scores = Column::new(MATCH_SCORE.into(), scores);
+        let scores = match matcher.metric {
+            FuzzyMetric::Levenshtein => scores.cast(&DataType::UInt32)?,
+            FuzzyMetric::JaroWinkler => scores,
+        };
+        Ok(DataFrame::new(vec![
+            Column::new(FUZZY_LEFT.into(), lefts),
+            Column::new(FUZZY_RIGHT.into(), rights),
+            scores,
+        ])?)
+    }
+
+    /// Returns the rows of this table without a matching key in `other`
+    fn unmatched(&self, other: &Self) -> Result<DataFrame> {
+        let keys = other.lf.clone().select(other.keys());
+
+        Ok(self
+            .lf
+            .clone()
+            .join(
+                keys,
+                self.keys(),
+                other.keys(),
+                PolarsJoinArgs::new(PolarsJoinType::Anti),
+            )
+            .collect()?)
+    }
+
+    /// How well the keys of this table and `other` match, as a `metric` column naming each
+    /// of `DIAGNOSE_METRICS` and a column of values for each table
+    ///
+    /// Rows with a null key never match, so they're counted apart from the distinct keys.
+    fn diagnose(&self, other: &Self) -> Result<DataFrame> {
+        let counts = [self.key_counts()?, other.key_counts()?];
+        let mut columns = vec![Column::new("metric".into(), DIAGNOSE_METRICS)];
+        for (idx, table) in [self, other].into_iter().enumerate() {
+            let (rows, nulls, keys) = &counts[idx];
+            let (_, _, other_keys) = &counts[1 - idx];
+            let matched = split_keys(keys, other_keys, PolarsJoinType::Semi)?;
+            let unmatched = split_keys(keys, other_keys, PolarsJoinType::Anti)?;
+            let matched_rows = total_rows(&matched)?;
+            let share = |n: u64| {
+                #[allow(clippy::cast_precision_loss)]
+                let percent = n as f64 / (*rows).max(1) as f64 * 100.0;
+                format!("{n} ({percent:.1}%)")
+            };
+            let most = keys.column(DIAGNOSE_ROWS)?.u64()?.max().unwrap_or(0);
+
+            let values = [
+                rows.to_string(),
+                nulls.to_string(),
+                keys.height().to_string(),
+                matched.height().to_string(),
+                share(matched_rows),
+                share(rows - matched_rows),
+                if most > 1 { "many" } else { "one" }.to_string(),
+                top_keys(&unmatched)?,
+            ];
+            columns.push(Column::new(table.name.as_str().into(), values));
+        }
+
+        Ok(DataFrame::new(columns)?)
+    }
+
+    /// The number of rows, the number of rows with a null key, and the number of rows of
+    /// each distinct key (in a `DIAGNOSE_ROWS` column after the keys, named by position)
+    fn key_counts(&self) -> Result<(u64, u64, DataFrame)> {
+        let names: Vec<String> = (0..self.on.len()).map(position_key).collect();
+        let keyed = self
+            .lf
+            .clone()
+            .select(
+                self.keys()
+                    .into_iter()
+                    .zip(&names)
+                    .map(|(key, name)| key.alias(name.as_str()))
+                    .collect::<Vec<_>>(),
+            )
+            .collect()
+            .with_context(|| format!("Failed to read the keys of {}", self.name))?;
+
+        let present: Vec<Expr> = names
+            .iter()
+            .map(|name| col(name.as_str()).is_not_null())
+            .collect();
+        let counts = keyed
+            .clone()
+            .lazy()
+            .filter(all_horizontal(present)?)
+            .group_by_stable(
+                names
+                    .iter()
+                    .map(|name| col(name.as_str()))
+                    .collect::<Vec<_>>(),
+            )
+            .agg([len().cast(DataType::UInt64).alias(DIAGNOSE_ROWS)])
+            .collect()?;
+        let rows = keyed.height() as u64;
+
+        Ok((rows, rows - total_rows(&counts)?, counts))
+    }
+
+    /// Reports the rows from either side which will be dropped by joining with `other`
+    fn report_unmatched(&self, other: &Self, method: JoinType) -> Result<()> {
+        if matches!(method, JoinType::Inner | JoinType::Right) {
+            report_dropped(
+                &format!(
+                    "join - '{}' rows without a match in '{}'",
+                    self.name, other.name
+                ),
+                &self.unmatched(other)?,
+            );
+        }
+
+        if matches!(method, JoinType::Inner | JoinType::Left) {
+            report_dropped(
+                &format!(
+                    "join - '{}' rows without a match in '{}'",
+                    other.name, self.name
+                ),
+                &other.unmatched(self)?,
+            );
+        }
+
+        Ok(())
+    }
 }
 
 fn create_tables(
     paths: &[String],
     names: &[String],
     on: &HashMap<String, Vec<String>>,
+    delimiters: &[char],
+    global: &GlobalArgs,
 ) -> Result<Vec<Table>> {
     if !names.is_empty() && names.len() != paths.len() {
         bail!("Number of names must match number of tables");
@@ -173,24 +987,437 @@ fn create_tables(
         names.to_vec()
     };
 
-    let global_cols = on.get("*").cloned().unwrap_or_default();
-
-    izip!(paths, labels)
-        .map(|(p, l)| {
-            let mut on_cols = global_cols.clone();
-            if let Some(cols) = on.get(&l) {
-                on_cols.extend_from_slice(cols);
-            }
+    check_labels(on, &labels, names.is_empty())?;
 
+    let tables = izip!(paths, labels, delimiters)
+        .enumerate()
+        .map(|(idx, (p, l, delimiter))| {
+            let on_cols = on_columns(on, &l, idx);
             if on_cols.is_empty() {
                 bail!("No columns specified for join on table '{l}'");
             }
 
-            Table::load(p, &l, &on_cols)
+            Table::load(p, &l, &on_cols, *delimiter, global)
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    // Check every table up front, so one run reports all the misspelled columns
+    let reports: Vec<String> = izip!(&tables, paths)
+        .filter_map(|(table, path)| {
+            check_columns(&table.header, &table.on, &table.name, path)
+                .err()
+                .map(|e| e.to_string())
+        })
+        .collect();
+    ensure!(reports.is_empty(), reports.join("\n"));
+
+    Ok(tables)
+}
+
+/// Join columns of the table labeled `label` at position `idx`, from `parse_on_strings`
+fn on_columns(on: &HashMap<String, Vec<String>>, label: &str, idx: usize) -> Vec<String> {
+    [on.get("*"), on.get(label), on.get(&position_key(idx))]
+        .into_iter()
+        .flatten()
+        .flatten()
+        .cloned()
+        .collect()
+}
+
+/// Fails if `--on` references table labels which don't exist, suggesting likely fixes
+fn check_labels(
+    on: &HashMap<String, Vec<String>>,
+    labels: &[String],
+    default_labels: bool,
+) -> Result<()> {
+    let mut unknown: Vec<&String> = on
+        .keys()
+        .filter(|key| *key != "*" && !key.starts_with('#') && !labels.contains(key))
+        .collect();
+    if unknown.is_empty() {
+        return Ok(());
+    }
+    unknown.sort();
+
+    let mut report: Vec<String> = unknown
+        .iter()
+        .map(|label| {
+            let hint = suggest(label, labels.iter().map(String::as_str))
+                .map(|close| format!(", did you mean '{close}'?"))
+                .unwrap_or_default();
+            format!("--on references unknown table label '{label}'{hint}")
         })
+        .collect();
+    report.push(format!("  tables are labeled: {}", labels.join(", ")));
+    if default_labels {
+        report
+            .push("  name your tables with --as (e.g., --as \"users,orders\")".to_string());
+    }
+
+    bail!(report.join("\n"))
+}
+
+/// Polars join arguments for `how`, keeping the order of the left rows, then the right,
+/// when `stable`
+pub fn join_args(how: PolarsJoinType, stable: bool) -> PolarsJoinArgs {
+    let mut args = PolarsJoinArgs::new(how);
+    if stable {
+        args.maintain_order = MaintainOrderJoin::LeftRight;
+    }
+
+    args
+}
+
+/// Fails if any join columns are missing from `df`, suggesting likely fixes for each
+fn check_columns(df: &DataFrame, on: &[String], name: &str, path: &str) -> Result<()> {
+    let columns = df.get_column_names_str();
+    let missing: Vec<&String> = on
+        .iter()
+        .filter(|c| !columns.contains(&c.as_str()))
+        .unique()
+        .collect();
+    if missing.is_empty() {
+        return Ok(());
+    }
+
+    let mut report = vec![];
+    for missing in missing {
+        report.push(format!(
+            "Column '{missing}' not found in table '{name}' ({path})"
+        ));
+        if let Some(close) = suggest(missing, columns.iter().copied()) {
+            if close.eq_ignore_ascii_case(missing) {
+                report.push(format!(
+                    "  did you mean '{close}'? (or pass --ignore-column-case)"
+                ));
+            } else {
+                report.push(format!("  did you mean '{close}'?"));
+            }
+        }
+    }
+    report.push(format!("  available columns: {}", columns.join(", ")));
+
+    bail!(report.join("\n"))
+}
+
+/// Column expressions for `header` with the `keys` columns moved to the front
+fn keys_first(header: &DataFrame, keys: &[String]) -> Vec<Expr> {
+    let rest = header
+        .get_column_names()
+        .into_iter()
+        .filter(|c| !keys.iter().any(|k| k == c.as_str()))
+        .map(ToString::to_string);
+
+    keys.iter()
+        .cloned()
+        .chain(rest)
+        .map(|c| col(c.as_str()))
         .collect()
 }
 
+/// The key `counts` (from `Table::key_counts`) which also appear in `other` with a semi
+/// join, or which don't with an anti join
+fn split_keys(
+    counts: &DataFrame,
+    other: &DataFrame,
+    how: PolarsJoinType,
+) -> Result<DataFrame> {
+    let keys: Vec<Expr> = counts
+        .get_column_names_str()
+        .into_iter()
+        .filter(|c| *c != DIAGNOSE_ROWS)
+        .map(col)
+        .collect();
+
+    Ok(counts
+        .clone()
+        .lazy()
+        .join(
+            other.clone().lazy().select(keys.clone()),
+            keys.clone(),
+            keys,
+            PolarsJoinArgs::new(how),
+        )
+        .collect()?)
+}
+
+/// The total number of rows of the key `counts`
+fn total_rows(counts: &DataFrame) -> Result<u64> {
+    Ok(counts.column(DIAGNOSE_ROWS)?.u64()?.sum().unwrap_or(0))
+}
+
+/// The keys of `counts` with the most rows, as `key (rows)`, with the columns of multi-column
+/// keys separated by `|`
+fn top_keys(counts: &DataFrame) -> Result<String> {
+    let keys: Vec<Expr> = counts
+        .get_column_names_str()
+        .into_iter()
+        .filter(|c| *c != DIAGNOSE_ROWS)
+        .map(|c| col(c).cast(DataType::String))
+        .collect();
+    let top = counts
+        .clone()
+        .lazy()
+        .sort(
+            [DIAGNOSE_ROWS],
+            SortMultipleOptions::default()
+                .with_order_descending(true)
+                .with_maintain_order(true),
+        )
+        .limit(DIAGNOSE_TOP)
+        .select([
+            concat_str(keys, "|", false).alias("key"),
+            col(DIAGNOSE_ROWS),
+        ])
+        .collect()?;
+
+    Ok(
+        izip!(top.column("key")?.str()?, top.column(DIAGNOSE_ROWS)?.u64()?)
+            .map(|(key, rows)| {
+                format!("{} ({})", key.unwrap_or_default(), rows.unwrap_or(0))
+            })
+            .join(", "),
+    )
+}
+
+/// Distinct non-null values of `column` as text, in order of first appearance
+fn distinct_text(lf: &LazyFrame, column: &str) -> Result<Vec<String>> {
+    let df = lf
+        .clone()
+        .select([col(column).cast(DataType::String)])
+        .collect()?;
+
+    Ok(df
+        .column(column)?
+        .str()?
+        .into_iter()
+        .flatten()
+        .unique()
+        .map(String::from)
+        .collect())
+}
+
+/// `lf` without the `columns`
+fn without(mut lf: LazyFrame, columns: &[&str]) -> Result<LazyFrame> {
+    let keep: Vec<Expr> = lf
+        .collect_schema()?
+        .iter_names()
+        .filter(|name| !columns.contains(&name.as_str()))
+        .map(|name| col(name.as_str()))
+        .collect();
+
+    Ok(lf.select(keep))
+}
+
+/// Marks the rows of the two `tables` so `merge_indicator` can tell which matched
+fn mark_rows(tables: &mut [Table]) -> Result<()> {
+    for (table, marker) in tables.iter_mut().zip([INDICATOR_LEFT, INDICATOR_RIGHT]) {
+        ensure!(
+            table.header.get_column_index(MERGE).is_none(),
+            "--indicator adds a {MERGE} column, but {} already has one",
+            table.name
+        );
+        table.lf = table.lf.clone().with_column(lit(true).alias(marker));
+    }
+
+    Ok(())
+}
+
+/// The `_merge` column of --indicator, from the row markers of each table
+///
+/// A table's marker is only null where the join found no row in that table.
+fn merge_indicator() -> Expr {
+    when(col(INDICATOR_LEFT).is_null())
+        .then(lit("right_only"))
+        .when(col(INDICATOR_RIGHT).is_null())
+        .then(lit("left_only"))
+        .otherwise(lit("both"))
+        .alias(MERGE)
+}
+
+/// Prunes `tables` down to their join keys and the `--select` columns
+///
+/// Each selected column is copied to a temporary column so it survives the joins
+/// regardless of name clashes or key coalescing. Returns the temporary column names
+/// with the names they should have in the output, in `--select` order.
+fn select_columns(
+    tables: &mut [Table],
+    select: &[String],
+    ignore_case: bool,
+) -> Result<Vec<(String, String)>> {
+    if select.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut picks: Vec<(usize, String)> = Vec::with_capacity(select.len());
+    for entry in select {
+        let (label, column) = match entry.split_once('.') {
+            Some((label, column)) if tables.iter().any(|t| t.name == label) => {
+                (Some(label), column)
+            }
+            _ => (None, entry.as_str()),
+        };
+        let candidates: Vec<usize> = (0..tables.len())
+            .filter(|idx| label.is_none_or(|label| tables[*idx].name == label))
+            .collect();
+
+        let mut found = None;
+        for idx in &candidates {
+            let df = &tables[*idx].header;
+            let resolved =
+                resolve_columns(df, &[column.to_string()], ignore_case)?.remove(0);
+            if df.get_column_index(&resolved).is_some() {
+                found = Some((*idx, resolved));
+                break;
+            }
+        }
+
+        let Some(pick) = found else {
+            let columns: Vec<String> = candidates
+                .iter()
+                .flat_map(|idx| {
+                    let table = &tables[*idx];
+                    table
+                        .header
+                        .get_column_names_str()
+                        .into_iter()
+                        .map(|c| format!("{}.{c}", table.name))
+                        .collect::<Vec<_>>()
+                })
+                .collect();
+            let mut report = vec![format!("--select column '{entry}' not found")];
+            let bare: Vec<&str> = columns
+                .iter()
+                .filter_map(|c| c.split_once('.').map(|(_, c)| c))
+                .collect();
+            if let Some(close) = suggest(column, bare.iter().copied()) {
+                let close =
+                    label.map_or_else(|| close.to_string(), |l| format!("{l}.{close}"));
+                report.push(format!("  did you mean '{close}'?"));
+            }
+            report.push(format!("  available columns: {}", columns.join(", ")));
+            bail!(report.join("\n"));
+        };
+        picks.push(pick);
+    }
+
+    let mut selected = Vec::with_capacity(picks.len());
+    for (idx, table) in tables.iter_mut().enumerate() {
+        let mut keep: Vec<Expr> =
+            table.on.iter().unique().map(|c| col(c.as_str())).collect();
+        for (k, (_, column)) in picks.iter().enumerate().filter(|(_, (i, _))| *i == idx) {
+            keep.push(col(column.as_str()).alias(format!("{SELECT_PREFIX}{k}")));
+        }
+        table.lf = table.lf.clone().select(keep);
+    }
+
+    for (k, (idx, column)) in picks.iter().enumerate() {
+        let shared = picks.iter().filter(|(_, c)| c == column).count() > 1;
+        let name = if shared {
+            format!("{}.{column}", tables[*idx].name)
+        } else {
+            column.clone()
+        };
+        selected.push((format!("{SELECT_PREFIX}{k}"), name));
+    }
+
+    Ok(selected)
+}
+
+/// Key under which `parse_on_strings` stores the columns for the table at `idx`
+fn position_key(idx: usize) -> String {
+    format!("#{}", idx + 1)
+}
+
+/// How a range condition compares a column of the first table with one of the second
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Comparison {
+    Lt,
+    LtEq,
+    Gt,
+    GtEq,
+}
+
+impl Comparison {
+    /// The same comparison with its sides swapped, e.g. `a < b` as `b > a`
+    const fn flip(self) -> Self {
+        match self {
+            Self::Lt => Self::Gt,
+            Self::LtEq => Self::GtEq,
+            Self::Gt => Self::Lt,
+            Self::GtEq => Self::LtEq,
+        }
+    }
+
+    fn compare(self, left: Expr, right: Expr) -> Expr {
+        match self {
+            Self::Lt => left.lt(right),
+            Self::LtEq => left.lt_eq(right),
+            Self::Gt => left.gt(right),
+            Self::GtEq => left.gt_eq(right),
+        }
+    }
+}
+
+/// A range condition from `--on`, between a column of the first table and one of the second
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Condition {
+    left: String,
+    op: Comparison,
+    right: String,
+}
+
+impl Condition {
+    /// Parses `entry` (e.g. `events.ts>=sessions.start` or `ts<end`) for two tables labeled
+    /// `labels`, swapping the sides if the second table's column comes first
+    ///
+    /// Unlabeled columns on the left belong to the first table and those on the right to the
+    /// second.
+    fn parse(entry: &str, labels: &[String]) -> Result<Self> {
+        let captures = CONDITION.captures(entry.trim()).with_context(|| {
+            format!(
+                "Invalid --on condition '{entry}'. Expected two columns compared with <, <=, > or >=, e.g. \"events.ts>=sessions.start\""
+            )
+        })?;
+        let table = |label: Option<regex::Match>, default: usize| -> Result<usize> {
+            let Some(label) = label.map(|l| l.as_str()) else {
+                return Ok(default);
+            };
+            labels.iter().position(|l| l == label).with_context(|| {
+                let hint = suggest(label, labels.iter().map(String::as_str))
+                    .map(|close| format!(", did you mean '{close}'?"))
+                    .unwrap_or_default();
+                format!("--on references unknown table label '{label}'{hint}")
+            })
+        };
+        let op = match &captures[3] {
+            "<" => Comparison::Lt,
+            "<=" => Comparison::LtEq,
+            ">" => Comparison::Gt,
+            _ => Comparison::GtEq,
+        };
+        let (left, right) = (captures[2].to_string(), captures[5].to_string());
+
+        match (table(captures.get(1), 0)?, table(captures.get(4), 1)?) {
+            (0, 1) => Ok(Self { left, op, right }),
+            (1, 0) => Ok(Self {
+                left: right,
+                op: op.flip(),
+                right: left,
+            }),
+            _ => bail!(
+                "--on condition '{entry}' must compare a column of {} with one of {}",
+                labels[0],
+                labels[1]
+            ),
+        }
+    }
+}
+
+/// Parses `--on` entries into join columns keyed by table label
+///
+/// Plain column names apply to every table (`*`), `T1.a=T2.b` entries apply to the
+/// labeled tables and unlabeled `a=b` entries apply to the tables by position.
 fn parse_on_strings(on: &[String]) -> HashMap<String, Vec<String>> {
     let default_key = "*".to_string();
     let mut result: HashMap<String, Vec<String>> = HashMap::with_capacity(on.len());
@@ -207,6 +1434,10 @@ fn parse_on_strings(on: &[String]) -> HashMap<String, Vec<String>> {
                 .split('=')
                 .filter_map(|part| part.split_once('.'))
                 .for_each(|(label, column)| insert(&mut result, label, column));
+        } else if entry.contains('=') {
+            entry.split('=').enumerate().for_each(|(idx, column)| {
+                insert(&mut result, &position_key(idx), column);
+            });
         } else {
             insert(&mut result, &default_key, entry);
         }
@@ -218,6 +1449,8 @@ fn parse_on_strings(on: &[String]) -> HashMap<String, Vec<String>> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use polars::df;
+    use polars::prelude::{IntoLazy, SortMultipleOptions};
     use std::collections::HashMap;
     use std::io::Write;
     use tempfile::NamedTempFile;
@@ -246,7 +1479,14 @@ mod tests {
         let mut on = HashMap::new();
         on.insert("*".to_string(), vec!["id".to_string()]);
 
-        let result = create_tables(&tables, &labels, &on).unwrap();
+        let result = create_tables(
+            &tables,
+            &labels,
+            &on,
+            &vec![','; tables.len()],
+            &GlobalArgs::default(),
+        )
+        .unwrap();
 
         assert_eq!(result.len(), 2);
         assert_eq!(result[0].name, "users");
@@ -282,7 +1522,14 @@ mod tests {
         let mut on = HashMap::new();
         on.insert("*".to_string(), vec!["id".to_string()]);
 
-        let result = create_tables(&tables, &labels, &on).unwrap();
+        let result = create_tables(
+            &tables,
+            &labels,
+            &on,
+            &vec![','; tables.len()],
+            &GlobalArgs::default(),
+        )
+        .unwrap();
 
         assert_eq!(result.len(), 3);
         assert_eq!(result[0].name, "T1");
@@ -316,7 +1563,268 @@ mod tests {
         let mut on = HashMap::new();
         on.insert("*".to_string(), vec!["id".to_string()]);
 
-        let _result = create_tables(&tables, &labels, &on).unwrap();
+        let _result = create_tables(
+            &tables,
+            &labels,
+            &on,
+            &vec![','; tables.len()],
+            &GlobalArgs::default(),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn test_unmatched_rows() {
+        let table = |df: DataFrame, name: &str, on: &str| Table {
+            lf: df.clone().lazy(),
+            header: df.clear(),
+            name: name.to_string(),
+            on: vec![on.to_string()],
+            keys: vec![on.to_string()],
+        };
+        let users = table(
+            df! { "id" => [1, 2, 3], "name" => ["Alice", "Bob", "Carol"] }.unwrap(),
+            "users",
+            "id",
+        );
+        let orders = table(
+            df! { "user_id" => [1, 1, 4], "product" => ["Widget", "Gadget", "Gizmo"] }
+                .unwrap(),
+            "orders",
+            "user_id",
+        );
+
+        let unmatched_users = users.unmatched(&orders).unwrap();
+        assert_eq!(unmatched_users.get_column_names(), &["id", "name"]);
+        assert_eq!(
+            unmatched_users
+                .column("id")
+                .unwrap()
+                .i32()
+                .unwrap()
+                .to_vec(),
+            [Some(2), Some(3)]
+        );
+
+        let unmatched_orders = orders.unmatched(&users).unwrap();
+        assert_eq!(unmatched_orders.height(), 1);
+        assert!(users.report_unmatched(&orders, JoinType::Inner).is_ok());
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn test_outer_join_keys() {
+        let table = |df: DataFrame, name: &str| Table {
+            lf: df.clone().lazy(),
+            header: df.clear(),
+            name: name.to_string(),
+            on: vec!["id".to_string()],
+            keys: vec!["id".to_string()],
+        };
+        let users = table(
+            df! { "name" => ["Alice", "Bob"], "id" => [1, 2] }.unwrap(),
+            "users",
+        );
+        let orders = table(
+            df! { "id" => [2, 3], "product" => ["Widget", "Gadget"] }.unwrap(),
+            "orders",
+        );
+        let collect = |joined: Table| {
+            joined
+                .lf
+                .select(keys_first(&joined.header, &joined.keys))
+                .sort(["name"], SortMultipleOptions::default())
+                .collect()
+                .unwrap()
+        };
+
+        let df = collect(users.join(&orders, JoinType::Outer, false, false).unwrap());
+        assert_eq!(
+            df.get_column_names(),
+            &["id", "id_right", "name", "product"]
+        );
+
+        let df = collect(users.join(&orders, JoinType::Outer, true, false).unwrap());
+        assert_eq!(df.get_column_names(), &["id", "name", "product"]);
+        assert_eq!(
+            df.column("id").unwrap().i32().unwrap().to_vec(),
+            [Some(3), Some(1), Some(2)]
+        );
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn test_merge_indicator() {
+        let table = |df: DataFrame, name: &str| Table {
+            lf: df.clone().lazy(),
+            header: df.clear(),
+            name: name.to_string(),
+            on: vec!["id".to_string()],
+            keys: vec!["id".to_string()],
+        };
+        let mut tables = [
+            table(df! { "id" => [1, 2] }.unwrap(), "users"),
+            table(df! { "id" => [2, 3] }.unwrap(), "orders"),
+        ];
+        mark_rows(&mut tables).unwrap();
+
+        let joined = tables[0]
+            .join(&tables[1], JoinType::Outer, true, false)
+            .unwrap();
+        let df = joined
+            .lf
+            .select([col("id"), merge_indicator()])
+            .sort(["id"], SortMultipleOptions::default())
+            .collect()
+            .unwrap();
+        let merge: Vec<_> = df
+            .column(MERGE)
+            .unwrap()
+            .str()
+            .unwrap()
+            .into_no_null_iter()
+            .collect();
+        assert_eq!(merge, ["left_only", "both", "right_only"]);
+
+        let mut tables = [table(df! { "_merge" => [1] }.unwrap(), "users")];
+        assert!(mark_rows(&mut tables).is_err());
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn test_check_labels() {
+        let labels = vec!["users".to_string(), "orders".to_string()];
+        let on = parse_on_strings(&["usres.id=orders.user_id".to_string()]);
+
+        assert_eq!(
+            check_labels(&on, &labels, false).unwrap_err().to_string(),
+            "--on references unknown table label 'usres', did you mean 'users'?\n  tables are labeled: users, orders"
+        );
+
+        let on = parse_on_strings(&["users.id=orders.user_id".to_string()]);
+        assert!(check_labels(&on, &labels, false).is_ok());
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn test_reconcile_keys() {
+        let table = |name: &str, df: DataFrame| Table {
+            header: df.clear(),
+            lf: df.lazy(),
+            name: name.to_string(),
+            on: vec!["id".to_string()],
+            keys: vec!["id".to_string()],
+        };
+        let ids = |table: Table| -> Vec<Option<String>> {
+            let df = table.lf.collect().unwrap();
+            let ids = df.column("id").unwrap().cast(&DataType::String).unwrap();
+            ids.str()
+                .unwrap()
+                .into_iter()
+                .map(|v| v.map(str::to_string))
+                .collect()
+        };
+
+        let mut left = table("T1", df! { "id" => [1i64, 2] }.unwrap());
+        let mut right = table("T2", df! { "id" => ["2", "03"] }.unwrap());
+        assert!(left.reconcile_keys(&mut right, false).is_err());
+        left.reconcile_keys(&mut right, true).unwrap();
+        assert_eq!(right.header.column("id").unwrap().dtype(), &DataType::Int64);
+        assert_eq!(ids(right), [Some("2".to_string()), Some("3".to_string())]);
+
+        // Unparseable text keeps the keys as text rather than losing values
+        let mut left = table("T1", df! { "id" => [1i64, 2] }.unwrap());
+        let mut right = table("T2", df! { "id" => ["2", "x3"] }.unwrap());
+        left.reconcile_keys(&mut right, true).unwrap();
+        assert_eq!(left.header.column("id").unwrap().dtype(), &DataType::String);
+        assert_eq!(ids(right), [Some("2".to_string()), Some("x3".to_string())]);
+
+        let mut left = table("T1", df! { "id" => [1i32] }.unwrap());
+        let mut right = table("T2", df! { "id" => [1.5f64] }.unwrap());
+        left.reconcile_keys(&mut right, true).unwrap();
+        assert_eq!(
+            left.header.column("id").unwrap().dtype(),
+            &DataType::Float64
+        );
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn test_diagnose() {
+        let table = |name: &str, df: DataFrame| Table {
+            header: df.clear(),
+            lf: df.lazy(),
+            name: name.to_string(),
+            on: vec!["id".to_string()],
+            keys: vec!["id".to_string()],
+        };
+        let orders = table(
+            "orders",
+            df! { "id" => [Some(1), Some(1), Some(2), Some(4), Some(4), None] }.unwrap(),
+        );
+        let users = table("users", df! { "id" => [1, 2, 3] }.unwrap());
+
+        let report = orders.diagnose(&users).unwrap();
+        assert_eq!(report.get_column_names_str(), ["metric", "orders", "users"]);
+        let values = |name: &str| -> Vec<String> {
+            report
+                .column(name)
+                .unwrap()
+                .str()
+                .unwrap()
+                .into_iter()
+                .map(|v| v.unwrap().to_string())
+                .collect()
+        };
+        assert_eq!(
+            values("orders"),
+            [
+                "6",
+                "1",
+                "3",
+                "2",
+                "3 (50.0%)",
+                "3 (50.0%)",
+                "many",
+                "4 (2)"
+            ]
+        );
+        assert_eq!(
+            values("users"),
+            ["3", "0", "3", "2", "2 (66.7%)", "1 (33.3%)", "one", "3 (1)"]
+        );
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn test_check_columns() {
+        let df = df! { "user_id" => [1], "Name" => ["Alice"] }.unwrap();
+
+        assert!(check_columns(&df, &["user_id".to_string()], "T1", "a.csv").is_ok());
+        assert_eq!(
+            check_columns(&df, &["usr_id".to_string()], "T1", "a.csv")
+                .unwrap_err()
+                .to_string(),
+            "Column 'usr_id' not found in table 'T1' (a.csv)\n  did you mean 'user_id'?\n  available columns: user_id, Name"
+        );
+        assert!(
+            check_columns(&df, &["name".to_string()], "T1", "a.csv")
+                .unwrap_err()
+                .to_string()
+                .contains("--ignore-column-case")
+        );
+        assert_eq!(
+            check_columns(
+                &df,
+                &["usr_id".to_string(), "email".to_string()],
+                "T1",
+                "a.csv"
+            )
+            .unwrap_err()
+            .to_string(),
+            "Column 'usr_id' not found in table 'T1' (a.csv)\n  did you mean 'user_id'?\nColumn 'email' not found in table 'T1' (a.csv)\n  available columns: user_id, Name"
+        );
     }
 
     #[test]
@@ -333,14 +1841,185 @@ mod tests {
         assert_eq!(result["T3"], vec!["col13", "col23"]);
     }
 
+    #[test]
+    fn test_parse_on_strings_unlabeled_pairs() {
+        let column_strings = vec!["region".to_string(), "id=user_id".to_string()];
+
+        let result = parse_on_strings(&column_strings);
+
+        assert_eq!(result["*"], vec!["region"]);
+        assert_eq!(result[&position_key(0)], vec!["id"]);
+        assert_eq!(result[&position_key(1)], vec!["user_id"]);
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn test_create_tables_with_unlabeled_pairs() {
+        let mut users_file = NamedTempFile::new().unwrap();
+        writeln!(users_file, "id,name").unwrap();
+        writeln!(users_file, "1,Alice").unwrap();
+
+        let mut orders_file = NamedTempFile::new().unwrap();
+        writeln!(orders_file, "order_id,user_id").unwrap();
+        writeln!(orders_file, "101,1").unwrap();
+
+        let tables = vec![
+            users_file.path().to_string_lossy().to_string(),
+            orders_file.path().to_string_lossy().to_string(),
+        ];
+        let on = parse_on_strings(&["id=user_id".to_string()]);
+
+        let result = create_tables(
+            &tables,
+            &[],
+            &on,
+            &vec![','; tables.len()],
+            &GlobalArgs::default(),
+        )
+        .unwrap();
+
+        assert_eq!(result[0].on, vec!["id"]);
+        assert_eq!(result[1].on, vec!["user_id"]);
+        assert_eq!(
+            result[0]
+                .join(&result[1], JoinType::Inner, false, false)
+                .unwrap()
+                .lf
+                .collect()
+                .unwrap()
+                .height(),
+            1
+        );
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn test_select_columns() {
+        let mut users_file = NamedTempFile::new().unwrap();
+        writeln!(users_file, "id,name,email").unwrap();
+        writeln!(users_file, "1,Alice,alice@example.com").unwrap();
+
+        let mut orders_file = NamedTempFile::new().unwrap();
+        writeln!(orders_file, "id,user_id,amount").unwrap();
+        writeln!(orders_file, "101,1,9.5").unwrap();
+
+        let tables = vec![
+            users_file.path().to_string_lossy().to_string(),
+            orders_file.path().to_string_lossy().to_string(),
+        ];
+        let labels = vec!["users".to_string(), "orders".to_string()];
+        let on = parse_on_strings(&["users.id=orders.user_id".to_string()]);
+        let mut tables = create_tables(
+            &tables,
+            &labels,
+            &on,
+            &vec![','; tables.len()],
+            &GlobalArgs::default(),
+        )
+        .unwrap();
+
+        let error = select_columns(&mut tables, &["nmae".to_string()], false)
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("did you mean 'name'?"));
+
+        let select = ["orders.id", "users.id", "name", "amount"].map(String::from);
+        let selected = select_columns(&mut tables, &select, false).unwrap();
+        let names: Vec<&str> = selected.iter().map(|(_, name)| name.as_str()).collect();
+        assert_eq!(names, ["orders.id", "users.id", "name", "amount"]);
+        // Unselected columns are pruned before joining, join keys are kept
+        let users = tables[0].lf.collect_schema().unwrap();
+        let orders = tables[1].lf.collect_schema().unwrap();
+        assert!(users.get("email").is_none());
+        assert!(orders.get("user_id").is_some());
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn test_join_args_validate_unlabeled_pair_count() {
+        let args = JoinArgs {
+            tables: vec![
+                "table1.csv".to_string(),
+                "table2.csv".to_string(),
+                "table3.csv".to_string(),
+            ],
+            r#as: vec![],
+            on: vec!["id=user_id".to_string()],
+            r#type: vec![JoinType::Inner],
+            select: vec![],
+            coalesce: false,
+            coerce_keys: false,
+            delimiter: ",".to_string(),
+            dry_run: false,
+            fuzzy: None,
+            max_distance: 2,
+            min_similarity: 0.9,
+            indicator: false,
+            diagnose: false,
+        };
+
+        assert_eq!(
+            args.validate().unwrap_err().to_string(),
+            "--on 'id=user_id' names 2 columns but 3 tables were given"
+        );
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn test_join_args_validate_per_pair_types() {
+        let mut args = JoinArgs {
+            tables: vec![
+                "table1.csv".to_string(),
+                "table2.csv".to_string(),
+                "table3.csv".to_string(),
+            ],
+            r#as: vec![],
+            on: vec!["id".to_string()],
+            r#type: vec![JoinType::Inner, JoinType::Left],
+            select: vec![],
+            coalesce: false,
+            coerce_keys: false,
+            delimiter: ",".to_string(),
+            dry_run: false,
+            fuzzy: None,
+            max_distance: 2,
+            min_similarity: 0.9,
+            indicator: false,
+            diagnose: false,
+        };
+        assert!(args.validate().is_ok());
+        assert_eq!(args.join_type(0), JoinType::Inner);
+        assert_eq!(args.join_type(1), JoinType::Left);
+
+        args.r#type = vec![JoinType::Outer];
+        assert_eq!(args.join_type(1), JoinType::Outer);
+
+        args.r#type = vec![JoinType::Inner, JoinType::Left, JoinType::Left];
+        assert!(
+            args.validate()
+                .unwrap_err()
+                .to_string()
+                .starts_with("--type lists 3 join types but 3 tables make 2 joins")
+        );
+    }
+
     #[test]
     fn test_join_args_validate_success() {
         let args = JoinArgs {
             tables: vec!["table1.csv".to_string(), "table2.csv".to_string()],
             r#as: vec!["T1".to_string(), "T2".to_string()],
             on: vec!["id".to_string()],
-            r#type: JoinType::Inner,
-            delimiter: ',',
+            r#type: vec![JoinType::Inner],
+            select: vec![],
+            coalesce: false,
+            coerce_keys: false,
+            delimiter: ",".to_string(),
+            dry_run: false,
+            fuzzy: None,
+            max_distance: 2,
+            min_similarity: 0.9,
+            indicator: false,
+            diagnose: false,
         };
 
         assert!(args.validate().is_ok());
@@ -353,8 +2032,17 @@ mod tests {
             tables: vec!["table1.csv".to_string()],
             r#as: vec![],
             on: vec!["id".to_string()],
-            r#type: JoinType::Inner,
-            delimiter: ',',
+            r#type: vec![JoinType::Inner],
+            select: vec![],
+            coalesce: false,
+            coerce_keys: false,
+            delimiter: ",".to_string(),
+            dry_run: false,
+            fuzzy: None,
+            max_distance: 2,
+            min_similarity: 0.9,
+            indicator: false,
+            diagnose: false,
         };
 
         let result = args.validate();
@@ -372,8 +2060,17 @@ mod tests {
             tables: vec!["table1.csv".to_string(), "table2.csv".to_string()],
             r#as: vec!["T1".to_string()], // Only one name for two tables
             on: vec!["id".to_string()],
-            r#type: JoinType::Inner,
-            delimiter: ',',
+            r#type: vec![JoinType::Inner],
+            select: vec![],
+            coalesce: false,
+            coerce_keys: false,
+            delimiter: ",".to_string(),
+            dry_run: false,
+            fuzzy: None,
+            max_distance: 2,
+            min_similarity: 0.9,
+            indicator: false,
+            diagnose: false,
         };
 
         let result = args.validate();
@@ -391,8 +2088,17 @@ mod tests {
             tables: vec!["table1.csv".to_string(), "table2.csv".to_string()],
             r#as: vec![],
             on: vec![], // No join columns specified
-            r#type: JoinType::Inner,
-            delimiter: ',',
+            r#type: vec![JoinType::Inner],
+            select: vec![],
+            coalesce: false,
+            coerce_keys: false,
+            delimiter: ",".to_string(),
+            dry_run: false,
+            fuzzy: None,
+            max_distance: 2,
+            min_similarity: 0.9,
+            indicator: false,
+            diagnose: false,
         };
 
         let result = args.validate();
@@ -402,4 +2108,227 @@ mod tests {
             "At least one column to join on is required"
         );
     }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn test_join_args_validate_fuzzy() {
+        let mut args = JoinArgs {
+            tables: vec!["crm.csv".to_string(), "customers.csv".to_string()],
+            r#as: vec![],
+            on: vec!["contact=customer_name".to_string()],
+            r#type: vec![JoinType::Left],
+            select: vec![],
+            coalesce: false,
+            coerce_keys: false,
+            delimiter: ",".to_string(),
+            dry_run: false,
+            fuzzy: Some(FuzzyMetric::JaroWinkler),
+            max_distance: 2,
+            min_similarity: 0.9,
+            indicator: false,
+            diagnose: false,
+        };
+        assert!(args.validate().is_ok());
+
+        args.r#type = vec![JoinType::Outer];
+        assert_eq!(
+            args.validate().unwrap_err().to_string(),
+            "--fuzzy supports inner and left joins"
+        );
+
+        args.r#type = vec![JoinType::Inner];
+        args.min_similarity = 1.5;
+        assert!(args.validate().is_err());
+
+        args.min_similarity = 0.9;
+        args.on.push("region".to_string());
+        assert!(args.validate().is_err());
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn test_fuzzy_join() {
+        let mut on = HashMap::new();
+        on.insert("#1".to_string(), vec!["contact".to_string()]);
+        on.insert("#2".to_string(), vec!["customer_name".to_string()]);
+        let tables = create_tables(
+            &[
+                "data/orders/crm.csv".to_string(),
+                "data/orders/customers.csv".to_string(),
+            ],
+            &[],
+            &on,
+            &[',', ','],
+            &GlobalArgs::default(),
+        )
+        .unwrap();
+        let matcher = Matcher {
+            metric: FuzzyMetric::Levenshtein,
+            max_distance: 2,
+            min_similarity: 0.9,
+        };
+
+        let joined = tables[0]
+            .fuzzy_join(&tables[1], JoinType::Inner, &matcher, false, false)
+            .unwrap();
+        assert_eq!(joined.keys, ["contact", "customer_name", "match_score"]);
+        let df = joined
+            .lf
+            .select(keys_first(&joined.header, &joined.keys))
+            .collect()
+            .unwrap();
+        let names: Vec<_> = df
+            .column("customer_name")
+            .unwrap()
+            .str()
+            .unwrap()
+            .into_no_null_iter()
+            .collect();
+        assert_eq!(
+            names,
+            ["John Doe", "Jane Smith", "Michael Johnson", "Emily Davis"]
+        );
+        let scores: Vec<_> = df
+            .column("match_score")
+            .unwrap()
+            .u32()
+            .unwrap()
+            .into_no_null_iter()
+            .collect();
+        assert_eq!(scores, [1, 0, 1, 1]);
+
+        let joined = tables[0]
+            .fuzzy_join(&tables[1], JoinType::Left, &matcher, false, false)
+            .unwrap();
+        assert_eq!(joined.lf.collect().unwrap().height(), 6);
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn test_parse_condition() {
+        let labels = vec!["events".to_string(), "sessions".to_string()];
+        let condition = |left: &str, op, right: &str| Condition {
+            left: left.to_string(),
+            op,
+            right: right.to_string(),
+        };
+
+        assert_eq!(
+            Condition::parse("events.ts>=sessions.start", &labels).unwrap(),
+            condition("ts", Comparison::GtEq, "start")
+        );
+        assert_eq!(
+            Condition::parse("ts < end", &labels).unwrap(),
+            condition("ts", Comparison::Lt, "end")
+        );
+        // The second table's column comes first, so the comparison flips
+        assert_eq!(
+            Condition::parse("sessions.end>events.ts", &labels).unwrap(),
+            condition("ts", Comparison::Lt, "end")
+        );
+
+        assert!(Condition::parse("ts=>start", &labels).is_err());
+        assert!(Condition::parse("events.ts<events.end", &labels).is_err());
+        assert_eq!(
+            Condition::parse("event.ts<sessions.end", &labels)
+                .unwrap_err()
+                .to_string(),
+            "--on references unknown table label 'event', did you mean 'events'?"
+        );
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn test_join_args_validate_conditions() {
+        let mut args = JoinArgs {
+            tables: vec!["access.csv".to_string(), "sessions.csv".to_string()],
+            r#as: vec!["access".to_string(), "sessions".to_string()],
+            on: vec![
+                "access.timestamp>=sessions.start".to_string(),
+                "access.timestamp<sessions.end".to_string(),
+            ],
+            r#type: vec![JoinType::Inner],
+            select: vec![],
+            coalesce: false,
+            coerce_keys: false,
+            delimiter: ",".to_string(),
+            dry_run: false,
+            fuzzy: None,
+            max_distance: 2,
+            min_similarity: 0.9,
+            indicator: false,
+            diagnose: false,
+        };
+        assert!(args.validate().is_ok());
+        assert_eq!(args.equalities(), Vec::<String>::new());
+        assert_eq!(args.conditions().unwrap().len(), 2);
+
+        args.r#type = vec![JoinType::Left];
+        assert!(args.validate().is_err());
+
+        args.r#type = vec![JoinType::Inner];
+        args.tables.push("users.csv".to_string());
+        args.r#as.push("users".to_string());
+        assert!(args.validate().is_err());
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn test_range_join() {
+        let mut on = HashMap::new();
+        on.insert("#1".to_string(), vec!["timestamp".to_string()]);
+        on.insert(
+            "#2".to_string(),
+            vec!["start".to_string(), "end".to_string()],
+        );
+        let tables = create_tables(
+            &[
+                "data/web/access.csv".to_string(),
+                "data/web/sessions.csv".to_string(),
+            ],
+            &[],
+            &on,
+            &[',', ','],
+            &GlobalArgs::default(),
+        )
+        .unwrap();
+        let conditions = [
+            Condition {
+                left: "timestamp".to_string(),
+                op: Comparison::GtEq,
+                right: "start".to_string(),
+            },
+            Condition {
+                left: "timestamp".to_string(),
+                op: Comparison::Lt,
+                right: "end".to_string(),
+            },
+        ];
+
+        let joined = tables[0]
+            .range_join(&tables[1], &[vec![], vec![]], &conditions, false)
+            .unwrap();
+        let df = joined.lf.collect().unwrap();
+        let sessions: Vec<_> = df
+            .column("session")
+            .unwrap()
+            .str()
+            .unwrap()
+            .into_no_null_iter()
+            .collect();
+        assert_eq!(sessions, ["s1", "s1", "s2"]);
+
+        // Columns of the right table named like left ones are suffixed
+        let earlier = Condition {
+            left: "timestamp".to_string(),
+            op: Comparison::Lt,
+            right: "timestamp".to_string(),
+        };
+        let joined = tables[0]
+            .range_join(&tables[0], &[vec![], vec![]], &[earlier], false)
+            .unwrap();
+        assert!(joined.header.get_column_index("timestamp_right").is_some());
+        // Each of the 4 requests pairs with every later one
+        assert_eq!(joined.lf.collect().unwrap().height(), 6);
+    }
 }
@@ -0,0 +1,192 @@
+// Find the columns, or small combinations of columns, which uniquely identify the rows of
+// a table.
+use anyhow::{Context, Result, ensure};
+use clap::{Args, ValueHint};
+use itertools::Itertools;
+use log::warn;
+use polars::prelude::*;
+
+use crate::args::GlobalArgs;
+use crate::io::{read_data, resolve_columns, write_data};
+
+#[derive(Args, Debug)]
+pub struct KeysArgs {
+    /// Input table (file or '-' for stdin)
+    #[arg(required = true, value_hint = ValueHint::FilePath)]
+    pub table: String,
+
+    /// Columns to consider as parts of a key (comma separated) [default: every column
+    /// except floats, lists and structs]
+    ///
+    /// Examples: --columns "date,ticker"
+    #[arg(long, value_delimiter = ',')]
+    pub columns: Vec<String>,
+
+    /// Largest number of columns to combine into a key
+    #[arg(long, default_value = "2")]
+    pub max_columns: usize,
+
+    /// Delimiter for input files
+    #[arg(long, default_value = ",")]
+    pub delimiter: char,
+}
+
+impl KeysArgs {
+    pub fn validate(&self) -> Result<()> {
+        ensure!(self.max_columns >= 1, "--max-columns must be at least 1");
+        ensure!(
+            self.columns.iter().all_unique(),
+            "--columns can't repeat a column"
+        );
+
+        Ok(())
+    }
+
+    pub fn execute(&self, global: &GlobalArgs) -> Result<()> {
+        let data =
+            read_data(&self.table, Some(self.delimiter), global).with_context(|| {
+                format!("keys - failed to read csv data from {}", self.table)
+            })?;
+        let columns = if self.columns.is_empty() {
+            data.get_columns()
+                .iter()
+                .filter(|c| is_candidate(c.dtype()))
+                .map(|c| c.name().to_string())
+                .collect()
+        } else {
+            resolve_columns(&data, &self.columns, global.ignore_column_case)?
+        };
+
+        let keys = candidate_keys(&data, &columns, self.max_columns)
+            .with_context(|| format!("keys - failed to find the keys of {}", self.table))?;
+        if keys.is_empty() {
+            warn!(
+                "no combination of up to {} column(s) of {} identifies its rows",
+                self.max_columns, self.table
+            );
+        }
+
+        let result = df!(
+            "key" => keys.iter().map(|key| key.join(",")).collect::<Vec<_>>(),
+            "columns" => keys.iter().map(|key| key.len() as u64).collect::<Vec<_>>(),
+        )?;
+
+        write_data(result, global)
+            .with_context(|| "keys - failed to write data to stdout".to_string())?;
+
+        Ok(())
+    }
+}
+
+/// Whether a column of `dtype` is worth trying as part of a key, leaving out floats, whose
+/// values rarely match exactly, and nested values
+fn is_candidate(dtype: &DataType) -> bool {
+    dtype.is_integer()
+        || dtype.is_temporal()
+        || matches!(dtype, DataType::String | DataType::Boolean)
+}
+
+/// The minimal combinations of up to `max` of `columns` with a different, non-null
+/// combination of values in every row, smallest first
+///
+/// Combinations holding a smaller key are left out, as are columns with nulls, which never
+/// match when joining.
+fn candidate_keys(
+    df: &DataFrame,
+    columns: &[String],
+    max: usize,
+) -> Result<Vec<Vec<String>>> {
+    let mut keys: Vec<Vec<String>> = vec![];
+    if df.height() == 0 {
+        return Ok(keys);
+    }
+
+    let mut complete = vec![];
+    for name in columns {
+        if df.column(name)?.null_count() == 0 {
+            complete.push(name.clone());
+        }
+    }
+
+    for size in 1..=max.min(complete.len()) {
+        for combination in complete.iter().cloned().combinations(size) {
+            if keys
+                .iter()
+                .any(|key| key.iter().all(|c| combination.contains(c)))
+            {
+                continue;
+            }
+            let by: Vec<Expr> = combination.iter().map(|c| col(c.as_str())).collect();
+            let groups = df
+                .clone()
+                .lazy()
+                .group_by(by)
+                .agg([len()])
+                .collect()?
+                .height();
+            if groups == df.height() {
+                keys.push(combination);
+            }
+        }
+    }
+
+    Ok(keys)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn names(columns: &[&str]) -> Vec<String> {
+        columns.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn test_candidate_keys() {
+        let df = df!(
+            "id" => [1, 2, 3, 4],
+            "date" => ["d1", "d1", "d2", "d2"],
+            "ticker" => ["A", "B", "A", "B"],
+            "note" => [Some("x"), None, Some("y"), Some("z")],
+        )
+        .unwrap();
+        let columns = names(&["id", "date", "ticker", "note"]);
+
+        let keys = candidate_keys(&df, &columns, 2).unwrap();
+        assert_eq!(keys, [names(&["id"]), names(&["date", "ticker"])]);
+
+        let keys = candidate_keys(&df, &columns, 1).unwrap();
+        assert_eq!(keys, [names(&["id"])]);
+
+        let keys = candidate_keys(&df.head(Some(0)), &columns, 2).unwrap();
+        assert!(keys.is_empty());
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut args = KeysArgs {
+            table: "data/stocks/prices.csv".to_string(),
+            columns: vec![],
+            max_columns: 0,
+            delimiter: ',',
+        };
+        assert!(args.validate().is_err());
+        args.max_columns = 3;
+        assert!(args.validate().is_ok());
+        args.columns = names(&["date", "date"]);
+        assert!(args.validate().is_err());
+    }
+
+    #[test]
+    fn test_keys_prices_csv() {
+        let args = KeysArgs {
+            table: "data/stocks/prices.csv".to_string(),
+            columns: vec![],
+            max_columns: 2,
+            delimiter: ',',
+        };
+        assert!(args.validate().is_ok());
+        assert!(args.execute(&GlobalArgs::default()).is_ok());
+    }
+}
@@ -1,26 +1,78 @@
-use anyhow::Result;
 use clap::Parser;
+use log::info;
+use std::process::ExitCode;
+use std::time::Instant;
 
 mod aggregate;
+mod anonymize;
 mod args;
+mod bench;
+mod bin;
 mod cat;
+mod clean;
+mod clipboard;
+mod complete;
+mod concat;
+mod corr;
+mod crosstab;
+mod database;
+mod dates;
+mod ddl;
+mod demo;
+mod derive;
+mod diff;
+mod exit;
+mod explode;
+mod extract;
+mod freq;
+mod fuzzy;
+mod grep;
 mod head;
+mod hist;
 mod io;
 mod join;
+mod keys;
+mod partition;
+mod profile;
 mod query;
+mod rank;
+mod rolling;
+mod shift;
+mod split;
+mod sqlite;
+mod suggest_types;
 mod tail;
+mod timings;
+mod top;
+mod unpack;
+mod validate;
+mod window;
 
 use args::Args;
-use io::config;
+use exit::exit_code;
+use io::{config, init_logging};
 
 /// The main entry point that parses CLI arguments and runs the join operation
-fn main() -> Result<()> {
+fn main() -> ExitCode {
     // Parse command line arguments
     let args = Args::parse();
+    init_logging(&args.global);
 
     // Configure Polars table formatting based on terminal dimensions and format option
-    config(&args.format);
-    args.run()?;
+    config(&args.global);
+    if let Some(limit) = args.global.memory_limit {
+        timings::limit_memory(limit);
+    }
+    let start = Instant::now();
+    let result = args.run();
+    if args.global.timings {
+        timings::report(start.elapsed());
+    }
+    if let Err(error) = result {
+        eprintln!("Error: {error:?}");
+        return exit_code(&error);
+    }
+    info!("finished in {:.2?}", start.elapsed());
 
-    Ok(())
+    ExitCode::SUCCESS
 }
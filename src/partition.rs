@@ -0,0 +1,270 @@
+// Randomly divide the rows of a table into files by ratio, e.g. train and test sets.
+use anyhow::{Context, Result, ensure};
+use clap::{Args, ValueHint};
+use itertools::Itertools;
+use polars::prelude::*;
+use std::fs;
+use std::path::Path;
+
+use crate::args::GlobalArgs;
+use crate::io::{read_data, resolve_columns, write_csv, write_data};
+
+/// Column numbering the input rows while they're divided
+const ROW_INDEX: &str = "__rabbet_row";
+
+#[derive(Args, Debug)]
+pub struct PartitionArgs {
+    /// Input table (file or '-' for stdin)
+    #[arg(required = true, value_hint = ValueHint::FilePath)]
+    pub table: String,
+
+    /// Relative sizes of the parts (comma separated)
+    ///
+    /// Examples: --ratios 0.8,0.2 or --ratios 70,15,15
+    #[arg(long, required = true, value_delimiter = ',')]
+    pub ratios: Vec<f64>,
+
+    /// Names of the parts, one per ratio, which name their files [default: part_<n>]
+    ///
+    /// Examples: --names train,test or --names "train,validation,test"
+    #[arg(long, value_delimiter = ',')]
+    pub names: Vec<String>,
+
+    /// Divide the rows with each combination of values in these columns by the ratios
+    /// separately, so every part has the same mix of them (comma separated)
+    ///
+    /// Examples: --stratify-by label
+    #[arg(long, value_delimiter = ',')]
+    pub stratify_by: Vec<String>,
+
+    /// Directory to write the files to (created if it doesn't exist)
+    #[arg(long, default_value = ".", value_hint = ValueHint::DirPath)]
+    pub output_dir: String,
+
+    /// Delimiter for input files
+    #[arg(long, default_value = ",")]
+    pub delimiter: char,
+}
+
+impl PartitionArgs {
+    pub fn validate(&self) -> Result<()> {
+        ensure!(
+            self.ratios.iter().all(|r| r.is_finite() && *r > 0.0),
+            "--ratios must all be positive numbers"
+        );
+        if !self.names.is_empty() {
+            ensure!(
+                self.names.len() == self.ratios.len(),
+                "--names needs one name per ratio, got {} name(s) for {} ratio(s)",
+                self.names.len(),
+                self.ratios.len()
+            );
+        }
+        ensure!(
+            self.names().iter().all_unique(),
+            "--names can't repeat a name, as each part gets its own file"
+        );
+
+        Ok(())
+    }
+
+    pub fn execute(&self, global: &GlobalArgs) -> Result<()> {
+        let df =
+            read_data(&self.table, Some(self.delimiter), global).with_context(|| {
+                format!("partition - failed to read csv data from {}", self.table)
+            })?;
+
+        let stratify_by =
+            resolve_columns(&df, &self.stratify_by, global.ignore_column_case)?;
+        let parts = assign_parts(&df, &stratify_by, &self.ratios, global.seed())
+            .with_context(|| format!("partition - failed to divide {}", self.table))?;
+
+        fs::create_dir_all(&self.output_dir).with_context(|| {
+            format!("partition - failed to create directory {}", self.output_dir)
+        })?;
+
+        let names = self.names();
+        let mut files = Vec::with_capacity(names.len());
+        let mut rows = Vec::with_capacity(names.len());
+        for (idx, name) in names.iter().enumerate() {
+            let mask: BooleanChunked = parts.iter().map(|part| *part == idx).collect();
+            let mut part = df.filter(&mask)?;
+
+            let path = Path::new(&self.output_dir).join(format!("{name}.csv"));
+            let mut buffer = Vec::new();
+            write_csv(&mut part, &mut buffer, global)?;
+            fs::write(&path, buffer).with_context(|| {
+                format!("partition - failed to write {}", path.display())
+            })?;
+
+            files.push(path.display().to_string());
+            rows.push(part.height() as u64);
+        }
+
+        write_data(df!("file" => files, "rows" => rows)?, global)
+            .with_context(|| "partition - failed to write data to stdout".to_string())?;
+
+        Ok(())
+    }
+
+    /// The `--names`, or `part_1`, `part_2`, ... for each ratio
+    fn names(&self) -> Vec<String> {
+        if self.names.is_empty() {
+            return (1..=self.ratios.len())
+                .map(|n| format!("part_{n}"))
+                .collect();
+        }
+
+        self.names.clone()
+    }
+}
+
+/// The part each row of `df` goes to, drawn at random for the rows of each combination of
+/// `stratify_by` values (or all rows without any) with the part sizes following `ratios`
+///
+/// The draws are seeded with `seed` when given, so they repeat from run to run.
+fn assign_parts(
+    df: &DataFrame,
+    stratify_by: &[String],
+    ratios: &[f64],
+    seed: Option<u64>,
+) -> Result<Vec<usize>> {
+    if stratify_by.is_empty() {
+        return Ok(shuffled_parts(df.height(), ratios, seed)?
+            .into_iter()
+            .map(|part| part as usize)
+            .collect());
+    }
+
+    let groups = df
+        .select(stratify_by.iter().map(String::as_str))?
+        .with_row_index(ROW_INDEX.into(), None)?
+        .partition_by_stable(stratify_by, true)?;
+    let mut parts = vec![0; df.height()];
+    for (idx, group) in (0_u64..).zip(&groups) {
+        // Each group draws from its own seed, so groups of the same size aren't divided alike
+        let seed = seed.map(|seed| seed.wrapping_add(idx));
+        let drawn = shuffled_parts(group.height(), ratios, seed)?;
+        for (row, part) in group
+            .column(ROW_INDEX)?
+            .idx()?
+            .into_no_null_iter()
+            .zip(drawn)
+        {
+            parts[row as usize] = part as usize;
+        }
+    }
+
+    Ok(parts)
+}
+
+/// The parts of `rows` rows sized by `ratios` (see `part_sizes`), in a random order
+fn shuffled_parts(rows: usize, ratios: &[f64], seed: Option<u64>) -> Result<Vec<u32>> {
+    let parts: Vec<u32> = part_sizes(rows, ratios)
+        .into_iter()
+        .zip(0_u32..)
+        .flat_map(|(size, part)| std::iter::repeat_n(part, size))
+        .collect();
+    let shuffled = Series::new("part".into(), parts).shuffle(seed);
+
+    Ok(shuffled.u32()?.into_no_null_iter().collect())
+}
+
+/// Divides `rows` into parts in proportion to `ratios`, giving the rows left over from
+/// rounding down to the parts with the largest remainders
+#[allow(
+    clippy::cast_precision_loss,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss
+)]
+fn part_sizes(rows: usize, ratios: &[f64]) -> Vec<usize> {
+    let total: f64 = ratios.iter().sum();
+    let exact: Vec<f64> = ratios
+        .iter()
+        .map(|ratio| rows as f64 * ratio / total)
+        .collect();
+    let mut sizes: Vec<usize> = exact.iter().map(|size| size.floor() as usize).collect();
+
+    let left = rows - sizes.iter().sum::<usize>();
+    let by_remainder = (0..sizes.len()).sorted_by(|a, b| {
+        (exact[*b] - exact[*b].floor()).total_cmp(&(exact[*a] - exact[*a].floor()))
+    });
+    for idx in by_remainder.take(left) {
+        sizes[idx] += 1;
+    }
+
+    sizes
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn args(ratios: &[f64], names: &[&str]) -> PartitionArgs {
+        PartitionArgs {
+            table: "data/orders/orders.csv".to_string(),
+            ratios: ratios.to_vec(),
+            names: names.iter().map(ToString::to_string).collect(),
+            stratify_by: vec![],
+            output_dir: ".".to_string(),
+            delimiter: ',',
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(args(&[0.8, 0.2], &["train", "test"]).validate().is_ok());
+        assert!(args(&[70.0, 15.0, 15.0], &[]).validate().is_ok());
+        assert!(args(&[0.8, 0.2], &["train"]).validate().is_err());
+        assert!(args(&[0.8, 0.2], &["train", "train"]).validate().is_err());
+        assert!(args(&[0.8, 0.0], &[]).validate().is_err());
+        assert_eq!(args(&[1.0, 1.0], &[]).names(), ["part_1", "part_2"]);
+    }
+
+    #[test]
+    fn test_part_sizes() {
+        assert_eq!(part_sizes(10, &[0.8, 0.2]), [8, 2]);
+        assert_eq!(part_sizes(6, &[70.0, 15.0, 15.0]), [4, 1, 1]);
+        assert_eq!(part_sizes(3, &[1.0, 1.0]), [2, 1]);
+        assert_eq!(part_sizes(0, &[0.5, 0.5]), [0, 0]);
+    }
+
+    #[test]
+    fn test_assign_parts() {
+        let df = df!(
+            "id" => (0..100).collect::<Vec<i32>>(),
+            "label" => (0..100).map(|i| if i < 20 { "a" } else { "b" }).collect::<Vec<_>>(),
+        )
+        .unwrap();
+
+        let parts = assign_parts(&df, &[], &[0.8, 0.2], Some(1)).unwrap();
+        assert_eq!(parts.iter().filter(|p| **p == 1).count(), 20);
+        assert_eq!(assign_parts(&df, &[], &[0.8, 0.2], Some(1)).unwrap(), parts);
+
+        let stratified =
+            assign_parts(&df, &["label".to_string()], &[0.5, 0.5], Some(1)).unwrap();
+        assert_eq!(stratified[..20].iter().filter(|p| **p == 1).count(), 10);
+        assert_eq!(stratified[20..].iter().filter(|p| **p == 1).count(), 40);
+    }
+
+    #[test]
+    fn test_partition_orders_csv() {
+        let dir = tempdir().unwrap();
+        let mut args = args(&[0.5, 0.5], &["train", "test"]);
+        args.output_dir = dir.path().to_string_lossy().to_string();
+        let global = GlobalArgs {
+            seed: Some(3),
+            ..GlobalArgs::default()
+        };
+
+        assert!(args.validate().is_ok());
+        assert!(args.execute(&global).is_ok());
+
+        let train = fs::read_to_string(dir.path().join("train.csv")).unwrap();
+        let test = fs::read_to_string(dir.path().join("test.csv")).unwrap();
+        assert_eq!(train.lines().count(), 4);
+        assert_eq!(test.lines().count(), 4);
+    }
+}
@@ -0,0 +1,315 @@
+// Profile the columns of a table: nulls, distinct values, ranges, lengths, common values
+// and candidate keys.
+use anyhow::{Context, Result};
+use clap::{Args, ValueHint};
+use itertools::Itertools;
+use polars::prelude::*;
+use serde_json::{Value, json};
+
+use crate::args::GlobalArgs;
+use crate::io::{read_data, resolve_columns, write_data, write_text};
+
+#[derive(Args, Debug)]
+pub struct ProfileArgs {
+    /// Input table (file or '-' for stdin)
+    #[arg(required = true, value_hint = ValueHint::FilePath)]
+    pub table: String,
+
+    /// Columns to profile (comma separated) [default: every column]
+    ///
+    /// Examples: --columns "age,plan"
+    #[arg(long, value_delimiter = ',')]
+    pub columns: Vec<String>,
+
+    /// Number of most common values to list per column (0 to leave them out)
+    #[arg(long, default_value = "5")]
+    pub top: usize,
+
+    /// Print the profile as a JSON document, for other tools to read
+    #[arg(long)]
+    pub json: bool,
+
+    /// Delimiter for input files
+    #[arg(long, default_value = ",")]
+    pub delimiter: char,
+}
+
+impl ProfileArgs {
+    #[allow(clippy::unused_self)]
+    #[allow(clippy::unnecessary_wraps)]
+    pub const fn validate(&self) -> Result<()> {
+        Ok(())
+    }
+
+    pub fn execute(&self, global: &GlobalArgs) -> Result<()> {
+        let data =
+            read_data(&self.table, Some(self.delimiter), global).with_context(|| {
+                format!("profile - failed to read csv data from {}", self.table)
+            })?;
+        let columns = if self.columns.is_empty() {
+            data.get_column_names_str()
+                .iter()
+                .map(ToString::to_string)
+                .collect()
+        } else {
+            resolve_columns(&data, &self.columns, global.ignore_column_case)?
+        };
+
+        let profiles = columns
+            .iter()
+            .map(|column| profile(&data, column, self.top))
+            .collect::<Result<Vec<_>>>()
+            .with_context(|| format!("profile - failed to profile {}", self.table))?;
+
+        if self.json {
+            let report = json!({
+                "table": self.table,
+                "rows": data.height(),
+                "columns": profiles.iter().map(Profile::json).collect::<Vec<_>>(),
+                "candidate_keys": profiles
+                    .iter()
+                    .filter(|p| p.key)
+                    .map(|p| p.column.as_str())
+                    .collect::<Vec<_>>(),
+            });
+            write_text(&serde_json::to_string_pretty(&report)?, global)
+                .with_context(|| "profile - failed to write data to stdout".to_string())?;
+            return Ok(());
+        }
+
+        let result = df!(
+            "column" => profiles.iter().map(|p| p.column.as_str()).collect::<Vec<_>>(),
+            "type" => profiles.iter().map(|p| p.dtype.as_str()).collect::<Vec<_>>(),
+            "nulls" => profiles.iter().map(|p| p.nulls).collect::<Vec<_>>(),
+            "distinct" => profiles.iter().map(|p| p.distinct).collect::<Vec<_>>(),
+            "min" => profiles.iter().map(|p| p.min.as_deref()).collect::<Vec<_>>(),
+            "max" => profiles.iter().map(|p| p.max.as_deref()).collect::<Vec<_>>(),
+            "min_length" => profiles.iter().map(|p| p.lengths.map(|l| l.0)).collect::<Vec<_>>(),
+            "mean_length" => profiles.iter().map(|p| p.lengths.map(|l| l.1)).collect::<Vec<_>>(),
+            "max_length" => profiles.iter().map(|p| p.lengths.map(|l| l.2)).collect::<Vec<_>>(),
+            "top" => profiles.iter().map(Profile::top).collect::<Vec<_>>(),
+            "key" => profiles.iter().map(|p| p.key).collect::<Vec<_>>(),
+        )?;
+
+        write_data(result, global)
+            .with_context(|| "profile - failed to write data to stdout".to_string())?;
+
+        Ok(())
+    }
+}
+
+/// Summary of the values in one column
+struct Profile {
+    column: String,
+    dtype: String,
+    nulls: u64,
+    /// Number of distinct non-null values
+    distinct: u64,
+    min: Option<String>,
+    max: Option<String>,
+    /// Minimum, mean and maximum number of characters of string values
+    lengths: Option<(u64, f64, u64)>,
+    /// Most common non-null values and their counts, ties ordered by value
+    values: Vec<(String, u64)>,
+    /// Whether every row has a different non-null value, so the column could identify rows
+    key: bool,
+}
+
+impl Profile {
+    /// The most common values as `value (count)`, comma separated
+    fn top(&self) -> String {
+        self.values
+            .iter()
+            .map(|(value, count)| format!("{value} ({count})"))
+            .join(", ")
+    }
+
+    fn json(&self) -> Value {
+        json!({
+            "name": self.column,
+            "type": self.dtype,
+            "nulls": self.nulls,
+            "distinct": self.distinct,
+            "min": self.min,
+            "max": self.max,
+            "length": self.lengths.map(|(min, mean, max)| json!({
+                "min": min,
+                "mean": mean,
+                "max": max,
+            })),
+            "top": self
+                .values
+                .iter()
+                .map(|(value, count)| json!({"value": value, "count": count}))
+                .collect::<Vec<_>>(),
+            "candidate_key": self.key,
+        })
+    }
+}
+
+/// Whether values of `dtype` can be ordered and counted, unlike lists and structs
+fn is_comparable(dtype: &DataType) -> bool {
+    dtype.is_primitive_numeric()
+        || dtype.is_temporal()
+        || matches!(dtype, DataType::String | DataType::Boolean)
+}
+
+/// Profiles `column` of `df`, listing its `top` most common values
+fn profile(df: &DataFrame, column: &str, top: usize) -> Result<Profile> {
+    let dtype = df.column(column)?.dtype().clone();
+    let comparable = is_comparable(&dtype);
+    let values = col(column);
+
+    let mut exprs = vec![
+        values
+            .clone()
+            .null_count()
+            .cast(DataType::UInt64)
+            .alias("nulls"),
+        values
+            .clone()
+            .drop_nulls()
+            .n_unique()
+            .cast(DataType::UInt64)
+            .alias("distinct"),
+    ];
+    if comparable {
+        exprs.push(values.clone().min().cast(DataType::String).alias("min"));
+        exprs.push(values.clone().max().cast(DataType::String).alias("max"));
+    }
+    if dtype == DataType::String {
+        let lengths = values.str().len_chars();
+        exprs.push(
+            lengths
+                .clone()
+                .min()
+                .cast(DataType::UInt64)
+                .alias("min_length"),
+        );
+        exprs.push(lengths.clone().mean().alias("mean_length"));
+        exprs.push(lengths.max().cast(DataType::UInt64).alias("max_length"));
+    }
+    let stats = df.clone().lazy().select(exprs).collect()?;
+
+    let count =
+        |name: &str| -> Result<u64> { Ok(stats.column(name)?.u64()?.get(0).unwrap_or(0)) };
+    let text = |name: &str| -> Result<Option<String>> {
+        if !comparable {
+            return Ok(None);
+        }
+        Ok(stats.column(name)?.str()?.get(0).map(str::to_string))
+    };
+    let lengths =
+        if dtype == DataType::String && stats.column("mean_length")?.null_count() == 0 {
+            Some((
+                count("min_length")?,
+                stats.column("mean_length")?.f64()?.get(0).unwrap_or(0.0),
+                count("max_length")?,
+            ))
+        } else {
+            None
+        };
+
+    let nulls = count("nulls")?;
+    let distinct = count("distinct")?;
+    let rows = u64::try_from(df.height())?;
+
+    Ok(Profile {
+        column: column.to_string(),
+        dtype: dtype.to_string(),
+        nulls,
+        distinct,
+        min: text("min")?,
+        max: text("max")?,
+        lengths,
+        values: if comparable && top > 0 {
+            common_values(df, column, top)?
+        } else {
+            vec![]
+        },
+        key: rows > 0 && nulls == 0 && distinct == rows,
+    })
+}
+
+/// The `top` most common non-null values of `column` with their counts, ties ordered by
+/// value
+fn common_values(df: &DataFrame, column: &str, top: usize) -> Result<Vec<(String, u64)>> {
+    let counts = df
+        .clone()
+        .lazy()
+        .select([col(column).alias("value")])
+        .filter(col("value").is_not_null())
+        .group_by([col("value")])
+        .agg([len().cast(DataType::UInt64).alias("count")])
+        .sort(
+            ["count", "value"],
+            SortMultipleOptions::default().with_order_descending_multi([true, false]),
+        )
+        .limit(IdxSize::try_from(top)?)
+        .select([col("value").cast(DataType::String), col("count")])
+        .collect()?;
+
+    Ok(counts
+        .column("value")?
+        .str()?
+        .into_iter()
+        .zip(counts.column("count")?.u64()?)
+        .filter_map(|(value, count)| Some((value?.to_string(), count?)))
+        .collect())
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn sample() -> DataFrame {
+        df!(
+            "id" => [1, 2, 3, 4, 5],
+            "city" => [Some("Oslo"), Some("Rome"), None, Some("Oslo"), Some("Bern")],
+            "score" => [Some(2.5), None, None, Some(9.0), Some(2.5)],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_profile_strings() {
+        let city = profile(&sample(), "city", 2).unwrap();
+        assert_eq!(city.nulls, 1);
+        assert_eq!(city.distinct, 3);
+        assert_eq!(city.min.as_deref(), Some("Bern"));
+        assert_eq!(city.max.as_deref(), Some("Rome"));
+        assert_eq!(city.lengths, Some((4, 4.0, 4)));
+        assert_eq!(city.top(), "Oslo (2), Bern (1)");
+        assert!(!city.key);
+    }
+
+    #[test]
+    fn test_profile_numbers() {
+        let df = sample();
+        let id = profile(&df, "id", 5).unwrap();
+        assert!(id.key);
+        assert_eq!(id.lengths, None);
+        assert_eq!(id.values.len(), 5);
+
+        let score = profile(&df, "score", 0).unwrap();
+        assert_eq!((score.nulls, score.distinct), (2, 2));
+        assert_eq!(score.max.as_deref(), Some("9.0"));
+        assert!(score.values.is_empty());
+        assert!(!score.key);
+        assert_eq!(score.json()["candidate_key"], json!(false));
+    }
+
+    #[test]
+    fn test_profile_customers_csv() {
+        let args = ProfileArgs {
+            table: "data/orders/customers.csv".to_string(),
+            columns: vec![],
+            top: 5,
+            json: false,
+            delimiter: ',',
+        };
+        assert!(args.validate().is_ok());
+        assert!(args.execute(&GlobalArgs::default()).is_ok());
+    }
+}
@@ -1,11 +1,28 @@
-use anyhow::{Context, Result, bail};
+use anyhow::{Context, Result, bail, ensure};
 use clap::{Args, ValueHint};
 use itertools::izip;
-use polars::{prelude::IntoLazy, sql::SQLContext};
+use log::{debug, warn};
+use polars::sql::SQLContext;
+use regex::Regex;
+use std::borrow::Cow;
 use std::io::{self, Read};
 
-use crate::args::OutputFormat;
-use crate::io::{read_data, write_data};
+use crate::args::GlobalArgs;
+use crate::exit::Failure;
+use crate::io::{print_plan, scan_data, table_delimiters, write_data};
+
+/// String literals, quoted identifiers, comments and `::` casts, which are skipped, or a
+/// `:name` placeholder or a `;` between statements
+#[allow(clippy::expect_used)]
+static TOKEN: std::sync::LazyLock<Regex> = std::sync::LazyLock::new(|| {
+    Regex::new(r#"'(?:[^']|'')*'|"(?:[^"]|"")*"|--[^\n]*|(?s:/\*.*?\*/)|::|:([A-Za-z_][A-Za-z0-9_]*)|;"#)
+        .expect("Invalid regex pattern")
+});
+
+#[allow(clippy::expect_used)]
+static NUMBER: std::sync::LazyLock<Regex> = std::sync::LazyLock::new(|| {
+    Regex::new(r"^-?(0|[1-9]\d*)(\.\d+)?([eE][+-]?\d+)?$").expect("Invalid regex pattern")
+});
 
 #[derive(Args, Debug)]
 pub struct QueryArgs {
@@ -18,8 +35,39 @@ pub struct QueryArgs {
     pub r#as: Vec<String>,
 
     /// The SQL query to execute (reads from stdin if not provided)
+    ///
+    /// Several `;` separated statements can build tables with `CREATE TABLE x AS SELECT ...`
+    /// for the last one, whose results are written.
     #[arg(last = true)]
     pub query: Option<String>,
+
+    /// The SQL query to execute, instead of the trailing argument
+    ///
+    /// Use this when a table is read from stdin.
+    /// Examples: --query "SELECT * FROM a JOIN b USING (id)"
+    #[arg(long = "query", value_name = "QUERY", conflicts_with = "query")]
+    pub query_option: Option<String>,
+
+    /// Values for `:name` placeholders in the query (can be repeated)
+    ///
+    /// Numbers are substituted as they are and anything else as a quoted string.
+    /// Examples: --param day=2024-01-01 --param quantity=3
+    #[arg(long = "param", value_name = "NAME=VALUE")]
+    pub params: Vec<String>,
+
+    /// Delimiter for input files, for every table or per table
+    ///
+    /// Give one delimiter for every table, one per table in order, or name=delimiter pairs
+    /// (other tables use ','), with \t for a tab.
+    /// Examples: --delimiter ';' or --delimiter ",\t" or --delimiter "events=\t"
+    #[arg(long, default_value = ",")]
+    pub delimiter: String,
+
+    /// Print the optimized query plan instead of running the query
+    ///
+    /// Shows which filters and columns are pushed down into the table scans.
+    #[arg(long)]
+    pub explain: bool,
 }
 
 impl QueryArgs {
@@ -32,54 +80,209 @@ impl QueryArgs {
             bail!("Number of table names must match number of tables");
         }
 
+        // Stdin can only be read once, by a single table or by the query
+        let stdin_tables = self.tables.iter().filter(|t| *t == "-").count();
+        if stdin_tables > 1 {
+            bail!("Only one table can be read from stdin ('-')");
+        }
+        if stdin_tables == 1 && self.sql().is_none() {
+            bail!(
+                "A table is read from stdin, so the query can't be. Pass it with --query or after '--'"
+            );
+        }
+
+        self.params()?;
+        table_delimiters(&self.delimiter, &self.names())?;
+
         Ok(())
     }
 
-    pub fn execute(&self, format: &OutputFormat) -> Result<()> {
+    pub fn execute(&self, global: &GlobalArgs) -> Result<()> {
         let mut ctx = SQLContext::new();
-        let names = if self.r#as.is_empty() {
-            (0..self.tables.len())
-                .map(|i| format!("T{}", i + 1))
-                .collect()
-        } else {
-            self.r#as.clone()
-        };
+        let names = self.names();
+        let delimiters = table_delimiters(&self.delimiter, &names)?;
 
-        for (name, table) in izip!(names.iter(), self.tables.iter()) {
+        for (name, table, delimiter) in izip!(&names, &self.tables, delimiters) {
             ctx.register(
                 name,
-                read_data(table, None)
-                    .with_context(|| format!("query - failed to read table '{table}'"))?
-                    .lazy(),
+                scan_data(table, Some(delimiter), global)
+                    .with_context(|| format!("query - failed to read table '{table}'"))?,
             );
         }
 
-        // Get the query either from the argument or from stdin
-        let query = match &self.query {
-            Some(q) if q != "-" => q.clone(),
-            _ => {
-                let mut buffer = String::new();
-                io::stdin()
-                    .read_to_string(&mut buffer)
-                    .context("Failed to read query from stdin")?;
-                buffer.trim().to_string()
-            }
+        // Get the query either from the arguments or from stdin
+        let query = if let Some(q) = self.sql() {
+            q.to_string()
+        } else {
+            let mut buffer = String::new();
+            io::stdin()
+                .read_to_string(&mut buffer)
+                .context("Failed to read query from stdin")?;
+            buffer.trim().to_string()
         };
 
-        if query.is_empty() {
+        let query = bind(&query, &self.params()?).map_err(|e| Failure::Usage.tag(e))?;
+        let statements = statements(&query);
+        let Some((last, setup)) = statements.split_last() else {
             bail!("Query cannot be empty");
+        };
+
+        // Earlier statements build tables (e.g. `CREATE TABLE x AS SELECT ...`) for the last one
+        for (idx, statement) in setup.iter().enumerate() {
+            if is_select(statement) {
+                warn!(
+                    "statement {} is a query whose results are discarded, only the last statement's are written",
+                    idx + 1
+                );
+            }
+            let _ = ctx.execute(statement).with_context(|| {
+                format!(
+                    "query - failed to execute statement {} '{statement}'",
+                    idx + 1
+                )
+            })?;
+        }
+        let lf = ctx
+            .execute(last)
+            .with_context(|| format!("query - failed to execute query '{last}'"))?;
+        if self.explain {
+            return print_plan(&lf);
         }
-        let result = ctx
-            .execute(&query)
-            .with_context(|| format!("query - failed to execute query '{query}'"))?
+        if log::log_enabled!(log::Level::Debug) {
+            debug!("query plan:\n{}", lf.describe_optimized_plan()?);
+        }
+        let result = lf
             .collect()
             .with_context(|| "query - failed to collect results".to_string())?;
 
-        write_data(result, format)
+        write_data(result, global)
             .with_context(|| "query - failed to write data to stdout".to_string())?;
 
         Ok(())
     }
+
+    /// Names of the tables in the query, from --as or `T1`, `T2`, ...
+    fn names(&self) -> Vec<String> {
+        if self.r#as.is_empty() {
+            (0..self.tables.len())
+                .map(|i| format!("T{}", i + 1))
+                .collect()
+        } else {
+            self.r#as.clone()
+        }
+    }
+
+    /// The query given on the command line, or `None` if it should be read from stdin
+    fn sql(&self) -> Option<&str> {
+        self.query_option
+            .as_deref()
+            .or(self.query.as_deref())
+            .filter(|q| *q != "-")
+    }
+
+    /// The `--param` names and values
+    fn params(&self) -> Result<Vec<(&str, &str)>> {
+        let mut params: Vec<(&str, &str)> = vec![];
+        for param in &self.params {
+            let Some((name, value)) = param.split_once('=') else {
+                bail!(
+                    "Invalid --param '{param}'. Expected NAME=VALUE, e.g. day=2024-01-01"
+                );
+            };
+            ensure!(
+                name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+                    && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_'),
+                "Invalid --param name '{name}'. Use letters, digits and underscores"
+            );
+            ensure!(
+                params.iter().all(|(other, _)| *other != name),
+                "--param '{name}' is given more than once"
+            );
+            params.push((name, value));
+        }
+
+        Ok(params)
+    }
+}
+
+/// Splits a script into its `;` separated statements, ignoring `;` inside string literals,
+/// quoted identifiers and comments, and dropping empty statements
+fn statements(script: &str) -> Vec<&str> {
+    let mut statements = vec![];
+    let mut start = 0;
+    for token in TOKEN.find_iter(script) {
+        if token.as_str() == ";" {
+            statements.push(&script[start..token.start()]);
+            start = token.end();
+        }
+    }
+    statements.push(&script[start..]);
+
+    statements
+        .into_iter()
+        .map(str::trim)
+        .filter(|statement| !strip_comments(statement).trim().is_empty())
+        .collect()
+}
+
+/// `sql` without its comments
+fn strip_comments(sql: &str) -> Cow<'_, str> {
+    TOKEN.replace_all(sql, |caps: &regex::Captures| {
+        if caps[0].starts_with("--") || caps[0].starts_with("/*") {
+            String::new()
+        } else {
+            caps[0].to_string()
+        }
+    })
+}
+
+/// Whether `statement` is a query producing results, rather than e.g. a `CREATE TABLE`
+fn is_select(statement: &str) -> bool {
+    let keyword = strip_comments(statement)
+        .split(|c: char| !c.is_ascii_alphabetic())
+        .find(|word| !word.is_empty())
+        .map(str::to_ascii_uppercase)
+        .unwrap_or_default();
+    ["SELECT", "WITH", "FROM", "VALUES"].contains(&keyword.as_str())
+}
+
+/// Substitutes the `:name` placeholders in `query` with their `params` values
+///
+/// Placeholders inside string literals, quoted identifiers and comments are left alone, as are
+/// `::` casts. Numbers are substituted as they are, and anything else as a string literal.
+fn bind(query: &str, params: &[(&str, &str)]) -> Result<String> {
+    let mut missing = vec![];
+    let mut used = vec![false; params.len()];
+    let bound = TOKEN.replace_all(query, |caps: &regex::Captures| {
+        let Some(name) = caps.get(1) else {
+            return caps[0].to_string();
+        };
+        let Some(idx) = params.iter().position(|(param, _)| *param == name.as_str()) else {
+            missing.push(format!(":{}", name.as_str()));
+            return caps[0].to_string();
+        };
+        used[idx] = true;
+        let value = params[idx].1;
+        if NUMBER.is_match(value) {
+            value.to_string()
+        } else {
+            format!("'{}'", value.replace('\'', "''"))
+        }
+    });
+
+    ensure!(
+        missing.is_empty(),
+        "No --param given for {} in the query",
+        missing.join(", ")
+    );
+    for ((name, _), used) in params.iter().zip(used) {
+        if !used {
+            warn!("--param '{name}' isn't used in the query");
+        }
+    }
+    debug!("bound query: {bound}");
+
+    Ok(bound.into_owned())
 }
 
 #[cfg(test)]
@@ -92,6 +295,10 @@ mod tests {
             tables: vec!["test.csv".to_string()],
             r#as: vec![],
             query: Some("SELECT * FROM T1".to_string()),
+            query_option: None,
+            params: vec![],
+            delimiter: ",".to_string(),
+            explain: false,
         };
         assert!(args.validate().is_ok());
     }
@@ -102,7 +309,35 @@ mod tests {
             tables: vec![],
             r#as: vec![],
             query: Some("SELECT * FROM T1".to_string()),
+            query_option: None,
+            params: vec![],
+            delimiter: ",".to_string(),
+            explain: false,
+        };
+        assert!(args.validate().is_err());
+    }
+
+    #[test]
+    fn test_validation_stdin_table() {
+        let mut args = QueryArgs {
+            tables: vec!["-".to_string(), "test.csv".to_string()],
+            r#as: vec!["a".to_string(), "b".to_string()],
+            query: None,
+            query_option: Some("SELECT * FROM a".to_string()),
+            params: vec![],
+            delimiter: ",".to_string(),
+            explain: false,
         };
+        assert!(args.validate().is_ok());
+
+        // The query would need stdin too
+        args.query_option = None;
+        assert!(args.validate().is_err());
+        args.query = Some("-".to_string());
+        assert!(args.validate().is_err());
+
+        args.query = Some("SELECT * FROM a".to_string());
+        args.tables = vec!["-".to_string(), "-".to_string()];
         assert!(args.validate().is_err());
     }
 
@@ -112,6 +347,10 @@ mod tests {
             tables: vec!["test1.csv".to_string(), "test2.csv".to_string()],
             r#as: vec!["table1".to_string()],
             query: Some("SELECT * FROM table1".to_string()),
+            query_option: None,
+            params: vec![],
+            delimiter: ",".to_string(),
+            explain: false,
         };
         assert!(args.validate().is_err());
     }
@@ -122,8 +361,107 @@ mod tests {
             tables: vec!["test1.csv".to_string(), "test2.csv".to_string()],
             r#as: vec!["table1".to_string(), "table2".to_string()],
             query: Some("SELECT * FROM table1".to_string()),
+            query_option: None,
+            params: vec![],
+            delimiter: ",".to_string(),
+            explain: false,
+        };
+        assert!(args.validate().is_ok());
+    }
+
+    #[test]
+    fn test_validation_params() {
+        let mut args = QueryArgs {
+            tables: vec!["test.csv".to_string()],
+            r#as: vec![],
+            query: Some("SELECT * FROM T1 WHERE day = :day".to_string()),
+            query_option: None,
+            params: vec!["day=2024-01-01".to_string(), "note=a=b".to_string()],
+            delimiter: ",".to_string(),
+            explain: false,
         };
         assert!(args.validate().is_ok());
+
+        args.params = vec!["day".to_string()];
+        assert!(args.validate().is_err());
+        args.params = vec!["1day=2024-01-01".to_string()];
+        assert!(args.validate().is_err());
+        args.params = vec!["day=2024-01-01".to_string(), "day=2024-01-02".to_string()];
+        assert!(args.validate().is_err());
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn test_bind() {
+        let params = [("day", "2024-01-01"), ("min", "3"), ("name", "O'Brien")];
+        let query = "SELECT ':day', \":min\", x::int FROM t -- :name\nWHERE day = :day AND n >= :min AND name = :name";
+        assert_eq!(
+            bind(query, &params).unwrap(),
+            "SELECT ':day', \":min\", x::int FROM t -- :name\nWHERE day = '2024-01-01' AND n >= 3 AND name = 'O''Brien'"
+        );
+
+        let result = bind("SELECT * FROM t WHERE a = :a AND b = :b", &params);
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "No --param given for :a, :b in the query"
+        );
+        // Leading zeros would be lost as a number
+        assert_eq!(bind(":zip", &[("zip", "007")]).unwrap(), "'007'");
+        assert_eq!(bind(":x", &[("x", "-1.5e3")]).unwrap(), "-1.5e3");
+    }
+
+    #[test]
+    fn test_statements() {
+        let script = "CREATE TABLE a AS SELECT ';' AS x; -- first; table\n\n/* b; */ SELECT * FROM a;\n-- done\n";
+        assert_eq!(
+            statements(script),
+            [
+                "CREATE TABLE a AS SELECT ';' AS x",
+                "-- first; table\n\n/* b; */ SELECT * FROM a"
+            ]
+        );
+        assert!(statements(" ; -- nothing").is_empty());
+
+        assert!(is_select("/* totals */ select 1"));
+        assert!(is_select("WITH a AS (SELECT 1) SELECT * FROM a"));
+        assert!(!is_select("-- select\nCREATE TABLE a AS SELECT 1"));
+    }
+
+    #[test]
+    fn test_query_orders_script() {
+        let args = QueryArgs {
+            tables: vec!["data/orders/orders.csv".to_string()],
+            r#as: vec!["orders".to_string()],
+            query: Some(
+                "CREATE TABLE big AS SELECT * FROM orders WHERE quantity > 2;
+                 CREATE TABLE spent AS SELECT customer_id, SUM(quantity * price) AS total FROM big GROUP BY customer_id;
+                 SELECT * FROM spent ORDER BY total DESC;"
+                    .to_string(),
+            ),
+            query_option: None,
+            params: vec![],
+            delimiter: ",".to_string(),
+            explain: false,
+        };
+
+        assert!(args.validate().is_ok());
+        assert!(args.execute(&GlobalArgs::default()).is_ok());
+    }
+
+    #[test]
+    fn test_query_orders_explain() {
+        let args = QueryArgs {
+            tables: vec!["data/orders/orders.csv".to_string()],
+            r#as: vec!["orders".to_string()],
+            query: Some("SELECT order_id FROM orders WHERE quantity > 2".to_string()),
+            query_option: None,
+            params: vec![],
+            delimiter: ",".to_string(),
+            explain: true,
+        };
+
+        assert!(args.validate().is_ok());
+        assert!(args.execute(&GlobalArgs::default()).is_ok());
     }
 
     #[test]
@@ -136,12 +474,16 @@ mod tests {
             query: Some(
                 "SELECT * FROM orders WHERE product_id = 'PRODUCT-005'".to_string(),
             ),
+            query_option: None,
+            params: vec![],
+            delimiter: ",".to_string(),
+            explain: false,
         };
 
         assert!(args.validate().is_ok());
 
         // Test that the query executes without error
-        let result = args.execute(&crate::args::OutputFormat::Auto);
+        let result = args.execute(&GlobalArgs::default());
         assert!(result.is_ok(), "Query execution should succeed");
     }
 
@@ -153,12 +495,16 @@ mod tests {
             tables: vec![orders_path.to_string()],
             r#as: vec![],
             query: Some("SELECT * FROM T1 WHERE product_id = 'PRODUCT-005'".to_string()),
+            query_option: None,
+            params: vec![],
+            delimiter: ",".to_string(),
+            explain: false,
         };
 
         assert!(args.validate().is_ok());
 
         // Test that the query executes without error using default table name
-        let result = args.execute(&crate::args::OutputFormat::Auto);
+        let result = args.execute(&GlobalArgs::default());
         assert!(
             result.is_ok(),
             "Query execution with default table name should succeed"
@@ -0,0 +1,188 @@
+// Rank the rows of a table by a column, optionally within each group, keeping every row.
+use anyhow::{Context, Result};
+use clap::{Args, ValueEnum, ValueHint};
+use polars::prelude::*;
+
+use crate::args::GlobalArgs;
+use crate::io::{resolve_columns, scan_data, write_data};
+
+#[derive(Debug, Clone, Copy, ValueEnum, PartialEq, Eq)]
+pub enum Method {
+    /// Ties share the lowest of their ranks, leaving a gap after them (1, 2, 2, 4)
+    Min,
+    /// Ties share the highest of their ranks (1, 3, 3, 4)
+    Max,
+    /// Ties share the mean of their ranks (1, 2.5, 2.5, 4)
+    Average,
+    /// Ties share a rank without leaving a gap after them (1, 2, 2, 3)
+    Dense,
+    /// Ties are ranked in the order they appear (1, 2, 3, 4)
+    Ordinal,
+}
+
+impl From<Method> for RankMethod {
+    fn from(method: Method) -> Self {
+        match method {
+            Method::Min => Self::Min,
+            Method::Max => Self::Max,
+            Method::Average => Self::Average,
+            Method::Dense => Self::Dense,
+            Method::Ordinal => Self::Ordinal,
+        }
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct RankArgs {
+    /// Input table (file or '-' for stdin)
+    #[arg(required = true, value_hint = ValueHint::FilePath)]
+    pub table: String,
+
+    /// Column to rank rows by, smallest first
+    ///
+    /// Examples: --by score
+    #[arg(long, required = true)]
+    pub by: String,
+
+    /// How to rank tied values
+    #[arg(long, value_enum, default_value = "min")]
+    pub method: Method,
+
+    /// Columns to group by, ranking the rows of each group separately (comma separated)
+    ///
+    /// Examples: --per region or --per "region,year"
+    #[arg(long, value_delimiter = ',')]
+    pub per: Vec<String>,
+
+    /// Rank the largest values first
+    #[arg(long)]
+    pub descending: bool,
+
+    /// Name of the column holding the ranks
+    #[arg(long, default_value = "rank")]
+    pub name: String,
+
+    /// Delimiter for input files
+    #[arg(long, default_value = ",")]
+    pub delimiter: char,
+}
+
+impl RankArgs {
+    #[allow(clippy::unused_self)]
+    #[allow(clippy::unnecessary_wraps)]
+    pub const fn validate(&self) -> Result<()> {
+        Ok(())
+    }
+
+    pub fn execute(&self, global: &GlobalArgs) -> Result<()> {
+        let mut lf =
+            scan_data(&self.table, Some(self.delimiter), global).with_context(|| {
+                format!("rank - failed to read csv data from {}", self.table)
+            })?;
+        let schema = lf.collect_schema()?;
+        let header = DataFrame::empty_with_schema(&schema);
+
+        let by = resolve_columns(
+            &header,
+            std::slice::from_ref(&self.by),
+            global.ignore_column_case,
+        )?
+        .remove(0);
+        let per = resolve_columns(&header, &self.per, global.ignore_column_case)?;
+        let result = lf
+            .with_column(self.rank(&by, &per))
+            .collect()
+            .with_context(|| format!("rank - failed to rank rows of {}", self.table))?;
+
+        write_data(result, global)
+            .with_context(|| "rank - failed to write data to stdout".to_string())?;
+
+        Ok(())
+    }
+
+    /// The rank of each row by `by` within its `per` group, named --name
+    ///
+    /// Nulls aren't ranked.
+    fn rank(&self, by: &str, per: &[String]) -> Expr {
+        let options = RankOptions {
+            method: self.method.into(),
+            descending: self.descending,
+        };
+        let mut rank = col(by).rank(options, None);
+        if !per.is_empty() {
+            rank = rank.over(per.iter().map(|c| col(c.as_str())).collect::<Vec<_>>());
+        }
+
+        rank.alias(self.name.as_str())
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn rank(method: Method) -> RankArgs {
+        RankArgs {
+            table: "data/sales/january.csv".to_string(),
+            by: "amount".to_string(),
+            method,
+            per: vec![],
+            descending: false,
+            name: "rank".to_string(),
+            delimiter: ',',
+        }
+    }
+
+    fn ranks(args: &RankArgs, per: &[String]) -> Vec<Option<f64>> {
+        let df = df!(
+            "region" => ["north", "south", "north", "north", "south"],
+            "score" => [Some(5), Some(7), Some(5), Some(9), None],
+        )
+        .unwrap();
+        df.lazy()
+            .with_column(args.rank("score", per))
+            .collect()
+            .unwrap()
+            .column("rank")
+            .unwrap()
+            .cast(&DataType::Float64)
+            .unwrap()
+            .f64()
+            .unwrap()
+            .into_iter()
+            .collect()
+    }
+
+    #[test]
+    fn test_rank_methods() {
+        let expected = [
+            (Method::Min, [1.0, 3.0, 1.0, 4.0]),
+            (Method::Max, [2.0, 3.0, 2.0, 4.0]),
+            (Method::Average, [1.5, 3.0, 1.5, 4.0]),
+            (Method::Dense, [1.0, 2.0, 1.0, 3.0]),
+            (Method::Ordinal, [1.0, 3.0, 2.0, 4.0]),
+        ];
+        for (method, expected) in expected {
+            let mut expected: Vec<_> = expected.into_iter().map(Some).collect();
+            expected.push(None);
+            assert_eq!(ranks(&rank(method), &[]), expected, "{method:?}");
+        }
+
+        let mut args = rank(Method::Dense);
+        args.descending = true;
+        let per = ["region".to_string()];
+        assert_eq!(
+            ranks(&args, &per),
+            [Some(2.0), Some(1.0), Some(2.0), Some(1.0), None]
+        );
+    }
+
+    #[test]
+    fn test_rank_sales_csv() {
+        let mut args = rank(Method::Min);
+        args.per = vec!["region".to_string()];
+        assert!(args.validate().is_ok());
+        assert!(args.execute(&GlobalArgs::default()).is_ok());
+    }
+}
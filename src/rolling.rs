@@ -0,0 +1,234 @@
+// Rolling statistics over the last few rows of a table, optionally within each group.
+use anyhow::{Context, Result, ensure};
+use clap::{Args, ValueEnum, ValueHint};
+use polars::prelude::*;
+
+use crate::args::GlobalArgs;
+use crate::io::{resolve_columns, scan_data, write_data};
+
+#[derive(Debug, Clone, Copy, ValueEnum, PartialEq, Eq)]
+pub enum Operation {
+    Mean,
+    Sum,
+    Min,
+    Max,
+    Median,
+    Std,
+    Var,
+}
+
+impl Operation {
+    /// Name of the operation in output column names, e.g. `price_rolling_mean`
+    const fn name(self) -> &'static str {
+        match self {
+            Self::Mean => "mean",
+            Self::Sum => "sum",
+            Self::Min => "min",
+            Self::Max => "max",
+            Self::Median => "median",
+            Self::Std => "std",
+            Self::Var => "var",
+        }
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct RollingArgs {
+    /// Input table (file or '-' for stdin)
+    #[arg(required = true, value_hint = ValueHint::FilePath)]
+    pub table: String,
+
+    /// Columns to compute rolling statistics of (comma separated)
+    ///
+    /// Examples: --column price or --column "price,volume"
+    #[arg(long, required = true, value_delimiter = ',')]
+    pub column: Vec<String>,
+
+    /// Number of rows in each window, ending at the current row
+    #[arg(long, required = true)]
+    pub window: usize,
+
+    /// Statistics to compute over each window (comma separated)
+    ///
+    /// Examples: --ops mean or --ops "mean,std"
+    #[arg(long, value_enum, value_delimiter = ',', default_value = "mean")]
+    pub ops: Vec<Operation>,
+
+    /// Fewest non-null values a window needs for a result, instead of a full window
+    #[arg(long)]
+    pub min_periods: Option<usize>,
+
+    /// Columns to group by, each group gets its own windows (comma separated)
+    ///
+    /// Examples: --by ticker
+    #[arg(long, value_delimiter = ',')]
+    pub by: Vec<String>,
+
+    /// Columns to order rows by before computing the windows (comma separated)
+    ///
+    /// The output keeps this order. Examples: --sort-by date
+    #[arg(long, value_delimiter = ',')]
+    pub sort_by: Vec<String>,
+
+    /// Delimiter for input files
+    #[arg(long, default_value = ",")]
+    pub delimiter: char,
+}
+
+impl RollingArgs {
+    pub fn validate(&self) -> Result<()> {
+        ensure!(self.window > 0, "--window must be at least 1");
+        if let Some(min_periods) = self.min_periods {
+            ensure!(
+                (1..=self.window).contains(&min_periods),
+                "--min-periods must be between 1 and --window ({})",
+                self.window
+            );
+        }
+
+        Ok(())
+    }
+
+    pub fn execute(&self, global: &GlobalArgs) -> Result<()> {
+        let mut lf =
+            scan_data(&self.table, Some(self.delimiter), global).with_context(|| {
+                format!("rolling - failed to read csv data from {}", self.table)
+            })?;
+        let schema = lf.collect_schema()?;
+        let header = DataFrame::empty_with_schema(&schema);
+
+        let columns = resolve_columns(&header, &self.column, global.ignore_column_case)?;
+        let by = resolve_columns(&header, &self.by, global.ignore_column_case)?;
+        let sort_by = resolve_columns(&header, &self.sort_by, global.ignore_column_case)?;
+        if !sort_by.is_empty() {
+            lf = lf.sort(
+                &sort_by,
+                SortMultipleOptions::default().with_maintain_order(true),
+            );
+        }
+        let result = lf
+            .with_columns(self.statistics(&columns, &by))
+            .collect()
+            .with_context(|| {
+                format!(
+                    "rolling - failed to compute rolling statistics of {}",
+                    self.table
+                )
+            })?;
+
+        write_data(result, global)
+            .with_context(|| "rolling - failed to write data to stdout".to_string())?;
+
+        Ok(())
+    }
+
+    /// Each operation over each of `columns` within its `by` group, named
+    /// `{column}_rolling_{operation}` like the `window` operations
+    ///
+    /// Windows with fewer than --min-periods non-null values (the whole window by default)
+    /// are null.
+    fn statistics(&self, columns: &[String], by: &[String]) -> Vec<Expr> {
+        let options = RollingOptionsFixedWindow {
+            window_size: self.window,
+            min_periods: self.min_periods.unwrap_or(self.window),
+            ..RollingOptionsFixedWindow::default()
+        };
+
+        let mut exprs = Vec::with_capacity(columns.len() * self.ops.len());
+        for column in columns {
+            for op in &self.ops {
+                let values = col(column.as_str());
+                let options = options.clone();
+                let mut expr = match op {
+                    Operation::Mean => values.rolling_mean(options),
+                    Operation::Sum => values.rolling_sum(options),
+                    Operation::Min => values.rolling_min(options),
+                    Operation::Max => values.rolling_max(options),
+                    Operation::Median => values.rolling_median(options),
+                    Operation::Std => values.rolling_std(options),
+                    Operation::Var => values.rolling_var(options),
+                };
+                if !by.is_empty() {
+                    expr =
+                        expr.over(by.iter().map(|c| col(c.as_str())).collect::<Vec<_>>());
+                }
+                exprs.push(expr.alias(format!("{column}_rolling_{}", op.name())));
+            }
+        }
+
+        exprs
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn rolling(window: usize, ops: Vec<Operation>) -> RollingArgs {
+        RollingArgs {
+            table: "data/sensors/readings.csv".to_string(),
+            column: vec!["humidity".to_string()],
+            window,
+            ops,
+            min_periods: None,
+            by: vec!["sensor".to_string()],
+            sort_by: vec!["timestamp".to_string()],
+            delimiter: ',',
+        }
+    }
+
+    #[test]
+    fn test_statistics() {
+        let df = df!(
+            "ticker" => ["a", "a", "b", "a", "b"],
+            "price" => [1.0, 3.0, 10.0, 8.0, 20.0],
+        )
+        .unwrap();
+        let mut args = rolling(2, vec![Operation::Mean, Operation::Max, Operation::Std]);
+        let values = |args: &RollingArgs, name: &str| -> Vec<Option<f64>> {
+            df.clone()
+                .lazy()
+                .with_columns(
+                    args.statistics(&["price".to_string()], &["ticker".to_string()]),
+                )
+                .collect()
+                .unwrap()
+                .column(name)
+                .unwrap()
+                .f64()
+                .unwrap()
+                .into_iter()
+                .collect()
+        };
+
+        assert_eq!(
+            values(&args, "price_rolling_mean"),
+            [None, Some(2.0), None, Some(5.5), Some(15.0)]
+        );
+        assert_eq!(
+            values(&args, "price_rolling_max"),
+            [None, Some(3.0), None, Some(8.0), Some(20.0)]
+        );
+        let std = values(&args, "price_rolling_std");
+        assert!((std[1].unwrap() - 2f64.sqrt()).abs() < 1e-9);
+
+        args.min_periods = Some(1);
+        assert_eq!(
+            values(&args, "price_rolling_mean"),
+            [Some(1.0), Some(2.0), Some(10.0), Some(5.5), Some(15.0)]
+        );
+    }
+
+    #[test]
+    fn test_rolling_readings_csv() {
+        let args = rolling(2, vec![Operation::Mean]);
+        assert!(args.validate().is_ok());
+        assert!(args.execute(&GlobalArgs::default()).is_ok());
+
+        assert!(rolling(0, vec![Operation::Mean]).validate().is_err());
+        let mut args = rolling(2, vec![Operation::Sum]);
+        args.min_periods = Some(3);
+        assert!(args.validate().is_err());
+    }
+}
@@ -0,0 +1,153 @@
+// Shift columns down (lag) or up (lead) by some rows, optionally within each group.
+use anyhow::{Context, Result, ensure};
+use clap::{Args, ValueHint};
+use polars::prelude::*;
+
+use crate::args::GlobalArgs;
+use crate::io::{resolve_columns, scan_data, write_data};
+
+#[derive(Args, Debug)]
+pub struct ShiftArgs {
+    /// Input table (file or '-' for stdin)
+    #[arg(required = true, value_hint = ValueHint::FilePath)]
+    pub table: String,
+
+    /// Columns to shift (comma separated)
+    ///
+    /// Examples: --column value or --column "temperature,humidity"
+    #[arg(long, required = true, value_delimiter = ',')]
+    pub column: Vec<String>,
+
+    /// Rows to shift by, positive for earlier rows' values (lag) and negative for later
+    /// rows' values (lead)
+    #[arg(long, default_value = "1", allow_negative_numbers = true)]
+    pub periods: i64,
+
+    /// Columns to group by, shifting within each group separately (comma separated)
+    ///
+    /// Examples: --per id
+    #[arg(long, value_delimiter = ',')]
+    pub per: Vec<String>,
+
+    /// Columns to order rows by before shifting (comma separated)
+    ///
+    /// The output keeps this order. Examples: --sort-by timestamp
+    #[arg(long, value_delimiter = ',')]
+    pub sort_by: Vec<String>,
+
+    /// Delimiter for input files
+    #[arg(long, default_value = ",")]
+    pub delimiter: char,
+}
+
+impl ShiftArgs {
+    pub fn validate(&self) -> Result<()> {
+        ensure!(self.periods != 0, "--periods must not be 0");
+
+        Ok(())
+    }
+
+    pub fn execute(&self, global: &GlobalArgs) -> Result<()> {
+        let mut lf =
+            scan_data(&self.table, Some(self.delimiter), global).with_context(|| {
+                format!("shift - failed to read csv data from {}", self.table)
+            })?;
+        let schema = lf.collect_schema()?;
+        let header = DataFrame::empty_with_schema(&schema);
+
+        let columns = resolve_columns(&header, &self.column, global.ignore_column_case)?;
+        let per = resolve_columns(&header, &self.per, global.ignore_column_case)?;
+        let sort_by = resolve_columns(&header, &self.sort_by, global.ignore_column_case)?;
+        if !sort_by.is_empty() {
+            lf = lf.sort(
+                &sort_by,
+                SortMultipleOptions::default().with_maintain_order(true),
+            );
+        }
+        let result = lf
+            .with_columns(self.shifted(&columns, &per))
+            .collect()
+            .with_context(|| {
+                format!("shift - failed to shift columns of {}", self.table)
+            })?;
+
+        write_data(result, global)
+            .with_context(|| "shift - failed to write data to stdout".to_string())?;
+
+        Ok(())
+    }
+
+    /// The `columns` shifted within their `per` group, named `{column}_lag` or `{column}_lead`
+    /// like the `window` operations
+    fn shifted(&self, columns: &[String], per: &[String]) -> Vec<Expr> {
+        let suffix = if self.periods > 0 { "lag" } else { "lead" };
+        columns
+            .iter()
+            .map(|column| {
+                let mut expr = col(column.as_str()).shift(lit(self.periods));
+                if !per.is_empty() {
+                    expr =
+                        expr.over(per.iter().map(|c| col(c.as_str())).collect::<Vec<_>>());
+                }
+                expr.alias(format!("{column}_{suffix}"))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn shift(periods: i64) -> ShiftArgs {
+        ShiftArgs {
+            table: "data/sensors/readings.csv".to_string(),
+            column: vec!["humidity".to_string()],
+            periods,
+            per: vec!["sensor".to_string()],
+            sort_by: vec![],
+            delimiter: ',',
+        }
+    }
+
+    #[test]
+    fn test_shifted() {
+        let df = df!(
+            "id" => ["a", "b", "a", "a", "b"],
+            "value" => [1, 10, 2, 3, 20],
+        )
+        .unwrap();
+        let shifted = |args: ShiftArgs, name: &str| -> Vec<Option<i32>> {
+            df.clone()
+                .lazy()
+                .with_columns(args.shifted(&["value".to_string()], &["id".to_string()]))
+                .collect()
+                .unwrap()
+                .column(name)
+                .unwrap()
+                .i32()
+                .unwrap()
+                .into_iter()
+                .collect()
+        };
+
+        assert_eq!(
+            shifted(shift(1), "value_lag"),
+            [None, None, Some(1), Some(2), Some(10)]
+        );
+        assert_eq!(
+            shifted(shift(-2), "value_lead"),
+            [Some(3), None, None, None, None]
+        );
+    }
+
+    #[test]
+    fn test_shift_readings_csv() {
+        let args = shift(1);
+        assert!(args.validate().is_ok());
+        assert!(args.execute(&GlobalArgs::default()).is_ok());
+
+        assert!(shift(0).validate().is_err());
+    }
+}
@@ -0,0 +1,251 @@
+// Partition a table into one file per group, or into fixed-size chunks (the inverse of `concat`).
+use anyhow::{Context, Result, bail, ensure};
+use clap::{ArgGroup, Args, ValueHint};
+use polars::prelude::*;
+use regex::Regex;
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+use crate::args::GlobalArgs;
+use crate::io::{read_data, resolve_columns, write_csv, write_data};
+
+#[allow(clippy::expect_used)]
+static PLACEHOLDER: std::sync::LazyLock<Regex> = std::sync::LazyLock::new(|| {
+    Regex::new(r"\{([^{}]+)\}").expect("Invalid regex pattern")
+});
+
+#[derive(Args, Debug)]
+#[command(group(ArgGroup::new("mode").required(true).args(["by", "rows"])))]
+pub struct SplitArgs {
+    /// Input table (file or '-' for stdin)
+    #[arg(required = true, value_hint = ValueHint::FilePath)]
+    pub table: String,
+
+    /// Columns whose values decide which file each row goes to (comma separated)
+    ///
+    /// Examples: --by region or --by "region,year"
+    #[arg(long, value_delimiter = ',')]
+    pub by: Vec<String>,
+
+    /// Number of rows per file, to split into fixed-size chunks instead of groups
+    #[arg(long)]
+    pub rows: Option<usize>,
+
+    /// Directory to write the files to (created if it doesn't exist)
+    #[arg(long, default_value = ".", value_hint = ValueHint::DirPath)]
+    pub output_dir: String,
+
+    /// File name template, with `{column}` for a --by column's value and `{n}` for the file number
+    ///
+    /// Defaults to the --by values joined with underscores (e.g., "{region}.csv"), or
+    /// "part_{n}.csv" with --rows. Examples: --template "sales-{region}-{year}.csv"
+    #[arg(long)]
+    pub template: Option<String>,
+
+    /// Delimiter for input files
+    #[arg(long, default_value = ",")]
+    pub delimiter: char,
+}
+
+impl SplitArgs {
+    pub fn validate(&self) -> Result<()> {
+        if self.rows == Some(0) {
+            bail!("--rows must be at least 1");
+        }
+
+        let template = self.template();
+        for placeholder in PLACEHOLDER.captures_iter(&template) {
+            let name = &placeholder[1];
+            ensure!(
+                name == "n" || self.by.iter().any(|c| c == name),
+                "Unknown placeholder '{{{name}}}' in --template. Use {{n}} or one of the --by columns"
+            );
+        }
+        if self.rows.is_some() {
+            ensure!(
+                template.contains("{n}"),
+                "--template must include {{n}} with --rows, so each chunk gets its own file"
+            );
+        }
+
+        Ok(())
+    }
+
+    pub fn execute(&self, global: &GlobalArgs) -> Result<()> {
+        let df =
+            read_data(&self.table, Some(self.delimiter), global).with_context(|| {
+                format!("split - failed to read csv data from {}", self.table)
+            })?;
+
+        let by = resolve_columns(&df, &self.by, global.ignore_column_case)?;
+        let mut parts = match self.rows {
+            Some(rows) => chunks(&df, rows),
+            None => df.partition_by_stable(&by, true).with_context(|| {
+                format!(
+                    "split - failed to group {} by {}",
+                    self.table,
+                    by.join(", ")
+                )
+            })?,
+        };
+
+        fs::create_dir_all(&self.output_dir).with_context(|| {
+            format!("split - failed to create directory {}", self.output_dir)
+        })?;
+
+        let template = self.template();
+        let mut seen = HashSet::new();
+        let mut files = Vec::with_capacity(parts.len());
+        let mut rows = Vec::with_capacity(parts.len());
+        for (idx, part) in parts.iter_mut().enumerate() {
+            let values = self
+                .by
+                .iter()
+                .zip(&by)
+                .map(|(name, column)| Ok((name.as_str(), first_value(part, column)?)))
+                .collect::<Result<Vec<_>>>()?;
+            let name = render(&template, &values, idx + 1);
+            ensure!(
+                seen.insert(name.clone()),
+                "split - --template '{template}' names more than one file '{name}'. Include every --by column or {{n}}"
+            );
+
+            let path = Path::new(&self.output_dir).join(&name);
+            let mut buffer = Vec::new();
+            write_csv(part, &mut buffer, global)?;
+            fs::write(&path, buffer)
+                .with_context(|| format!("split - failed to write {}", path.display()))?;
+
+            files.push(path.display().to_string());
+            rows.push(part.height() as u64);
+        }
+
+        write_data(df!("file" => files, "rows" => rows)?, global)
+            .with_context(|| "split - failed to write data to stdout".to_string())?;
+
+        Ok(())
+    }
+
+    /// The `--template`, or the default for the split mode
+    fn template(&self) -> String {
+        self.template.clone().unwrap_or_else(|| {
+            if self.by.is_empty() {
+                "part_{n}.csv".to_string()
+            } else {
+                let placeholders: Vec<String> =
+                    self.by.iter().map(|c| format!("{{{c}}}")).collect();
+                format!("{}.csv", placeholders.join("_"))
+            }
+        })
+    }
+}
+
+/// Splits `df` into consecutive chunks of at most `rows` rows
+fn chunks(df: &DataFrame, rows: usize) -> Vec<DataFrame> {
+    (0..df.height())
+        .step_by(rows)
+        .map(|offset| df.slice(i64::try_from(offset).unwrap_or(i64::MAX), rows))
+        .collect()
+}
+
+/// The first value of `column` in `df` as text, for naming the file of a group
+fn first_value(df: &DataFrame, column: &str) -> Result<String> {
+    let values = df.column(column)?.cast(&DataType::String)?;
+    Ok(values.str()?.get(0).unwrap_or("null").to_string())
+}
+
+/// Fills in a file name template, replacing characters which aren't safe in file names
+fn render(template: &str, values: &[(&str, String)], n: usize) -> String {
+    PLACEHOLDER
+        .replace_all(template, |caps: &regex::Captures| {
+            let name = &caps[1];
+            if name == "n" {
+                return n.to_string();
+            }
+            values
+                .iter()
+                .find(|(column, _)| *column == name)
+                .map_or_else(String::new, |(_, value)| {
+                    value
+                        .chars()
+                        .map(|c| {
+                            if c.is_control() || r#"/\:*?"<>|"#.contains(c) {
+                                '_'
+                            } else {
+                                c
+                            }
+                        })
+                        .collect()
+                })
+        })
+        .into_owned()
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn args(by: &[&str], rows: Option<usize>, template: Option<&str>) -> SplitArgs {
+        SplitArgs {
+            table: "data/orders/orders.csv".to_string(),
+            by: by.iter().map(ToString::to_string).collect(),
+            rows,
+            output_dir: ".".to_string(),
+            template: template.map(str::to_string),
+            delimiter: ',',
+        }
+    }
+
+    #[test]
+    fn test_validate_template() {
+        assert!(args(&["region"], None, None).validate().is_ok());
+        assert!(args(&[], Some(10), None).validate().is_ok());
+        assert!(
+            args(&["region"], None, Some("{year}.csv"))
+                .validate()
+                .is_err()
+        );
+        assert!(args(&[], Some(10), Some("chunk.csv")).validate().is_err());
+        assert!(args(&[], Some(0), None).validate().is_err());
+    }
+
+    #[test]
+    fn test_render() {
+        let values = [
+            ("region", "north/east".to_string()),
+            ("year", "2024".to_string()),
+        ];
+        assert_eq!(
+            render("{region}-{year}-{n}.csv", &values, 3),
+            "north_east-2024-3.csv"
+        );
+        assert_eq!(
+            args(&["region", "year"], None, None).template(),
+            "{region}_{year}.csv"
+        );
+    }
+
+    #[test]
+    fn test_chunks() {
+        let df = df!("id" => [1, 2, 3, 4, 5]).unwrap();
+        let heights: Vec<usize> = chunks(&df, 2).iter().map(DataFrame::height).collect();
+        assert_eq!(heights, [2, 2, 1]);
+    }
+
+    #[test]
+    fn test_split_orders_csv() {
+        let dir = tempdir().unwrap();
+        let mut args = args(&["customer_id"], None, None);
+        args.output_dir = dir.path().to_string_lossy().to_string();
+
+        assert!(args.validate().is_ok());
+        assert!(args.execute(&GlobalArgs::default()).is_ok());
+
+        let customers = fs::read_to_string(dir.path().join("CUSTOMER-003.csv")).unwrap();
+        assert_eq!(customers.lines().count(), 4);
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 4);
+    }
+}
@@ -0,0 +1,285 @@
+// Bridge between tables and SQLite databases, reading with `from-sqlite` and writing with `--format sqlite`.
+use anyhow::{Context, Result, bail};
+use clap::{ArgGroup, Args, ValueHint};
+use polars::prelude::*;
+use rusqlite::types::Value;
+use rusqlite::{Connection, OpenFlags, params_from_iter};
+use std::fmt::Write;
+use std::path::Path;
+
+use crate::args::GlobalArgs;
+use crate::io::write_data;
+
+#[derive(Args, Debug)]
+#[command(group(ArgGroup::new("source").required(true).args(["table", "query"])))]
+pub struct FromSqliteArgs {
+    /// Sqlite database file
+    #[arg(required = true, value_hint = ValueHint::FilePath)]
+    pub database: String,
+
+    /// Table to read
+    #[arg(long)]
+    pub table: Option<String>,
+
+    /// SQL query to read the results of instead of a table
+    ///
+    /// Examples: --query "SELECT id, amount FROM orders WHERE amount > 100"
+    #[arg(long)]
+    pub query: Option<String>,
+}
+
+impl FromSqliteArgs {
+    pub fn validate(&self) -> Result<()> {
+        if !Path::new(&self.database).is_file() {
+            bail!("Database file does not exist: {}", self.database);
+        }
+
+        Ok(())
+    }
+
+    pub fn execute(&self, global: &GlobalArgs) -> Result<()> {
+        let sql = match (&self.table, &self.query) {
+            (_, Some(query)) => query.clone(),
+            (Some(table), None) => format!("SELECT * FROM {}", quote_identifier(table)),
+            (None, None) => bail!("Either --table or --query is required"),
+        };
+
+        let df = read_query(&self.database, &sql).with_context(|| {
+            format!("from-sqlite - failed to read from {}", self.database)
+        })?;
+
+        write_data(df, global)
+            .with_context(|| "from-sqlite - failed to write data to stdout".to_string())?;
+
+        Ok(())
+    }
+}
+
+/// Runs `sql` against the database at `path` and collects the rows into a `DataFrame`
+///
+/// Sqlite values are dynamically typed, so each column becomes an integer column if
+/// every value is an integer, a float column if every value is numeric and a string
+/// column otherwise.
+pub fn read_query(path: &str, sql: &str) -> Result<DataFrame> {
+    let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    let mut stmt = conn.prepare(sql)?;
+    let names: Vec<String> = stmt.column_names().into_iter().map(String::from).collect();
+
+    let mut values: Vec<Vec<Value>> = vec![Vec::new(); names.len()];
+    let mut rows = stmt.query([])?;
+    while let Some(row) = rows.next()? {
+        for (idx, column) in values.iter_mut().enumerate() {
+            column.push(row.get::<_, Value>(idx)?);
+        }
+    }
+
+    let columns = names
+        .iter()
+        .zip(values)
+        .map(|(name, values)| to_column(name, &values))
+        .collect();
+
+    Ok(DataFrame::new(columns)?)
+}
+
+/// Builds a column from sqlite values using the narrowest type which holds all of them
+fn to_column(name: &str, values: &[Value]) -> Column {
+    let present = || values.iter().filter(|v| !matches!(v, Value::Null));
+
+    if present().all(|v| matches!(v, Value::Integer(_))) {
+        let ints: Vec<Option<i64>> = values
+            .iter()
+            .map(|v| match v {
+                Value::Integer(i) => Some(*i),
+                _ => None,
+            })
+            .collect();
+        return Column::new(name.into(), ints);
+    }
+
+    if present().all(|v| matches!(v, Value::Integer(_) | Value::Real(_))) {
+        #[allow(clippy::cast_precision_loss)]
+        let floats: Vec<Option<f64>> = values
+            .iter()
+            .map(|v| match v {
+                Value::Integer(i) => Some(*i as f64),
+                Value::Real(f) => Some(*f),
+                _ => None,
+            })
+            .collect();
+        return Column::new(name.into(), floats);
+    }
+
+    let strings: Vec<Option<String>> = values
+        .iter()
+        .map(|v| match v {
+            Value::Null => None,
+            Value::Integer(i) => Some(i.to_string()),
+            Value::Real(f) => Some(f.to_string()),
+            Value::Text(s) => Some(s.clone()),
+            Value::Blob(bytes) => Some(bytes.iter().fold(String::new(), |mut hex, b| {
+                let _ = write!(hex, "{b:02x}");
+                hex
+            })),
+        })
+        .collect();
+    Column::new(name.into(), strings)
+}
+
+/// Writes `df` to `table` in the sqlite database at `path`, replacing any existing table
+/// unless `append` adds the rows to it
+///
+/// Integer and boolean columns are stored as INTEGER, floats as REAL and everything
+/// else (including dates) as TEXT.
+pub fn write_table(df: &DataFrame, path: &str, table: &str, append: bool) -> Result<()> {
+    let mut conn = Connection::open(path)
+        .with_context(|| format!("Failed to open SQLite database {path}"))?;
+    let tx = conn.transaction()?;
+
+    let table = quote_identifier(table);
+    let definitions: Vec<String> = df
+        .get_columns()
+        .iter()
+        .map(|column| {
+            format!(
+                "{} {}",
+                quote_identifier(column.name()),
+                affinity(column.dtype())
+            )
+        })
+        .collect();
+    if !append {
+        tx.execute(&format!("DROP TABLE IF EXISTS {table}"), [])?;
+    }
+    tx.execute(
+        &format!(
+            "CREATE TABLE IF NOT EXISTS {table} ({})",
+            definitions.join(", ")
+        ),
+        [],
+    )?;
+
+    let columns = df
+        .get_columns()
+        .iter()
+        .map(to_values)
+        .collect::<Result<Vec<_>>>()?;
+    {
+        let placeholders = vec!["?"; df.width()].join(", ");
+        let mut insert =
+            tx.prepare(&format!("INSERT INTO {table} VALUES ({placeholders})"))?;
+        for row in 0..df.height() {
+            insert.execute(params_from_iter(columns.iter().map(|column| &column[row])))?;
+        }
+    }
+    tx.commit()?;
+
+    Ok(())
+}
+
+/// The type affinity `write_table` stores a column of `dtype` with
+pub fn affinity(dtype: &DataType) -> &'static str {
+    match dtype {
+        dtype if dtype.is_integer() || dtype.is_bool() => "INTEGER",
+        dtype if dtype.is_float() => "REAL",
+        _ => "TEXT",
+    }
+}
+
+/// Converts a column into sqlite values matching the affinity chosen by `write_table`
+fn to_values(column: &Column) -> Result<Vec<Value>> {
+    let values = match column.dtype() {
+        dtype if dtype.is_integer() => column
+            .cast(&DataType::Int64)?
+            .i64()?
+            .into_iter()
+            .map(|v| v.map_or(Value::Null, Value::Integer))
+            .collect(),
+        dtype if dtype.is_bool() => column
+            .bool()?
+            .into_iter()
+            .map(|v| v.map_or(Value::Null, |b| Value::Integer(i64::from(b))))
+            .collect(),
+        dtype if dtype.is_float() => column
+            .cast(&DataType::Float64)?
+            .f64()?
+            .into_iter()
+            .map(|v| v.map_or(Value::Null, Value::Real))
+            .collect(),
+        _ => column
+            .cast(&DataType::String)?
+            .str()?
+            .into_iter()
+            .map(|v| v.map_or(Value::Null, |s| Value::Text(s.to_string())))
+            .collect(),
+    };
+
+    Ok(values)
+}
+
+/// Quotes a table or column name for use in SQL
+pub fn quote_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_write_and_read_table() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("test.sqlite");
+        let path = path.to_str().unwrap();
+        let df = df!(
+            "id" => [1, 2, 3],
+            "name" => [Some("a"), None, Some("c")],
+            "amount" => [1.5, 2.0, 3.25],
+            "active" => [true, false, true],
+        )
+        .unwrap();
+
+        write_table(&df, path, "my table", false).unwrap();
+        // Writing again replaces the table rather than appending to it
+        write_table(&df, path, "my table", false).unwrap();
+
+        let result = read_query(path, "SELECT * FROM \"my table\"").unwrap();
+        assert_eq!(result.height(), 3);
+        assert_eq!(result.column("id").unwrap().dtype(), &DataType::Int64);
+        assert_eq!(result.column("amount").unwrap().dtype(), &DataType::Float64);
+        assert_eq!(result.column("name").unwrap().null_count(), 1);
+        assert_eq!(
+            result.column("active").unwrap().i64().unwrap().get(1),
+            Some(0)
+        );
+
+        write_table(&df, path, "my table", true).unwrap();
+        let result = read_query(path, "SELECT * FROM \"my table\"").unwrap();
+        assert_eq!(result.height(), 6);
+    }
+
+    #[test]
+    fn test_mixed_values_become_strings() {
+        let column = to_column(
+            "mixed",
+            &[Value::Integer(1), Value::Text("two".into()), Value::Null],
+        );
+        assert_eq!(column.dtype(), &DataType::String);
+        assert_eq!(column.str().unwrap().get(0), Some("1"));
+
+        let column = to_column("numbers", &[Value::Integer(1), Value::Real(2.5)]);
+        assert_eq!(column.dtype(), &DataType::Float64);
+    }
+
+    #[test]
+    fn test_validate_missing_database() {
+        let args = FromSqliteArgs {
+            database: "nonexistent.sqlite".to_string(),
+            table: Some("orders".to_string()),
+            query: None,
+        };
+        assert!(args.validate().is_err());
+    }
+}
@@ -0,0 +1,243 @@
+// Recommend a `--dtypes` specification for a table, including any date formats detected.
+use anyhow::{Context, Result};
+use clap::{Args, ValueHint};
+use polars::prelude::*;
+
+use crate::args::GlobalArgs;
+use crate::io::{convert_expr, read_data, write_data, write_text};
+
+/// Date formats tried against string columns, in order of preference
+const DATE_FORMATS: [&str; 5] =
+    ["%Y-%m-%d", "%Y/%m/%d", "%d/%m/%Y", "%m/%d/%Y", "%d.%m.%Y"];
+
+/// Datetime formats tried against string columns, in order of preference
+const DATETIME_FORMATS: [&str; 5] = [
+    "%Y-%m-%dT%H:%M:%S",
+    "%Y-%m-%d %H:%M:%S",
+    "%Y-%m-%dT%H:%M",
+    "%Y-%m-%d %H:%M",
+    "%d/%m/%Y %H:%M:%S",
+];
+
+#[derive(Args, Debug)]
+pub struct SuggestTypesArgs {
+    /// Input table (file or '-' for stdin)
+    #[arg(required = true, value_hint = ValueHint::FilePath)]
+    pub table: String,
+
+    /// Number of rows to scan when detecting date formats (0 scans every row)
+    #[arg(long, default_value = "1000")]
+    pub sample: usize,
+
+    /// Only print the `--dtypes` specification, ready to paste into another command
+    #[arg(long)]
+    pub spec: bool,
+
+    /// Delimiter for input files
+    #[arg(long, default_value = ",")]
+    pub delimiter: char,
+}
+
+impl SuggestTypesArgs {
+    #[allow(clippy::unused_self)]
+    #[allow(clippy::unnecessary_wraps)]
+    pub const fn validate(&self) -> Result<()> {
+        Ok(())
+    }
+
+    pub fn execute(&self, global: &GlobalArgs) -> Result<()> {
+        let data =
+            read_data(&self.table, Some(self.delimiter), global).with_context(|| {
+                format!(
+                    "suggest-types - failed to read csv data from {}",
+                    self.table
+                )
+            })?;
+        let sample = if self.sample == 0 {
+            data
+        } else {
+            data.head(Some(self.sample))
+        };
+
+        let suggestions = sample
+            .get_columns()
+            .iter()
+            .map(|column| suggest(&sample, column))
+            .collect::<Result<Vec<_>>>()?;
+
+        if self.spec {
+            let entries: Vec<String> = suggestions.iter().map(Suggestion::entry).collect();
+            write_text(&entries.join(","), global).with_context(|| {
+                "suggest-types - failed to write data to stdout".to_string()
+            })?;
+            return Ok(());
+        }
+
+        let result = df!(
+            "column" => suggestions.iter().map(|s| s.column.as_str()).collect::<Vec<_>>(),
+            "type" => suggestions.iter().map(|s| s.dtype).collect::<Vec<_>>(),
+            "spec" => suggestions.iter().map(Suggestion::entry).collect::<Vec<_>>(),
+            "example" => suggestions.iter().map(|s| s.example.as_deref()).collect::<Vec<_>>(),
+        )?;
+
+        write_data(result, global).with_context(|| {
+            "suggest-types - failed to write data to stdout".to_string()
+        })?;
+
+        Ok(())
+    }
+}
+
+/// A recommended `--dtypes` entry for one column
+struct Suggestion {
+    column: String,
+    /// One of the `--dtypes` type names
+    dtype: &'static str,
+    /// Detected date or datetime format
+    format: Option<&'static str>,
+    /// First non-null value in the sample
+    example: Option<String>,
+}
+
+impl Suggestion {
+    /// The `column=type[:format]` entry for `--dtypes`
+    fn entry(&self) -> String {
+        self.format.map_or_else(
+            || format!("{}={}", self.column, self.dtype),
+            |format| format!("{}={}:{format}", self.column, self.dtype),
+        )
+    }
+}
+
+/// Suggests a type for `column`, detecting date formats in string columns
+fn suggest(df: &DataFrame, column: &Column) -> Result<Suggestion> {
+    let name = column.name().to_string();
+    let example = column
+        .drop_nulls()
+        .cast(&DataType::String)?
+        .str()?
+        .iter()
+        .flatten()
+        .next()
+        .map(str::to_string);
+
+    let (dtype, format) = match column.dtype() {
+        dtype if dtype.is_integer() => ("int", None),
+        dtype if dtype.is_float() => ("float", None),
+        DataType::Boolean => ("bool", None),
+        DataType::String => detect_format(df, &name)?,
+        _ => ("str", None),
+    };
+
+    Ok(Suggestion {
+        column: name,
+        dtype,
+        format,
+        example,
+    })
+}
+
+/// Finds the first date or datetime format which parses every non-null value of `column`
+pub fn detect_format(
+    df: &DataFrame,
+    column: &str,
+) -> Result<(&'static str, Option<&'static str>)> {
+    let present = df.column(column)?.len() - df.column(column)?.null_count();
+    if present == 0 {
+        return Ok(("str", None));
+    }
+
+    let datetime = DataType::Datetime(TimeUnit::Microseconds, None);
+    let candidates = DATETIME_FORMATS
+        .iter()
+        .map(|format| ("datetime", &datetime, format))
+        .chain(
+            DATE_FORMATS
+                .iter()
+                .map(|format| ("date", &DataType::Date, format)),
+        );
+    for (name, dtype, format) in candidates {
+        let parsed = df
+            .clone()
+            .lazy()
+            .select([
+                convert_expr(column, &DataType::String, dtype, Some(format), false)
+                    .null_count(),
+            ])
+            .collect()?;
+        let nulls = parsed.get_columns()[0].u32()?.get(0).unwrap_or_default() as usize;
+        if df.height() - nulls == present {
+            return Ok((name, Some(format)));
+        }
+    }
+
+    Ok(("str", None))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_format() {
+        let df = df!(
+            "day" => [Some("03/01/2024"), None, Some("25/12/2024")],
+            "stamp" => ["2024-01-03 10:00:00", "2024-01-04 11:30:00", "2024-01-05 09:15:00"],
+            "name" => ["a", "b", "c"],
+        )
+        .unwrap();
+
+        assert_eq!(
+            detect_format(&df, "day").unwrap(),
+            ("date", Some("%d/%m/%Y"))
+        );
+        assert_eq!(
+            detect_format(&df, "stamp").unwrap(),
+            ("datetime", Some("%Y-%m-%d %H:%M:%S"))
+        );
+        assert_eq!(detect_format(&df, "name").unwrap(), ("str", None));
+    }
+
+    #[test]
+    fn test_suggestion_entry() {
+        let df = df!("id" => [1, 2], "day" => ["2024-01-03", "2024-01-04"]).unwrap();
+        let entries: Vec<String> = df
+            .get_columns()
+            .iter()
+            .map(|column| suggest(&df, column).unwrap().entry())
+            .collect();
+
+        assert_eq!(entries, ["id=int", "day=date:%Y-%m-%d"]);
+    }
+
+    #[test]
+    fn test_suggest_empty_columns() {
+        let df = df!(
+            "id" => [1, 2],
+            "note" => [None::<&str>, None],
+        )
+        .unwrap();
+        let note = suggest(&df, df.column("note").unwrap()).unwrap();
+        assert_eq!(note.entry(), "note=str");
+        assert_eq!(note.example, None);
+
+        let df = df.head(Some(0));
+        let id = suggest(&df, df.column("id").unwrap()).unwrap();
+        assert_eq!(id.entry(), "id=int");
+        assert_eq!(id.example, None);
+    }
+
+    #[test]
+    fn test_suggest_types_orders_csv() {
+        let args = SuggestTypesArgs {
+            table: "data/orders/orders.csv".to_string(),
+            sample: 1000,
+            spec: true,
+            delimiter: ',',
+        };
+
+        assert!(args.validate().is_ok());
+        assert!(args.execute(&GlobalArgs::default()).is_ok());
+    }
+}
@@ -1,43 +1,183 @@
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, ensure};
 use clap::{Args, ValueHint};
+use log::warn;
+use polars::prelude::*;
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom};
+use std::thread;
+use std::time::Duration;
 
-use crate::args::OutputFormat;
-use crate::io::{read_data, write_data};
+use crate::args::GlobalArgs;
+use crate::io::{FILE_COLUMN, parse_rows, read_data, write_files, write_rows};
 
 #[derive(Args, Debug)]
 pub struct TailArgs {
-    /// Input table (file or '-' for stdin)
+    /// Input tables (files or '-' for stdin), each shown under a `==> file <==` header
+    ///
+    /// Examples: january.csv february.csv march.csv
     #[arg(required = true, value_hint = ValueHint::FilePath)]
-    pub table: String,
+    pub tables: Vec<String>,
 
     /// Number of lines to display from the end
     #[arg(short, long, default_value = "5")]
     pub n: usize,
+
+    /// Stack the files into one table with a leading `__file` column instead of headers
+    #[arg(long)]
+    pub file_column: bool,
+
+    /// Keep printing rows as they're appended to the file, until interrupted
+    #[arg(short, long)]
+    pub follow: bool,
+
+    /// Seconds to wait between checks for new rows with --follow
+    #[arg(long, default_value = "1", requires = "follow")]
+    pub interval: f64,
 }
 
 impl TailArgs {
-    #[allow(clippy::unused_self)]
-    #[allow(clippy::unnecessary_wraps)]
-    pub const fn validate(&self) -> Result<()> {
+    pub fn validate(&self) -> Result<()> {
+        ensure!(
+            self.tables.iter().filter(|t| *t == "-").count() <= 1,
+            "stdin ('-') can only be used for one table"
+        );
+        if self.follow {
+            ensure!(
+                self.tables.len() == 1 && self.tables[0] != "-",
+                "--follow needs a single file (not stdin)"
+            );
+            ensure!(
+                self.interval.is_finite() && self.interval > 0.0,
+                "--interval must be a positive number of seconds"
+            );
+        }
+
         Ok(())
     }
 
     #[allow(clippy::expect_used)]
-    pub fn execute(&self, format: &OutputFormat) -> Result<()> {
+    pub fn execute(&self, global: &GlobalArgs) -> Result<()> {
         // TODO: Update read_data to use a circular buffer for better performance
-        let data = read_data(self.table.as_str(), Some(',')).with_context(|| {
-            format!("tail - failed to read csv data from {}", self.table)
-        })?;
-
-        let tail_data = data.tail(Some(self.n));
+        let parts = self
+            .tables
+            .iter()
+            .map(|table| {
+                let data = read_data(table, Some(','), global).with_context(|| {
+                    format!("tail - failed to read csv data from {table}")
+                })?;
+                Ok((table.clone(), data.tail(Some(self.n))))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        // Rows appended after this are followed, parsed like the rows already read
+        let follower = if self.follow {
+            ensure!(
+                global.output.is_none(),
+                "tail - --follow writes to stdout, so it can't be combined with --output"
+            );
+            let table = &self.tables[0];
+            Some(
+                Follower::new(table, parts[0].1.schema().clone())
+                    .with_context(|| format!("tail - failed to follow {table}"))?,
+            )
+        } else {
+            None
+        };
 
-        write_data(tail_data, format)
+        let mut written = parts.iter().map(|(_, data)| data.height()).sum::<usize>();
+        write_files(parts, self.file_column, global)
             .with_context(|| "tail - failed to write csv data to stdout".to_string())?;
 
+        if let Some(mut follower) = follower {
+            let interval = Duration::from_secs_f64(self.interval);
+            loop {
+                thread::sleep(interval);
+                let Some(lines) = follower.poll().with_context(|| {
+                    format!("tail - failed to follow {}", follower.path)
+                })?
+                else {
+                    continue;
+                };
+                let mut rows =
+                    parse_rows(lines, follower.schema.clone(), Some(','), global)
+                        .with_context(|| {
+                            format!(
+                                "tail - failed to parse rows appended to {}",
+                                follower.path
+                            )
+                        })?;
+                if self.file_column {
+                    let file = Column::new_scalar(
+                        FILE_COLUMN.into(),
+                        Scalar::from(PlSmallStr::from(follower.path.as_str())),
+                        rows.height(),
+                    );
+                    rows.insert_column(0, file)?;
+                }
+                let height = rows.height();
+                write_rows(rows, written, global).with_context(|| {
+                    "tail - failed to write csv data to stdout".to_string()
+                })?;
+                written += height;
+            }
+        }
+
         Ok(())
     }
 }
 
+/// Watches a file for appended lines, e.g. a log being written to
+struct Follower {
+    path: String,
+    /// Columns of the file when it was first read, which appended rows are parsed into
+    schema: SchemaRef,
+    /// Bytes of the file already read
+    offset: u64,
+    /// The end of a line that's still being written
+    pending: Vec<u8>,
+}
+
+impl Follower {
+    /// Follows `path` from its current end
+    fn new(path: &str, schema: SchemaRef) -> Result<Self> {
+        Ok(Self {
+            path: path.to_string(),
+            schema,
+            offset: fs::metadata(path)?.len(),
+            pending: Vec::new(),
+        })
+    }
+
+    /// The complete lines appended since the last poll, if any
+    ///
+    /// If the file shrank (e.g. it was truncated or rotated), following restarts from its
+    /// new end.
+    fn poll(&mut self) -> Result<Option<String>> {
+        let len = fs::metadata(&self.path)?.len();
+        if len < self.offset {
+            warn!("{} was truncated, following from its new end", self.path);
+            self.offset = len;
+            self.pending.clear();
+        }
+        if len == self.offset {
+            return Ok(None);
+        }
+
+        let mut file = File::open(&self.path)?;
+        file.seek(SeekFrom::Start(self.offset))?;
+        file.take(len - self.offset)
+            .read_to_end(&mut self.pending)?;
+        self.offset = len;
+
+        let Some(end) = self.pending.iter().rposition(|b| *b == b'\n') else {
+            return Ok(None);
+        };
+        let lines: Vec<u8> = self.pending.drain(..=end).collect();
+        let lines = String::from_utf8_lossy(&lines).into_owned();
+
+        Ok((!lines.trim().is_empty()).then_some(lines))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -45,8 +185,11 @@ mod tests {
     #[test]
     fn test_validate_always_succeeds() {
         let args = TailArgs {
-            table: "test.csv".to_string(),
+            tables: vec!["test.csv".to_string()],
             n: 5,
+            file_column: false,
+            follow: false,
+            interval: 1.0,
         };
         assert!(args.validate().is_ok());
     }
@@ -56,21 +199,83 @@ mod tests {
     #[allow(clippy::unwrap_used)]
     fn test_tail_nonexistent_file_panics() {
         let args = TailArgs {
-            table: "nonexistent_file.csv".to_string(),
+            tables: vec!["nonexistent_file.csv".to_string()],
             n: 5,
+            file_column: false,
+            follow: false,
+            interval: 1.0,
         };
 
-        args.execute(&crate::args::OutputFormat::Auto).unwrap();
+        args.execute(&GlobalArgs::default()).unwrap();
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn test_follower() {
+        use std::io::Write;
+
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(file, "id,name\n1,a").unwrap();
+        let path = file.path().to_str().unwrap();
+        let schema = read_data(path, Some(','), &GlobalArgs::default())
+            .unwrap()
+            .schema()
+            .clone();
+        let mut follower = Follower::new(path, schema).unwrap();
+        assert_eq!(follower.poll().unwrap(), None);
+
+        write!(file, "2,b\n3,").unwrap();
+        let lines = follower.poll().unwrap().unwrap();
+        assert_eq!(lines, "2,b\n");
+        writeln!(file, "c").unwrap();
+        let lines = follower.poll().unwrap().unwrap();
+        assert_eq!(lines, "3,c\n");
+
+        let rows = parse_rows(
+            lines,
+            follower.schema.clone(),
+            Some(','),
+            &GlobalArgs::default(),
+        )
+        .unwrap();
+        assert_eq!(rows.column("id").unwrap().i64().unwrap().get(0), Some(3));
+
+        file.as_file().set_len(0).unwrap();
+        assert_eq!(follower.poll().unwrap(), None);
+    }
+
+    #[test]
+    fn test_validate_follow() {
+        let mut args = TailArgs {
+            tables: vec!["a.csv".to_string(), "b.csv".to_string()],
+            n: 5,
+            file_column: false,
+            follow: true,
+            interval: 1.0,
+        };
+        assert!(args.validate().is_err());
+
+        args.tables = vec!["-".to_string()];
+        assert!(args.validate().is_err());
+
+        args.tables = vec!["a.csv".to_string()];
+        assert!(args.validate().is_ok());
+
+        args.interval = 0.0;
+        assert!(args.validate().is_err());
     }
 
     #[test]
     fn test_tail_orders_csv() {
         let args = TailArgs {
-            table: "data/orders/orders.csv".to_string(),
+            tables: vec!["data/orders/orders.csv".to_string()],
             n: 2,
+            file_column: false,
+            follow: false,
+            interval: 1.0,
         };
 
         assert!(args.validate().is_ok());
-        assert!(args.execute(&crate::args::OutputFormat::Auto).is_ok());
+        assert!(args.execute(&GlobalArgs::default()).is_ok());
     }
 }
@@ -0,0 +1,208 @@
+// Time spent in each stage of a command and its memory use, for --timings and --memory-limit.
+use log::warn;
+use std::io::{self, Write};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::exit::Failure;
+
+/// A stage of a command, timed by wrapping it in `timed`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    /// Reading input files (and collecting Arrow, Parquet and JSON tables)
+    Read,
+    /// Decoding input text and parsing it as CSV
+    Parse,
+    /// Writing the output
+    Write,
+}
+
+/// Time spent in each stage so far, indexed by `Stage as usize`
+static SPENT: Mutex<[Duration; 3]> = Mutex::new([Duration::ZERO; 3]);
+
+/// Runs `f`, adding how long it took to the time spent in `stage`
+pub fn timed<T>(stage: Stage, f: impl FnOnce() -> T) -> T {
+    let start = Instant::now();
+    let result = f();
+    if let Ok(mut spent) = SPENT.lock() {
+        spent[stage as usize] += start.elapsed();
+    }
+    result
+}
+
+/// Prints the time spent in each stage and the peak memory use to stderr
+///
+/// Whatever `total` wasn't spent reading, parsing or writing is reported as compute, which
+/// includes reading tables that are scanned lazily (e.g., by `query`) as their scans run
+/// within the query.
+pub fn report(total: Duration) {
+    let [read, parse, write] = SPENT.lock().map(|spent| *spent).unwrap_or_default();
+    let compute = total.saturating_sub(read + parse + write);
+
+    eprintln!("rabbet timings:");
+    for (stage, duration) in [
+        ("read", read),
+        ("parse", parse),
+        ("compute", compute),
+        ("write", write),
+        ("total", total),
+    ] {
+        eprintln!("  {stage:<12}{duration:>10.2?}");
+    }
+    if let Some(kib) = peak_rss_kib(Usage::Process) {
+        eprintln!("  {:<12}{:>10}", "peak memory", format_size(kib * 1024));
+    }
+}
+
+/// Exits with an error as soon as the peak memory use passes `limit` bytes
+///
+/// The memory use is checked every few milliseconds on a background thread, so this stops
+/// runaway commands before the system runs out of memory rather than guaranteeing a bound.
+/// The command can't be unwound from that thread, so it flushes whatever output was
+/// already written and exits with `Failure::MemoryLimit`'s code itself.
+pub fn limit_memory(limit: u64) {
+    if peak_rss_kib(Usage::Process).is_none() {
+        warn!("--memory-limit isn't supported on this platform, ignoring it");
+        return;
+    }
+
+    thread::spawn(move || {
+        loop {
+            let used = peak_rss_kib(Usage::Process).unwrap_or_default() * 1024;
+            if used > limit {
+                eprintln!(
+                    "Error: rabbet used {} of memory, more than --memory-limit {}. Pass --low-memory, or select fewer columns and filter rows earlier (e.g., in a query)",
+                    format_size(used),
+                    format_size(limit)
+                );
+                let _ = io::stdout().flush();
+                std::process::exit(i32::from(Failure::MemoryLimit.code()));
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+    });
+}
+
+/// Parses a size in bytes, with an optional K, M, G or T suffix for powers of 1024
+///
+/// Suffixes may be followed by `B` or `iB` (e.g., `512MB` or `2GiB`), and the number may have
+/// a fraction (e.g., `1.5G`).
+pub fn parse_size(size: &str) -> Result<u64, String> {
+    let upper = size.trim().to_ascii_uppercase();
+    let digits = upper.trim_end_matches("IB").trim_end_matches('B');
+    let (number, power) = match digits.char_indices().last() {
+        Some((i, 'K')) => (&digits[..i], 1),
+        Some((i, 'M')) => (&digits[..i], 2),
+        Some((i, 'G')) => (&digits[..i], 3),
+        Some((i, 'T')) => (&digits[..i], 4),
+        _ => (digits, 0),
+    };
+    let number: f64 = number
+        .trim()
+        .parse()
+        .ok()
+        .filter(|n: &f64| n.is_finite() && *n > 0.0)
+        .ok_or_else(|| {
+            format!("invalid size '{size}', expected e.g. 2G, 512M or 1048576")
+        })?;
+
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss,
+        clippy::cast_precision_loss
+    )]
+    Ok((number * 1024f64.powi(power)) as u64)
+}
+
+/// Formats a size in bytes with a binary unit, e.g. `1.5GiB`
+#[allow(clippy::cast_precision_loss)]
+fn format_size(bytes: u64) -> String {
+    let mut size = bytes as f64;
+    for unit in ["B", "KiB", "MiB", "GiB"] {
+        if size < 1024.0 {
+            return format!("{size:.1}{unit}");
+        }
+        size /= 1024.0;
+    }
+    format!("{size:.1}TiB")
+}
+
+/// Whose resource usage to look up
+#[derive(Debug, Clone, Copy)]
+pub enum Usage {
+    /// This process
+    Process,
+    /// Finished child processes, e.g. the runs of `bench`
+    Children,
+}
+
+/// Returns the largest resident set size of `who` in KiB
+#[cfg(unix)]
+pub fn peak_rss_kib(who: Usage) -> Option<u64> {
+    let who = match who {
+        Usage::Process => libc::RUSAGE_SELF,
+        Usage::Children => libc::RUSAGE_CHILDREN,
+    };
+    let mut usage = std::mem::MaybeUninit::<libc::rusage>::zeroed();
+    // SAFETY: `usage` is a valid, writable `rusage` for getrusage to fill in
+    let usage = unsafe {
+        if libc::getrusage(who, usage.as_mut_ptr()) != 0 {
+            return None;
+        }
+        usage.assume_init()
+    };
+
+    let max_rss = u64::try_from(usage.ru_maxrss).ok()?;
+    // macOS reports bytes while Linux and the BSDs report KiB
+    if cfg!(target_os = "macos") {
+        Some(max_rss / 1024)
+    } else {
+        Some(max_rss)
+    }
+}
+
+#[cfg(not(unix))]
+pub const fn peak_rss_kib(_who: Usage) -> Option<u64> {
+    None
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timed() {
+        let before = SPENT.lock().unwrap()[Stage::Parse as usize];
+        let value = timed(Stage::Parse, || {
+            std::thread::sleep(Duration::from_millis(5));
+            42
+        });
+        assert_eq!(value, 42);
+        assert!(
+            SPENT.lock().unwrap()[Stage::Parse as usize]
+                >= before + Duration::from_millis(5)
+        );
+
+        #[cfg(unix)]
+        assert!(peak_rss_kib(Usage::Process).is_some_and(|kib| kib > 0));
+    }
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("1048576"), Ok(1_048_576));
+        assert_eq!(parse_size("512K"), Ok(512 * 1024));
+        assert_eq!(parse_size("2G"), Ok(2 * 1024 * 1024 * 1024));
+        assert_eq!(parse_size("2gib"), Ok(2 * 1024 * 1024 * 1024));
+        assert_eq!(parse_size("1.5MB"), Ok(3 * 512 * 1024));
+        assert_eq!(parse_size(" 1T "), Ok(1024_u64.pow(4)));
+        assert!(parse_size("").is_err());
+        assert!(parse_size("G").is_err());
+        assert!(parse_size("-1G").is_err());
+        assert!(parse_size("2X").is_err());
+
+        assert_eq!(format_size(512), "512.0B");
+        assert_eq!(format_size(3 * 512 * 1024), "1.5MiB");
+    }
+}
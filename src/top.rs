@@ -0,0 +1,166 @@
+// The k largest (or smallest) rows of a table by some columns, optionally within each group.
+use anyhow::{Context, Result};
+use clap::{Args, ValueHint};
+use polars::prelude::*;
+
+use crate::args::GlobalArgs;
+use crate::io::{resolve_columns, scan_data, write_data};
+
+#[derive(Args, Debug)]
+pub struct TopArgs {
+    /// Input table (file or '-' for stdin)
+    #[arg(required = true, value_hint = ValueHint::FilePath)]
+    pub table: String,
+
+    /// Number of rows to return (per group with --per)
+    #[arg(short, default_value = "10")]
+    pub k: usize,
+
+    /// Columns to rank rows by, with later columns breaking ties (comma separated)
+    ///
+    /// Examples: --by revenue or --by "revenue,units"
+    #[arg(long, required = true, value_delimiter = ',')]
+    pub by: Vec<String>,
+
+    /// Columns to group by, returning the top rows of each group (comma separated)
+    ///
+    /// Examples: --per region
+    #[arg(long, value_delimiter = ',')]
+    pub per: Vec<String>,
+
+    /// Return the smallest rows instead of the largest
+    #[arg(long)]
+    pub smallest: bool,
+
+    /// Delimiter for input files
+    #[arg(long, default_value = ",")]
+    pub delimiter: char,
+}
+
+impl TopArgs {
+    #[allow(clippy::unused_self)]
+    #[allow(clippy::unnecessary_wraps)]
+    pub const fn validate(&self) -> Result<()> {
+        Ok(())
+    }
+
+    pub fn execute(&self, global: &GlobalArgs) -> Result<()> {
+        let mut lf =
+            scan_data(&self.table, Some(self.delimiter), global).with_context(|| {
+                format!("top - failed to read csv data from {}", self.table)
+            })?;
+        let schema = lf.collect_schema()?;
+        let header = DataFrame::empty_with_schema(&schema);
+
+        let by = resolve_columns(&header, &self.by, global.ignore_column_case)?;
+        let per = resolve_columns(&header, &self.per, global.ignore_column_case)?;
+        let result = top(lf, &by, &per, self.k, self.smallest)?
+            .select(
+                header
+                    .get_columns()
+                    .iter()
+                    .map(|c| col(c.name().clone()))
+                    .collect::<Vec<_>>(),
+            )
+            .collect()
+            .with_context(|| format!("top - failed to rank rows of {}", self.table))?;
+
+        write_data(result, global)
+            .with_context(|| "top - failed to write data to stdout".to_string())?;
+
+        Ok(())
+    }
+}
+
+/// Keeps the `k` largest (or smallest) rows by `by`, within each `per` group if given
+///
+/// Nulls rank last. Rows come out in rank order, grouped by first appearance in that
+/// order when `per` is given, and ties keep their input order.
+fn top(
+    lf: LazyFrame,
+    by: &[String],
+    per: &[String],
+    k: usize,
+    smallest: bool,
+) -> Result<LazyFrame> {
+    let by: Vec<Expr> = by.iter().map(|c| col(c.as_str())).collect();
+    let options = SortMultipleOptions::default().with_maintain_order(true);
+
+    if per.is_empty() {
+        let k = IdxSize::try_from(k)?;
+        return Ok(if smallest {
+            lf.bottom_k(k, by, options)
+        } else {
+            lf.top_k(k, by, options)
+        });
+    }
+
+    let per: Vec<Expr> = per.iter().map(|c| col(c.as_str())).collect();
+    Ok(lf
+        .sort_by_exprs(
+            by,
+            options
+                .with_order_descending(!smallest)
+                .with_nulls_last(true),
+        )
+        .group_by_stable(per)
+        .head(Some(k)))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn values(df: &DataFrame, column: &str) -> Vec<Option<i32>> {
+        df.column(column)
+            .unwrap()
+            .i32()
+            .unwrap()
+            .into_iter()
+            .collect()
+    }
+
+    #[test]
+    fn test_top() {
+        let df = df!(
+            "region" => ["north", "south", "north", "south", "north"],
+            "amount" => [Some(5), Some(7), None, Some(1), Some(9)],
+        )
+        .unwrap();
+        let by = ["amount".to_string()];
+
+        let result = top(df.clone().lazy(), &by, &[], 2, false)
+            .unwrap()
+            .collect()
+            .unwrap();
+        assert_eq!(values(&result, "amount"), [Some(9), Some(7)]);
+
+        let result = top(df.clone().lazy(), &by, &[], 2, true)
+            .unwrap()
+            .collect()
+            .unwrap();
+        assert_eq!(values(&result, "amount"), [Some(1), Some(5)]);
+
+        let result = top(df.lazy(), &by, &["region".to_string()], 1, false)
+            .unwrap()
+            .collect()
+            .unwrap();
+        assert_eq!(values(&result, "amount"), [Some(9), Some(7)]);
+    }
+
+    #[test]
+    fn test_top_orders_csv() {
+        let args = TopArgs {
+            table: "data/orders/orders.csv".to_string(),
+            k: 2,
+            by: vec!["price".to_string()],
+            per: vec!["customer_id".to_string()],
+            smallest: false,
+            delimiter: ',',
+        };
+
+        assert!(args.validate().is_ok());
+        assert!(args.execute(&GlobalArgs::default()).is_ok());
+    }
+}
@@ -0,0 +1,286 @@
+// Parse a column of JSON objects and turn the requested fields into new columns.
+use anyhow::{Context, Result, bail, ensure};
+use clap::{Args, ValueHint};
+use itertools::Itertools;
+use polars::prelude::*;
+use serde_json::Value;
+
+use crate::args::GlobalArgs;
+use crate::io::{read_data, resolve_columns, write_data};
+
+#[derive(Args, Debug)]
+pub struct UnpackArgs {
+    /// Input table (file or '-' for stdin)
+    #[arg(required = true, value_hint = ValueHint::FilePath)]
+    pub table: String,
+
+    /// String column holding a JSON object in each row
+    #[arg(long, required = true)]
+    pub column: String,
+
+    /// Fields to unpack as dotted paths into the objects (comma separated)
+    ///
+    /// Defaults to every top level field. Numbers index into arrays.
+    /// Examples: --fields "user.id,user.plan" or --fields "items.0.sku"
+    #[arg(long, value_delimiter = ',')]
+    pub fields: Vec<String>,
+
+    /// Names of the new columns, one per field (comma separated)
+    ///
+    /// Defaults to the field paths with `.` replaced by `_`, e.g. `user_id`.
+    #[arg(long, value_delimiter = ',', requires = "fields")]
+    pub into: Vec<String>,
+
+    /// Drop the JSON column once it's unpacked
+    #[arg(long)]
+    pub drop_column: bool,
+
+    /// Delimiter for input files
+    #[arg(long, default_value = ",")]
+    pub delimiter: char,
+}
+
+impl UnpackArgs {
+    pub fn validate(&self) -> Result<()> {
+        for field in &self.fields {
+            ensure!(
+                !field.is_empty() && field.split('.').all(|key| !key.is_empty()),
+                "Invalid field '{field}'. Use dotted paths like 'user.id'"
+            );
+        }
+        if !self.into.is_empty() && self.into.len() != self.fields.len() {
+            bail!(
+                "--into names {} column(s) but --fields lists {} field(s)",
+                self.into.len(),
+                self.fields.len()
+            );
+        }
+
+        Ok(())
+    }
+
+    pub fn execute(&self, global: &GlobalArgs) -> Result<()> {
+        let df =
+            read_data(&self.table, Some(self.delimiter), global).with_context(|| {
+                format!("unpack - failed to read csv data from {}", self.table)
+            })?;
+
+        let column = resolve_columns(
+            &df,
+            std::slice::from_ref(&self.column),
+            global.ignore_column_case,
+        )?
+        .remove(0);
+        let result = self.unpack(df, &column).with_context(|| {
+            format!("unpack - failed to unpack '{column}' in {}", self.table)
+        })?;
+
+        write_data(result, global)
+            .with_context(|| "unpack - failed to write data to stdout".to_string())?;
+
+        Ok(())
+    }
+
+    /// Adds a column for each field of the JSON objects in `column`
+    ///
+    /// Rows which are null or blank, or which lack a field, get nulls.
+    fn unpack(&self, mut df: DataFrame, column: &str) -> Result<DataFrame> {
+        let dtype = df.column(column)?.dtype();
+        ensure!(
+            dtype == &DataType::String,
+            "Column '{column}' must be a string column of JSON, found {dtype}"
+        );
+
+        let objects = df
+            .column(column)?
+            .str()?
+            .into_iter()
+            .enumerate()
+            .map(|(idx, text)| match text.map(str::trim) {
+                None | Some("") => Ok(Value::Null),
+                Some(text) => serde_json::from_str(text).with_context(|| {
+                    format!("Row {} of '{column}' isn't valid JSON: {text}", idx + 1)
+                }),
+            })
+            .collect::<Result<Vec<Value>>>()?;
+
+        let fields = if self.fields.is_empty() {
+            top_level_fields(&objects)
+        } else {
+            self.fields.clone()
+        };
+        ensure!(
+            !fields.is_empty(),
+            "Column '{column}' has no JSON objects to unpack"
+        );
+        let names: Vec<String> = if self.into.is_empty() {
+            fields.iter().map(|field| field.replace('.', "_")).collect()
+        } else {
+            self.into.clone()
+        };
+
+        if self.drop_column {
+            df = df.drop(column)?;
+        }
+        for (field, name) in fields.iter().zip(&names) {
+            ensure!(
+                df.column(name).is_err(),
+                "Column '{name}' for field '{field}' already exists. Pick another name with --into"
+            );
+            let values: Vec<&Value> =
+                objects.iter().map(|object| lookup(object, field)).collect();
+            df.with_column(to_column(name, &values))?;
+        }
+
+        Ok(df)
+    }
+}
+
+/// The top level keys of the JSON objects, in the order they first appear
+fn top_level_fields(objects: &[Value]) -> Vec<String> {
+    objects
+        .iter()
+        .filter_map(Value::as_object)
+        .flat_map(|object| object.keys().cloned())
+        .unique()
+        .collect()
+}
+
+/// The value at the dotted `path` in `value`, or null if any part of it is missing
+fn lookup<'a>(value: &'a Value, path: &str) -> &'a Value {
+    path.split('.')
+        .try_fold(value, |value, key| match value {
+            Value::Object(object) => object.get(key),
+            Value::Array(items) => key.parse::<usize>().ok().and_then(|idx| items.get(idx)),
+            _ => None,
+        })
+        .unwrap_or(&Value::Null)
+}
+
+/// A column of `values`, typed as booleans, integers or floats when every value is one,
+/// and otherwise as text with nested objects and arrays kept as JSON
+fn to_column(name: &str, values: &[&Value]) -> Column {
+    let present = || values.iter().filter(|value| !value.is_null());
+    let series = if present().all(|value| value.is_boolean()) {
+        Series::new(
+            name.into(),
+            values
+                .iter()
+                .map(|value| value.as_bool())
+                .collect::<Vec<_>>(),
+        )
+    } else if present().all(|value| value.is_i64()) {
+        Series::new(
+            name.into(),
+            values
+                .iter()
+                .map(|value| value.as_i64())
+                .collect::<Vec<_>>(),
+        )
+    } else if present().all(|value| value.is_number()) {
+        Series::new(
+            name.into(),
+            values
+                .iter()
+                .map(|value| value.as_f64())
+                .collect::<Vec<_>>(),
+        )
+    } else {
+        let text: Vec<Option<String>> = values
+            .iter()
+            .map(|value| match value {
+                Value::Null => None,
+                Value::String(text) => Some(text.clone()),
+                other => Some(other.to_string()),
+            })
+            .collect();
+        Series::new(name.into(), text)
+    };
+
+    series.into()
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn args(fields: &[&str], into: &[&str]) -> UnpackArgs {
+        UnpackArgs {
+            table: "data/web/events.csv".to_string(),
+            column: "payload".to_string(),
+            fields: fields.iter().map(ToString::to_string).collect(),
+            into: into.iter().map(ToString::to_string).collect(),
+            drop_column: false,
+            delimiter: ',',
+        }
+    }
+
+    fn events() -> DataFrame {
+        df!(
+            "id" => [1, 2, 3, 4],
+            "payload" => [
+                Some(r#"{"user": {"id": 7, "plan": "pro"}, "tags": ["a", "b"]}"#),
+                Some(r#"{"user": {"id": 9}, "score": 1.5}"#),
+                None,
+                Some(" "),
+            ],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_unpack() {
+        let df = args(&["user.id", "user.plan", "tags.1"], &[])
+            .unpack(events(), "payload")
+            .unwrap();
+        let ids = df.column("user_id").unwrap();
+        assert_eq!(ids.dtype(), &DataType::Int64);
+        assert_eq!(ids.null_count(), 2);
+        let plans: Vec<Option<&str>> = df
+            .column("user_plan")
+            .unwrap()
+            .str()
+            .unwrap()
+            .into_iter()
+            .collect();
+        assert_eq!(plans, [Some("pro"), None, None, None]);
+        assert_eq!(
+            df.column("tags_1").unwrap().str().unwrap().get(0),
+            Some("b")
+        );
+
+        let mut args = args(&[], &[]);
+        args.drop_column = true;
+        let df = args.unpack(events(), "payload").unwrap();
+        assert_eq!(df.get_column_names_str(), ["id", "user", "tags", "score"]);
+        assert_eq!(
+            df.column("user").unwrap().str().unwrap().get(0),
+            Some(r#"{"id":7,"plan":"pro"}"#)
+        );
+        assert_eq!(df.column("score").unwrap().dtype(), &DataType::Float64);
+    }
+
+    #[test]
+    fn test_unpack_errors() {
+        assert!(args(&["id"], &[]).unpack(events(), "payload").is_err());
+
+        let invalid = df!("payload" => [r#"{"user": "#]).unwrap();
+        let err = args(&[], &[]).unpack(invalid, "payload").unwrap_err();
+        assert!(err.to_string().contains("Row 1"));
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(args(&["user.id"], &["user_id"]).validate().is_ok());
+        assert!(args(&["user.id"], &["a", "b"]).validate().is_err());
+        assert!(args(&["user..id"], &[]).validate().is_err());
+    }
+
+    #[test]
+    fn test_unpack_events_csv() {
+        let args = args(&["user.id", "user.plan"], &[]);
+        assert!(args.validate().is_ok());
+        assert!(args.execute(&GlobalArgs::default()).is_ok());
+    }
+}
@@ -0,0 +1,404 @@
+// Check a table against a schema contract, e.g. as a data-quality gate in CI.
+use anyhow::{Context, Result, anyhow, bail, ensure};
+use clap::{ArgGroup, Args, ValueHint};
+use polars::prelude::*;
+use serde_json::Value;
+use std::fs;
+
+use crate::args::GlobalArgs;
+use crate::exit::Failure;
+use crate::io::{DTYPE_NAMES, read_data, resolve_columns, write_data};
+
+/// Maximum number of row numbers listed for each failed check
+const ROW_LIMIT: usize = 10;
+
+#[derive(Args, Debug)]
+#[command(group(ArgGroup::new("rules").required(true).multiple(true).args(["schema", "require"])))]
+pub struct ValidateArgs {
+    /// Input table (file or '-' for stdin)
+    #[arg(required = true, value_hint = ValueHint::FilePath)]
+    pub table: String,
+
+    /// JSON schema file with a rule for each column
+    ///
+    /// Example: {"columns": [{"name": "id", "type": "int", "unique": true}]}
+    #[arg(long, value_hint = ValueHint::FilePath)]
+    pub schema: Option<String>,
+
+    /// Inline column rule as `column[:type][:check...]` (repeatable)
+    ///
+    /// Checks: `not_null`, unique, min=<value> and max=<value>.
+    /// Examples: --require "`id:int:unique:not_null`" --require "`price:float:min=0`"
+    #[arg(long)]
+    pub require: Vec<String>,
+
+    /// Delimiter for input files
+    #[arg(long, default_value = ",")]
+    pub delimiter: char,
+}
+
+/// The checks a column must pass
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Rule {
+    column: String,
+    /// One of `DTYPE_NAMES`
+    dtype: Option<String>,
+    not_null: bool,
+    unique: bool,
+    min: Option<String>,
+    max: Option<String>,
+}
+
+impl Rule {
+    /// Parses an inline rule like `id:int:unique:not_null`
+    fn parse(spec: &str) -> Result<Self> {
+        let mut parts = spec.split(':');
+        let column = parts.next().unwrap_or_default().trim();
+        ensure!(!column.is_empty(), "Missing column name in rule '{spec}'");
+
+        let mut rule = Self {
+            column: column.to_string(),
+            ..Self::default()
+        };
+        for part in parts.map(str::trim) {
+            match part.split_once('=') {
+                Some(("min", value)) => rule.min = Some(value.to_string()),
+                Some(("max", value)) => rule.max = Some(value.to_string()),
+                None if part == "not_null" => rule.not_null = true,
+                None if part == "unique" => rule.unique = true,
+                None if DTYPE_NAMES.contains(&part) => rule.dtype = Some(part.to_string()),
+                _ => bail!(
+                    "Invalid check '{part}' in rule '{spec}'. Expected a type ({}), not_null, unique, min=<value> or max=<value>",
+                    DTYPE_NAMES.join(", ")
+                ),
+            }
+        }
+
+        Ok(rule)
+    }
+
+    /// Parses a rule from an object in a schema file's `columns` list
+    fn from_json(value: &Value) -> Result<Self> {
+        let object = value
+            .as_object()
+            .ok_or_else(|| anyhow!("Expected an object for each column, found {value}"))?;
+        let column = object
+            .get("name")
+            .and_then(Value::as_str)
+            .ok_or_else(|| anyhow!("Missing \"name\" in column {value}"))?;
+
+        let mut rule = Self {
+            column: column.to_string(),
+            ..Self::default()
+        };
+        for (key, value) in object {
+            match (key.as_str(), value) {
+                ("name", _) => {}
+                ("type", Value::String(dtype)) if DTYPE_NAMES.contains(&dtype.as_str()) => {
+                    rule.dtype = Some(dtype.clone());
+                }
+                ("not_null", Value::Bool(flag)) => rule.not_null = *flag,
+                ("unique", Value::Bool(flag)) => rule.unique = *flag,
+                ("min" | "max", Value::String(_) | Value::Number(_)) => {
+                    let bound = value
+                        .as_str()
+                        .map_or_else(|| value.to_string(), str::to_string);
+                    if key == "min" {
+                        rule.min = Some(bound);
+                    } else {
+                        rule.max = Some(bound);
+                    }
+                }
+                _ => bail!(
+                    "Invalid \"{key}\": {value} for column '{column}'. Expected \"type\" ({}), \"not_null\", \"unique\", \"min\" or \"max\"",
+                    DTYPE_NAMES.join(", ")
+                ),
+            }
+        }
+
+        Ok(rule)
+    }
+}
+
+/// A check a column failed, with the 0-based rows which broke it (if any)
+#[derive(Debug)]
+struct Violation {
+    column: String,
+    check: String,
+    details: String,
+    rows: Vec<usize>,
+}
+
+impl ValidateArgs {
+    pub fn validate(&self) -> Result<()> {
+        for spec in &self.require {
+            Rule::parse(spec)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn execute(&self, global: &GlobalArgs) -> Result<()> {
+        let mut rules = match &self.schema {
+            Some(path) => read_schema(path)
+                .with_context(|| format!("validate - failed to read schema from {path}"))?,
+            None => vec![],
+        };
+        for spec in &self.require {
+            rules.push(Rule::parse(spec)?);
+        }
+
+        let df =
+            read_data(&self.table, Some(self.delimiter), global).with_context(|| {
+                format!("validate - failed to read csv data from {}", self.table)
+            })?;
+
+        let names: Vec<String> = rules.iter().map(|rule| rule.column.clone()).collect();
+        let resolved = resolve_columns(&df, &names, global.ignore_column_case)?;
+        for (rule, column) in rules.iter_mut().zip(resolved) {
+            rule.column = column;
+        }
+
+        let violations = check(&df, &rules)
+            .with_context(|| format!("validate - failed to check {}", self.table))?;
+        if violations.is_empty() {
+            if !global.quiet {
+                eprintln!(
+                    "validate - {} row(s) passed {} rule(s)",
+                    df.height(),
+                    rules.len()
+                );
+            }
+            return Ok(());
+        }
+
+        write_data(report(&violations)?, global)
+            .with_context(|| "validate - failed to write data to stdout".to_string())?;
+
+        Err(Failure::Invalid.tag(anyhow!(
+            "validate - {} check(s) failed for {}",
+            violations.len(),
+            self.table
+        )))
+    }
+}
+
+/// Reads the rules from a JSON schema file like `{"columns": [{"name": "id", ...}]}`
+fn read_schema(path: &str) -> Result<Vec<Rule>> {
+    let text = fs::read_to_string(path)?;
+    let schema: Value = serde_json::from_str(&text)?;
+    let columns = schema
+        .get("columns")
+        .and_then(Value::as_array)
+        .ok_or_else(|| anyhow!("Expected a \"columns\" list of column rules"))?;
+
+    columns.iter().map(Rule::from_json).collect()
+}
+
+/// Whether a column of type `dtype` satisfies a rule's type name
+fn matches_type(name: &str, dtype: &DataType) -> bool {
+    match name {
+        "str" => dtype.is_string(),
+        "int" => dtype.is_integer(),
+        "float" => dtype.is_float(),
+        "bool" => dtype.is_bool(),
+        "date" => dtype.is_date(),
+        "datetime" => matches!(dtype, DataType::Datetime(..)),
+        _ => false,
+    }
+}
+
+/// Checks every rule against `df`, skipping the value checks of missing or mistyped columns
+fn check(df: &DataFrame, rules: &[Rule]) -> Result<Vec<Violation>> {
+    let mut violations = vec![];
+    for rule in rules {
+        let violation = |check: &str, details: String, rows: Vec<usize>| Violation {
+            column: rule.column.clone(),
+            check: check.to_string(),
+            details,
+            rows,
+        };
+
+        let Ok(column) = df.column(&rule.column) else {
+            violations.push(violation("present", "column not found".to_string(), vec![]));
+            continue;
+        };
+        if let Some(name) = &rule.dtype {
+            if !matches_type(name, column.dtype()) {
+                violations.push(violation(
+                    &format!("type {name}"),
+                    format!("found {}", column.dtype()),
+                    vec![],
+                ));
+                continue;
+            }
+        }
+
+        let values = column.as_materialized_series();
+        let mut masks = vec![];
+        if rule.not_null {
+            masks.push(("not_null".to_string(), values.is_null()));
+        }
+        if rule.unique {
+            let duplicated = df.select([rule.column.as_str()])?.is_duplicated()?;
+            masks.push(("unique".to_string(), duplicated & values.is_not_null()));
+        }
+        for (name, bound) in [("min", &rule.min), ("max", &rule.max)] {
+            let Some(bound) = bound else { continue };
+            let limit = Series::new(name.into(), [bound.as_str()])
+                .strict_cast(values.dtype())
+                .with_context(|| {
+                    format!(
+                        "{name} '{bound}' for column '{}' isn't a valid {}",
+                        rule.column,
+                        values.dtype()
+                    )
+                })?;
+            let outside = if name == "min" {
+                values.lt(&limit)?
+            } else {
+                values.gt(&limit)?
+            };
+            masks.push((format!("{name} {bound}"), outside));
+        }
+
+        for (check, mask) in masks {
+            let rows: Vec<usize> = mask
+                .into_iter()
+                .enumerate()
+                .filter_map(|(idx, failed)| failed.unwrap_or_default().then_some(idx))
+                .collect();
+            if !rows.is_empty() {
+                let details = if check == "unique" {
+                    "duplicate values"
+                } else if check == "not_null" {
+                    "null values"
+                } else {
+                    "out of range"
+                };
+                violations.push(violation(&check, details.to_string(), rows));
+            }
+        }
+    }
+
+    Ok(violations)
+}
+
+/// One row per violation, listing the first few (1-based) row numbers which broke it
+fn report(violations: &[Violation]) -> Result<DataFrame> {
+    let rows: Vec<Option<u64>> = violations
+        .iter()
+        .map(|v| (!v.rows.is_empty()).then_some(v.rows.len() as u64))
+        .collect();
+    let samples: Vec<Option<String>> = violations
+        .iter()
+        .map(|v| {
+            let mut sample: Vec<String> = v
+                .rows
+                .iter()
+                .take(ROW_LIMIT)
+                .map(|idx| (idx + 1).to_string())
+                .collect();
+            if v.rows.len() > ROW_LIMIT {
+                sample.push(format!("... and {} more", v.rows.len() - ROW_LIMIT));
+            }
+            (!sample.is_empty()).then(|| sample.join(", "))
+        })
+        .collect();
+
+    Ok(df!(
+        "column" => violations.iter().map(|v| v.column.as_str()).collect::<Vec<_>>(),
+        "check" => violations.iter().map(|v| v.check.as_str()).collect::<Vec<_>>(),
+        "details" => violations.iter().map(|v| v.details.as_str()).collect::<Vec<_>>(),
+        "failures" => rows,
+        "rows" => samples,
+    )?)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_rule() {
+        let rule = Rule::parse("id:int:unique:not_null").unwrap();
+        assert_eq!(
+            rule,
+            Rule {
+                column: "id".to_string(),
+                dtype: Some("int".to_string()),
+                not_null: true,
+                unique: true,
+                ..Rule::default()
+            }
+        );
+        let rule = Rule::parse("price:min=0:max=100").unwrap();
+        assert_eq!(rule.min.as_deref(), Some("0"));
+        assert_eq!(rule.max.as_deref(), Some("100"));
+
+        assert!(Rule::parse("id:integer").is_err());
+        assert!(Rule::parse(":int").is_err());
+    }
+
+    #[test]
+    fn test_rule_from_json() {
+        let value: Value = serde_json::from_str(
+            r#"{"name": "price", "type": "float", "not_null": true, "min": 0}"#,
+        )
+        .unwrap();
+        let rule = Rule::from_json(&value).unwrap();
+        assert_eq!(rule.dtype.as_deref(), Some("float"));
+        assert_eq!(rule.min.as_deref(), Some("0"));
+        assert!(rule.not_null);
+
+        let value: Value =
+            serde_json::from_str(r#"{"name": "id", "nullable": true}"#).unwrap();
+        assert!(Rule::from_json(&value).is_err());
+    }
+
+    #[test]
+    fn test_check() {
+        let df = df!(
+            "id" => [Some(1), Some(2), Some(2), None],
+            "price" => [5.0, -1.0, 3.0, 120.0],
+        )
+        .unwrap();
+        let rules = [
+            Rule::parse("id:int:unique:not_null").unwrap(),
+            Rule::parse("price:float:min=0:max=100").unwrap(),
+            Rule::parse("name").unwrap(),
+            Rule::parse("price:str").unwrap(),
+        ];
+
+        let violations = check(&df, &rules).unwrap();
+        let summary: Vec<(&str, &str, &[usize])> = violations
+            .iter()
+            .map(|v| (v.column.as_str(), v.check.as_str(), v.rows.as_slice()))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("id", "not_null", &[3][..]),
+                ("id", "unique", &[1, 2][..]),
+                ("price", "min 0", &[1][..]),
+                ("price", "max 100", &[3][..]),
+                ("name", "present", &[][..]),
+                ("price", "type str", &[][..]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_orders_csv() {
+        let args = ValidateArgs {
+            table: "data/orders/orders.csv".to_string(),
+            schema: Some("data/orders/schema.json".to_string()),
+            require: vec![],
+            delimiter: ',',
+        };
+
+        assert!(args.validate().is_ok());
+        assert!(args.execute(&GlobalArgs::default()).is_ok());
+    }
+}
@@ -0,0 +1,292 @@
+// Window functions (cumulative sums, ranks, lag/lead, rolling means) over partitions of a table.
+use anyhow::{Context, Result, bail, ensure};
+use clap::{Args, ValueHint};
+use polars::prelude::*;
+
+use crate::args::GlobalArgs;
+use crate::io::{read_data, resolve_columns, write_data};
+
+/// Window operations accepted by `--with`
+const OPERATIONS: [&str; 11] = [
+    "cumsum",
+    "cummin",
+    "cummax",
+    "cumcount",
+    "rank",
+    "dense_rank",
+    "lag",
+    "lead",
+    "diff",
+    "rolling_mean",
+    "rolling_sum",
+];
+
+/// Operations which take an optional `:N` period, with their default period
+const PERIODS: [(&str, usize); 5] = [
+    ("lag", 1),
+    ("lead", 1),
+    ("diff", 1),
+    ("rolling_mean", 3),
+    ("rolling_sum", 3),
+];
+
+#[derive(Args, Debug)]
+pub struct WindowArgs {
+    /// Input table (file or '-' for stdin)
+    #[arg(required = true, value_hint = ValueHint::FilePath)]
+    pub table: String,
+
+    /// Columns to partition by, each partition gets its own window (comma separated)
+    ///
+    /// Examples: --over "`customer_id`" or --over "`customer_id,region`"
+    #[arg(long, value_delimiter = ',')]
+    pub over: Vec<String>,
+
+    /// Columns to order rows by before applying the window operations (comma separated)
+    ///
+    /// The output keeps this order. Examples: --sort-by "`order_date`"
+    #[arg(long, value_delimiter = ',')]
+    pub sort_by: Vec<String>,
+
+    /// Window operations as column=operation pairs (comma separated)
+    ///
+    /// Operations: cumsum, cummin, cummax, cumcount, rank, `dense_rank`, lag, lead, diff,
+    /// `rolling_mean`, `rolling_sum`. Lag, lead, diff and the rolling operations accept a period
+    /// (e.g., lag:2 or `rolling_mean:7`).
+    ///
+    /// Examples: --with "`amount=cumsum,order_date=rank`" or --with "`amount=rolling_mean:7`"
+    #[arg(long, value_delimiter = ',')]
+    pub with: Vec<String>,
+
+    /// Delimiter for input files
+    #[arg(long, default_value = ",")]
+    pub delimiter: char,
+}
+
+impl WindowArgs {
+    pub fn validate(&self) -> Result<()> {
+        ensure!(
+            !self.with.is_empty(),
+            "At least one window operation must be specified with --with"
+        );
+
+        for spec in &self.with {
+            parse_spec(spec)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn execute(&self, global: &GlobalArgs) -> Result<()> {
+        let df =
+            read_data(&self.table, Some(self.delimiter), global).with_context(|| {
+                format!("window - failed to read csv data from {}", self.table)
+            })?;
+
+        let over = resolve_columns(&df, &self.over, global.ignore_column_case)?;
+        let sort_by = resolve_columns(&df, &self.sort_by, global.ignore_column_case)?;
+        let exprs = self
+            .with
+            .iter()
+            .map(|spec| {
+                let (column, operation, period) = parse_spec(spec)?;
+                let column =
+                    resolve_columns(&df, &[column.to_string()], global.ignore_column_case)?
+                        .remove(0);
+                let expr = window_expr(&column, operation, period)?;
+
+                Ok(if over.is_empty() {
+                    expr
+                } else {
+                    expr.over(over.iter().map(|c| col(c.as_str())).collect::<Vec<_>>())
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut lf = df.lazy();
+        if !sort_by.is_empty() {
+            lf = lf.sort(
+                &sort_by,
+                SortMultipleOptions::default().with_maintain_order(true),
+            );
+        }
+        let result = lf.with_columns(exprs).collect().with_context(|| {
+            format!(
+                "window - failed to apply window operations to {}",
+                self.table
+            )
+        })?;
+
+        write_data(result, global)
+            .with_context(|| "window - failed to write data to stdout".to_string())?;
+
+        Ok(())
+    }
+}
+
+/// Splits a `column=operation[:period]` spec, filling in the default period
+fn parse_spec(spec: &str) -> Result<(&str, &str, Option<usize>)> {
+    let Some((column, operation)) = spec.split_once('=') else {
+        bail!("Invalid window specification '{spec}'. Expected format: column=operation");
+    };
+    let (operation, period) = match operation.split_once(':') {
+        Some((operation, period)) => (operation, Some(period)),
+        None => (operation, None),
+    };
+    ensure!(
+        OPERATIONS.contains(&operation),
+        "Invalid operation '{operation}'. Valid operations: {}",
+        OPERATIONS.join(", ")
+    );
+
+    let default = PERIODS
+        .iter()
+        .find_map(|(name, default)| (*name == operation).then_some(*default));
+    let period = match (period, default) {
+        (None, default) => default,
+        (Some(period), Some(_)) => {
+            let period: usize = period
+                .parse()
+                .ok()
+                .filter(|period| *period > 0)
+                .with_context(|| {
+                    format!(
+                        "Invalid period '{period}' in '{spec}'. Expected a positive integer"
+                    )
+                })?;
+            Some(period)
+        }
+        (Some(_), None) => {
+            bail!("Operation '{operation}' in '{spec}' doesn't take a period")
+        }
+    };
+
+    Ok((column, operation, period))
+}
+
+/// Builds the expression for one window operation, named `{column}_{operation}`
+fn window_expr(column: &str, operation: &str, period: Option<usize>) -> Result<Expr> {
+    let alias = format!("{column}_{operation}");
+    let n = period.unwrap_or(1);
+    let rolling = RollingOptionsFixedWindow {
+        window_size: n,
+        min_periods: n,
+        ..RollingOptionsFixedWindow::default()
+    };
+    let rank = |method| RankOptions {
+        method,
+        descending: false,
+    };
+
+    let expr = match operation {
+        "cumsum" => col(column).cum_sum(false),
+        "cummin" => col(column).cum_min(false),
+        "cummax" => col(column).cum_max(false),
+        "cumcount" => col(column).cum_count(false),
+        "rank" => col(column).rank(rank(RankMethod::Min), None),
+        "dense_rank" => col(column).rank(rank(RankMethod::Dense), None),
+        "lag" => col(column).shift(lit(i64::try_from(n)?)),
+        "lead" => col(column).shift(lit(-i64::try_from(n)?)),
+        "diff" => col(column).diff(
+            lit(i64::try_from(n)?),
+            polars::series::ops::NullBehavior::Ignore,
+        ),
+        "rolling_mean" => col(column).rolling_mean(rolling),
+        "rolling_sum" => col(column).rolling_sum(rolling),
+        _ => bail!("Unsupported operation: {operation}"),
+    };
+
+    Ok(expr.alias(alias))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_spec() {
+        assert_eq!(
+            parse_spec("amount=cumsum").unwrap(),
+            ("amount", "cumsum", None)
+        );
+        assert_eq!(
+            parse_spec("amount=lag").unwrap(),
+            ("amount", "lag", Some(1))
+        );
+        assert_eq!(
+            parse_spec("amount=rolling_mean:7").unwrap(),
+            ("amount", "rolling_mean", Some(7))
+        );
+        assert!(parse_spec("amount").is_err());
+        assert!(parse_spec("amount=median").is_err());
+        assert!(parse_spec("amount=lag:0").is_err());
+        assert!(parse_spec("amount=rank:2").is_err());
+    }
+
+    #[test]
+    fn test_window_over_partitions() {
+        let df = df!(
+            "customer" => ["a", "b", "a", "b", "a"],
+            "amount" => [1, 10, 2, 20, 3],
+        )
+        .unwrap();
+
+        let result = df
+            .lazy()
+            .with_columns([
+                window_expr("amount", "cumsum", None)
+                    .unwrap()
+                    .over([col("customer")]),
+                window_expr("amount", "lag", Some(1))
+                    .unwrap()
+                    .over([col("customer")]),
+            ])
+            .collect()
+            .unwrap();
+
+        let cumsum: Vec<_> = result
+            .column("amount_cumsum")
+            .unwrap()
+            .i32()
+            .unwrap()
+            .into_iter()
+            .collect();
+        assert_eq!(cumsum, [Some(1), Some(10), Some(3), Some(30), Some(6)]);
+
+        let lag: Vec<_> = result
+            .column("amount_lag")
+            .unwrap()
+            .i32()
+            .unwrap()
+            .into_iter()
+            .collect();
+        assert_eq!(lag, [None, None, Some(1), Some(10), Some(2)]);
+    }
+
+    #[test]
+    fn test_validate_requires_operations() {
+        let args = WindowArgs {
+            table: "test.csv".to_string(),
+            over: vec![],
+            sort_by: vec![],
+            with: vec![],
+            delimiter: ',',
+        };
+        assert!(args.validate().is_err());
+    }
+
+    #[test]
+    fn test_window_orders_csv() {
+        let args = WindowArgs {
+            table: "data/orders/orders.csv".to_string(),
+            over: vec!["customer_id".to_string()],
+            sort_by: vec!["order_date".to_string()],
+            with: vec!["price=cumsum".to_string(), "order_date=rank".to_string()],
+            delimiter: ',',
+        };
+
+        assert!(args.validate().is_ok());
+        assert!(args.execute(&GlobalArgs::default()).is_ok());
+    }
+}